@@ -0,0 +1,40 @@
+-- Description: Create system prompts table
+-- Version: 20251201000000
+-- Create the system_prompts table, mirroring the prompts table
+CREATE TABLE system_prompts (
+    uuid TEXT NOT NULL PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    slug TEXT NOT NULL UNIQUE,
+    prompt TEXT NOT NULL,
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    last_modified INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    -- Add constraints to ensure data integrity
+    CHECK (length(name) > 0),
+    CHECK (length(slug) > 0),
+    CHECK (length(prompt) > 0)
+);
+
+-- Create trigger to update last_modified automatically
+CREATE TRIGGER update_system_prompts_last_modified
+AFTER
+UPDATE
+    ON system_prompts FOR EACH ROW
+    WHEN OLD.last_modified = NEW.last_modified BEGIN
+UPDATE
+    system_prompts
+SET
+    last_modified = unixepoch('now')
+WHERE
+    uuid = NEW.uuid;
+
+END;
+
+-- Create indexes for common lookups
+CREATE INDEX idx_system_prompts_name ON system_prompts(name);
+CREATE INDEX idx_system_prompts_slug ON system_prompts(slug);
+
+INSERT INTO system_prompts (uuid, name, slug, prompt)
+VALUES
+    ('7c9e6679-7425-40de-944b-e07fc1f90ae7', 'Helpful Assistant', 'helpful-assistant', 'You are a helpful AI assistant.'),
+    ('7c9e6679-7425-40de-944b-e07fc1f90ae8', 'Concise Assistant', 'concise-assistant', 'You are a helpful AI assistant. Answer as briefly as possible, without preamble.'),
+    ('7c9e6679-7425-40de-944b-e07fc1f90ae9', 'Senior Engineer', 'senior-engineer', 'You are a senior software engineer. Be precise, point out trade-offs and prefer simple solutions.');
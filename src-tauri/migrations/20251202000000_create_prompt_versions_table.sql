@@ -0,0 +1,65 @@
+-- Description: Keep every revision of a prompt's text
+-- Version: 20251202000000
+-- Versions are not deleted with their prompt, so old experiments
+-- can still be traced back to the text they ran.
+CREATE TABLE prompt_versions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    prompt_uuid TEXT NOT NULL,
+    version INTEGER NOT NULL,
+    prompt TEXT NOT NULL,
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    UNIQUE (prompt_uuid, version)
+);
+
+CREATE INDEX idx_prompt_versions_prompt_uuid ON prompt_versions(prompt_uuid);
+
+-- Existing prompts start at version 1
+INSERT INTO prompt_versions (prompt_uuid, version, prompt, date_created)
+SELECT uuid, 1, prompt, last_modified FROM prompts;
+
+-- Record the first version of new prompts
+CREATE TRIGGER insert_prompt_version
+AFTER
+INSERT
+    ON prompts FOR EACH ROW BEGIN
+INSERT INTO
+    prompt_versions (prompt_uuid, version, prompt)
+VALUES
+    (
+        NEW.uuid,
+        COALESCE(
+            (
+                SELECT MAX(version)
+                FROM prompt_versions
+                WHERE prompt_uuid = NEW.uuid
+            ),
+            0
+        ) + 1,
+        NEW.prompt
+    );
+
+END;
+
+-- Record a new version whenever the prompt's text changes
+CREATE TRIGGER update_prompt_version
+AFTER
+UPDATE
+    OF prompt ON prompts FOR EACH ROW
+    WHEN OLD.prompt <> NEW.prompt BEGIN
+INSERT INTO
+    prompt_versions (prompt_uuid, version, prompt)
+VALUES
+    (
+        NEW.uuid,
+        COALESCE(
+            (
+                SELECT MAX(version)
+                FROM prompt_versions
+                WHERE prompt_uuid = NEW.uuid
+            ),
+            0
+        ) + 1,
+        NEW.prompt
+    );
+
+END;
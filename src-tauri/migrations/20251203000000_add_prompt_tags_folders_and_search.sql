@@ -0,0 +1,64 @@
+-- Description: Tags, folders and full-text search for the prompt library
+-- Version: 20251203000000
+-- Folders are "/" separated paths, e.g.: "marketing/emails"
+ALTER TABLE prompts ADD COLUMN folder TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX idx_prompts_folder ON prompts(folder);
+
+CREATE TABLE prompt_tags (
+    prompt_uuid TEXT NOT NULL REFERENCES prompts(uuid) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (prompt_uuid, tag),
+    CHECK (length(tag) > 0)
+);
+
+CREATE INDEX idx_prompt_tags_tag ON prompt_tags(tag);
+
+-- Full-text index over prompt names and bodies
+CREATE VIRTUAL TABLE prompts_fts USING fts5(
+    name,
+    prompt,
+    content = 'prompts',
+    content_rowid = 'rowid'
+);
+
+INSERT INTO prompts_fts(prompts_fts) VALUES ('rebuild');
+
+-- Keep the index in sync with the prompts table
+CREATE TRIGGER prompts_fts_insert
+AFTER
+INSERT
+    ON prompts BEGIN
+INSERT INTO
+    prompts_fts (rowid, name, prompt)
+VALUES
+    (NEW.rowid, NEW.name, NEW.prompt);
+
+END;
+
+CREATE TRIGGER prompts_fts_delete
+AFTER
+DELETE ON prompts BEGIN
+INSERT INTO
+    prompts_fts (prompts_fts, rowid, name, prompt)
+VALUES
+    ('delete', OLD.rowid, OLD.name, OLD.prompt);
+
+END;
+
+CREATE TRIGGER prompts_fts_update
+AFTER
+UPDATE
+    OF name,
+    prompt ON prompts BEGIN
+INSERT INTO
+    prompts_fts (prompts_fts, rowid, name, prompt)
+VALUES
+    ('delete', OLD.rowid, OLD.name, OLD.prompt);
+
+INSERT INTO
+    prompts_fts (rowid, name, prompt)
+VALUES
+    (NEW.rowid, NEW.name, NEW.prompt);
+
+END;
@@ -0,0 +1,14 @@
+-- Description: Tags and markdown descriptions for experiments
+-- Version: 20251204000000
+ALTER TABLE experiments ADD COLUMN description TEXT NOT NULL DEFAULT '';
+
+CREATE TABLE experiment_tags (
+    experiment_uuid TEXT NOT NULL REFERENCES experiments(experiment_uuid) ON DELETE CASCADE,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (experiment_uuid, tag),
+    CHECK (length(tag) > 0)
+);
+
+CREATE INDEX idx_experiment_tags_tag ON experiment_tags(tag);
+
+CREATE INDEX idx_experiments_favorite ON experiments(is_favorite);
@@ -0,0 +1,30 @@
+-- Description: Human ratings and notes for individual inferences
+-- Version: 20251205000000
+-- Give every logged inference an id, so it can be rated
+UPDATE experiments
+SET contents = json_set(
+        contents,
+        '$.inferences',
+        (
+            SELECT json_group_array(
+                    CASE
+                        WHEN json_type(value, '$.inference_id') IS NULL THEN json_set(value, '$.inference_id', lower(hex(randomblob(16))))
+                        ELSE json(value)
+                    END
+                )
+            FROM json_each(contents, '$.inferences')
+        )
+    );
+
+CREATE TABLE inference_ratings (
+    experiment_uuid TEXT NOT NULL REFERENCES experiments(experiment_uuid) ON DELETE CASCADE,
+    inference_id TEXT NOT NULL,
+    -- 1 = thumbs up, -1 = thumbs down
+    thumb INTEGER CHECK (thumb IN (-1, 1)),
+    rating INTEGER CHECK (rating BETWEEN 1 AND 5),
+    -- JSON object of rubric criterion -> score
+    rubric TEXT NOT NULL DEFAULT '{}',
+    note TEXT NOT NULL DEFAULT '',
+    last_modified INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    PRIMARY KEY (experiment_uuid, inference_id)
+);
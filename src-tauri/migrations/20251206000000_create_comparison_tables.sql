@@ -0,0 +1,21 @@
+-- Description: Blind pairwise (A/B) comparison sessions and their votes
+-- Version: 20251206000000
+CREATE TABLE comparison_sessions (
+    uuid TEXT PRIMARY KEY NOT NULL,
+    experiment_uuid TEXT NOT NULL REFERENCES experiments(experiment_uuid) ON DELETE CASCADE,
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
+
+CREATE INDEX idx_comparison_sessions_experiment ON comparison_sessions(experiment_uuid);
+
+CREATE TABLE comparison_votes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_uuid TEXT NOT NULL REFERENCES comparison_sessions(uuid) ON DELETE CASCADE,
+    -- Inference ids, as shown on each side
+    left_inference_id TEXT NOT NULL,
+    right_inference_id TEXT NOT NULL,
+    winner TEXT NOT NULL CHECK (winner IN ('left', 'right', 'tie')),
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
+
+CREATE INDEX idx_comparison_votes_session ON comparison_votes(session_uuid);
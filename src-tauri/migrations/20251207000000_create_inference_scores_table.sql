@@ -0,0 +1,22 @@
+-- Description: Assertions on prompts and automatic scores of inferences
+-- Version: 20251207000000
+-- JSON list of assertions checked against every response to the prompt
+ALTER TABLE prompts ADD COLUMN assertions TEXT NOT NULL DEFAULT '[]';
+
+CREATE TABLE inference_scores (
+    experiment_uuid TEXT NOT NULL REFERENCES experiments(experiment_uuid) ON DELETE CASCADE,
+    inference_id TEXT NOT NULL,
+    -- e.g.: "exact_match", "rouge_l"
+    name TEXT NOT NULL,
+    -- "assertion", "judge" or "evaluator"
+    source TEXT NOT NULL,
+    -- 0 to 1; NULL when the score couldn't be computed
+    score REAL,
+    -- Pass/fail checks only
+    passed BOOLEAN,
+    detail TEXT NOT NULL DEFAULT '',
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    PRIMARY KEY (experiment_uuid, inference_id, source, name)
+);
+
+CREATE INDEX idx_inference_scores_experiment ON inference_scores(experiment_uuid);
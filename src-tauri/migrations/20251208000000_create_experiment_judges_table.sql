@@ -0,0 +1,15 @@
+-- Description: LLM-as-judge settings of experiments
+-- Version: 20251208000000
+-- Not a foreign key: the judge is set when an experiment starts,
+-- before its first inference is logged
+CREATE TABLE experiment_judges (
+    experiment_uuid TEXT PRIMARY KEY NOT NULL,
+    model TEXT NOT NULL,
+    rubric TEXT NOT NULL,
+    -- Scale the judge answers on; scores are stored normalized to 0-1
+    min_score REAL NOT NULL DEFAULT 1,
+    max_score REAL NOT NULL DEFAULT 10,
+    -- Judge each inference right after it's generated
+    auto BOOLEAN NOT NULL DEFAULT 1,
+    last_modified INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
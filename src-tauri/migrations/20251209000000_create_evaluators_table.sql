@@ -0,0 +1,24 @@
+-- Description: External evaluator commands, run on the inferences of the experiments they're attached to
+-- Version: 20251209000000
+CREATE TABLE evaluators (
+    -- Also the name of the scores the evaluator gives
+    name TEXT PRIMARY KEY NOT NULL,
+    -- Program and JSON list of arguments, run without a shell
+    command TEXT NOT NULL,
+    args TEXT NOT NULL DEFAULT '[]',
+    -- Empty to use the app's working directory
+    working_dir TEXT NOT NULL DEFAULT '',
+    -- In seconds
+    timeout INTEGER NOT NULL DEFAULT 30,
+    enabled BOOLEAN NOT NULL DEFAULT 1,
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    last_modified INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
+
+-- Not a foreign key to experiments: evaluators are attached when an
+-- experiment starts, before its first inference is logged
+CREATE TABLE experiment_evaluators (
+    experiment_uuid TEXT NOT NULL,
+    evaluator_name TEXT NOT NULL REFERENCES evaluators(name) ON DELETE CASCADE ON UPDATE CASCADE,
+    PRIMARY KEY (experiment_uuid, evaluator_name)
+);
@@ -0,0 +1,19 @@
+-- Description: Rhai scripts run as pre/post inference hooks
+-- Version: 20251210000000
+CREATE TABLE scripts (
+    name TEXT PRIMARY KEY NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    source TEXT NOT NULL,
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    last_modified INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
+
+-- Not a foreign key to experiments: scripts are attached when an
+-- experiment starts, before its first inference is logged
+CREATE TABLE experiment_scripts (
+    experiment_uuid TEXT NOT NULL,
+    script_name TEXT NOT NULL REFERENCES scripts(name) ON DELETE CASCADE ON UPDATE CASCADE,
+    -- Scripts run in ascending order
+    position INTEGER NOT NULL,
+    PRIMARY KEY (experiment_uuid, script_name)
+);
@@ -0,0 +1,21 @@
+-- Description: Reruns of inferences with the same seed and options
+-- Version: 20251211000000
+CREATE TABLE reproducibility_checks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    experiment_uuid TEXT NOT NULL REFERENCES experiments(experiment_uuid) ON DELETE CASCADE,
+    inference_id TEXT NOT NULL,
+    model TEXT NOT NULL,
+    -- As reported by the server at the time; NULL when unavailable
+    model_digest TEXT,
+    ollama_version TEXT,
+    -- The runs were sent all at once instead of one after another
+    concurrent BOOLEAN NOT NULL DEFAULT 0,
+    run_count INTEGER NOT NULL,
+    -- Runs with the same output as the logged inference
+    identical_count INTEGER NOT NULL,
+    -- JSON list of the outcome of each run
+    runs TEXT NOT NULL DEFAULT '[]',
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
+
+CREATE INDEX idx_reproducibility_checks_experiment ON reproducibility_checks(experiment_uuid);
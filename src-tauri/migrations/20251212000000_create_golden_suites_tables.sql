@@ -0,0 +1,55 @@
+-- Description: Golden-output regression suites and their runs
+-- Version: 20251212000000
+-- grid: a grid search from the app; regression: the run of a golden suite
+ALTER TABLE experiments ADD COLUMN run_type TEXT NOT NULL DEFAULT 'grid'
+    CHECK (run_type IN ('grid', 'regression'));
+
+CREATE INDEX idx_experiments_run_type ON experiments(run_type);
+
+CREATE TABLE golden_suites (
+    name TEXT PRIMARY KEY NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    -- JSON similarity thresholds a rerun must meet to pass
+    thresholds TEXT NOT NULL DEFAULT '{}',
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    last_modified INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
+
+-- Baselines are copies, so they outlive the experiment they come from
+CREATE TABLE golden_baselines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    suite_name TEXT NOT NULL REFERENCES golden_suites(name) ON DELETE CASCADE ON UPDATE CASCADE,
+    source_experiment_uuid TEXT NOT NULL,
+    source_inference_id TEXT NOT NULL,
+    -- JSON TParamIteration and config of the inference
+    parameters TEXT NOT NULL,
+    config TEXT NOT NULL,
+    -- The prompt sent to the model, after templates and pre-hooks
+    rendered_prompt TEXT NOT NULL,
+    response TEXT NOT NULL,
+    -- JSON names of the scripts whose post-hooks produced the response
+    scripts TEXT NOT NULL DEFAULT '[]',
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now')),
+    UNIQUE (suite_name, source_inference_id)
+);
+
+CREATE INDEX idx_golden_baselines_suite ON golden_baselines(suite_name);
+
+-- One per run of a suite; the reruns are the inferences of the experiment
+CREATE TABLE regression_runs (
+    experiment_uuid TEXT PRIMARY KEY NOT NULL REFERENCES experiments(experiment_uuid) ON DELETE CASCADE,
+    -- Not a foreign key: reports are kept when a suite is deleted
+    suite_name TEXT NOT NULL,
+    -- Set when the baselines ran on another model than their own
+    model TEXT,
+    ollama_version TEXT,
+    -- JSON thresholds the run was judged with
+    thresholds TEXT NOT NULL,
+    baseline_count INTEGER NOT NULL,
+    passed_count INTEGER NOT NULL,
+    -- JSON list of the comparison of each baseline
+    results TEXT NOT NULL DEFAULT '[]',
+    date_created INTEGER NOT NULL DEFAULT (unixepoch('now'))
+);
+
+CREATE INDEX idx_regression_runs_suite ON regression_runs(suite_name);
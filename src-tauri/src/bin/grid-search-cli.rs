@@ -0,0 +1,243 @@
+/*
+Runs an experiment spec without the app, e.g.: on a headless GPU box over SSH.
+
+    grid-search-cli sweep.toml --db grid_search.db --server http://gpu-box:11434
+
+Inferences are logged, scored and judged the same way as in the app, in the
+database given with --db. Prompts given by slug, evaluators and scripts are
+read from the same database, so pointing it at (a copy of) the app's
+grid_search.db shares its library and history. With --output the results
+are also exported to a CSV, JSONL or Parquet file.
+*/
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use grid_search_desktop::evaluator::attach_evaluators;
+use grid_search_desktop::export::{export_rows, write_rows, ExportFormat};
+use grid_search_desktop::hooks::attach_scripts;
+use grid_search_desktop::judge::set_judge;
+use grid_search_desktop::runner::{run_grid, Progress};
+use grid_search_desktop::scoring::get_scores;
+use grid_search_desktop::spec::ExperimentSpec;
+use grid_search_desktop::{
+    fetch_experiment, open_database, Error, ExperimentLog, IDefaultConfigs, TParamIteration,
+};
+use uuid::Uuid;
+
+const USAGE: &str = "Usage: grid-search-cli <SPEC> --db <PATH> [OPTIONS]
+
+Runs the grid search described by an experiment spec file
+(.json, .toml, .yaml or .yml).
+
+Options:
+  --server <URL>       Ollama server [default: the spec's, or
+                       http://localhost:11434]
+  --timeout <SECS>     Timeout of each request [default: the spec's, or 300]
+  --concurrency <N>    Inferences run at the same time [default: the spec's]
+  --db <PATH>          SQLite database to read the prompt library from and to
+                       log to, e.g.: the app's grid_search.db (required)
+  --output <FILE>      Also export the results to a .csv, .jsonl or .parquet
+                       file
+  -h, --help           Print this help
+
+Exits with status 2 when some inferences failed.";
+
+struct Args {
+    spec: PathBuf,
+    server_url: Option<String>,
+    request_timeout: Option<u64>,
+    concurrency: Option<usize>,
+    db: PathBuf,
+    output: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        spec: PathBuf::new(),
+        server_url: None,
+        request_timeout: None,
+        concurrency: None,
+        db: PathBuf::new(),
+        output: None,
+    };
+    let mut spec = None;
+    let mut db = None;
+
+    let mut argv = env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        let mut value = |name: &str| {
+            argv.next()
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Err(String::new()),
+            "--server" => args.server_url = Some(value(&arg)?),
+            "--timeout" => {
+                args.request_timeout = Some(
+                    value(&arg)?
+                        .parse()
+                        .ok()
+                        .filter(|secs| *secs > 0)
+                        .ok_or_else(|| "--timeout must be a number of seconds".to_string())?,
+                )
+            }
+            "--concurrency" => {
+                args.concurrency = Some(
+                    value(&arg)?
+                        .parse()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| "--concurrency must be a positive number".to_string())?,
+                )
+            }
+            "--db" => db = Some(PathBuf::from(value(&arg)?)),
+            "--output" => args.output = Some(value(&arg)?),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {}", arg)),
+            _ if spec.is_none() => spec = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+
+    args.spec = spec.ok_or_else(|| "Missing the spec file".to_string())?;
+    args.db = db.ok_or_else(|| "Missing --db, the database to log to".to_string())?;
+    Ok(args)
+}
+
+fn describe(params: &TParamIteration) -> String {
+    let mut description = format!(
+        "{} | temp {}, top_k {}, top_p {}, repeat_penalty {}, seed {}",
+        params.model,
+        params.temperature,
+        params.top_k,
+        params.top_p,
+        params.repeat_penalty,
+        params.seed
+    );
+    if let Some(think) = params.think {
+        description.push_str(&format!(", think {}", think));
+    }
+    if let Some(row) = params.dataset_row {
+        description.push_str(&format!(", row {}", row));
+    }
+    description
+}
+
+fn print_progress(done: usize, total: usize, progress: &Progress) {
+    let width = total.to_string().len();
+    let outcome = match &progress.result {
+        Ok(inference) => format!(
+            "ok in {:.1}s, {} tokens",
+            progress.elapsed.as_secs_f64(),
+            inference.result.eval_count.unwrap_or_default()
+        ),
+        Err(err) => format!("failed in {:.1}s: {}", progress.elapsed.as_secs_f64(), err),
+    };
+    println!(
+        "[{:>width$}/{}] {} | {}",
+        done,
+        total,
+        describe(&progress.params),
+        outcome,
+        width = width
+    );
+}
+
+async fn run(args: Args) -> Result<usize, Error> {
+    let spec = ExperimentSpec::load(&args.spec)?;
+
+    let db_path = &args.db;
+    if let Some(dir) = db_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let pool = open_database(db_path).await?;
+
+    // Prompts given by slug and the evaluators come from the database
+    let prompts = spec.resolve_prompts(&pool).await?;
+    spec.check_evaluators(&pool).await?;
+    let dataset = spec.load_dataset(&prompts)?;
+
+    // The judge, evaluators and scripts are set before the first inference,
+    // as in the app
+    let experiment_uuid = Uuid::new_v4().to_string();
+    if let Some(judge) = &spec.judge {
+        set_judge(&pool, &experiment_uuid, judge).await?;
+    }
+    attach_evaluators(&pool, &experiment_uuid, &spec.evaluators).await?;
+    attach_scripts(&pool, &experiment_uuid, &spec.scripts).await?;
+    let iterations = spec.iterations(&experiment_uuid, &prompts, dataset.as_ref());
+    let total = iterations.len();
+    let concurrency = args.concurrency.unwrap_or(spec.concurrency);
+
+    // Options given on the command line override the spec's server profile
+    let mut config = spec.server.apply(IDefaultConfigs {
+        request_timeout: 300,
+        server_url: "http://localhost:11434".to_string(),
+        // Each inference sends the system prompt of its grid cell
+        system_prompt: String::new(),
+        default_options: HashMap::new(),
+    });
+    if let Some(server_url) = args.server_url {
+        config.server_url = server_url;
+    }
+    if let Some(request_timeout) = args.request_timeout {
+        config.request_timeout = request_timeout;
+    }
+
+    println!("Experiment {}", experiment_uuid);
+    println!(
+        "{} inferences on {}, {} at a time",
+        total, config.server_url, concurrency
+    );
+    println!("Logging to {}", db_path.display());
+
+    let mut done = 0;
+    let failed = run_grid(&pool, &config, iterations, concurrency, |progress| {
+        done += 1;
+        print_progress(done, total, &progress);
+    })
+    .await;
+
+    let experiment = fetch_experiment(&pool, &experiment_uuid).await?;
+    println!(
+        "Finished {}: {} inferences, {} failed",
+        experiment.name, total, failed
+    );
+
+    if let Some(output) = &args.output {
+        let log = ExperimentLog::parse(&experiment.contents)?;
+        let scores = get_scores(&pool, &experiment_uuid).await?;
+        let rows = export_rows(&log, &experiment.name, &[], &scores);
+        write_rows(output, ExportFormat::from_path(output)?, &rows)?;
+        println!("Exported {} inferences to {}", rows.len(), output);
+    }
+
+    pool.close().await;
+    Ok(failed)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(err) if err.is_empty() => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(args).await {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::from(2),
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
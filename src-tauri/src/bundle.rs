@@ -0,0 +1,96 @@
+/*
+Prompt bundles.
+
+The prompt library can be exported to, and imported from, a versioned
+JSON or YAML file so curated prompt sets can be shared (e.g.: through git).
+The format is chosen from the file extension.
+*/
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::Error;
+
+/// Bump when the bundle format changes in a non backwards compatible way
+pub const PROMPT_BUNDLE_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromptBundle {
+    pub bundle_version: u32,
+    pub exported: String,
+    pub prompts: Vec<BundledPrompt>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundledPrompt {
+    pub uuid: String,
+    pub name: String,
+    pub slug: String,
+    pub body: String,
+    #[serde(default)]
+    pub folder: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub date_created: Option<i64>,  // Unix timestamp
+    pub last_modified: Option<i64>, // Unix timestamp
+}
+
+/// What to do when an imported prompt has the same uuid, name or slug as a stored one
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BundleFormat {
+    Json,
+    Yaml,
+}
+
+fn bundle_format(path: &str) -> Result<BundleFormat, Error> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "json" => Ok(BundleFormat::Json),
+        "yaml" | "yml" => Ok(BundleFormat::Yaml),
+        _ => Err(Error::StringError(format!(
+            "Unsupported bundle format: '{}'. Use a .json, .yaml or .yml file.",
+            path
+        ))),
+    }
+}
+
+pub fn read_bundle(path: &str) -> Result<PromptBundle, Error> {
+    let contents = fs::read_to_string(path)?;
+
+    let bundle: PromptBundle = match bundle_format(path)? {
+        BundleFormat::Json => serde_json::from_str(&contents)?,
+        BundleFormat::Yaml => serde_yaml::from_str(&contents)?,
+    };
+
+    if bundle.bundle_version > PROMPT_BUNDLE_VERSION {
+        return Err(Error::StringError(format!(
+            "Bundle version {} is newer than the supported version ({}). Please update the app.",
+            bundle.bundle_version, PROMPT_BUNDLE_VERSION
+        )));
+    }
+
+    Ok(bundle)
+}
+
+pub fn write_bundle(path: &str, bundle: &PromptBundle) -> Result<(), Error> {
+    let contents = match bundle_format(path)? {
+        BundleFormat::Json => serde_json::to_string_pretty(bundle)?,
+        BundleFormat::Yaml => serde_yaml::to_string(bundle)?,
+    };
+
+    fs::write(path, contents)?;
+    Ok(())
+}
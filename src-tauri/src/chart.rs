@@ -0,0 +1,532 @@
+/*
+Charts of inference fields across parameter sweeps.
+
+Charts are drawn with plotters, as SVG (also embedded in reports) or PNG.
+A chart maps fields of the flattened export rows to its axes:
+
+* line: the mean of `y` for each value of `x`, one line per series.
+* scatter: one point per inference.
+* bar: the mean of `y` for each value of `x`.
+* heatmap: the mean of `value` for each (`x`, `y`) pair, e.g.: tokens per
+  second over temperature and top_p.
+
+Besides the export columns, `response_length` (characters) and `failed`
+(1 for failed inferences) can be charted.
+
+Text is laid out with the Geist font of the app, embedded in the binary, so
+charts don't depend on the fonts installed on the system.
+*/
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::export::ExportRow;
+use crate::Error;
+
+const FONT: &str = "sans-serif";
+const FONT_DATA: &[u8] = include_bytes!("../../public/fonts/Geist/GeistVariableVF.ttf");
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartKind {
+    Line,
+    Scatter,
+    Bar,
+    Heatmap,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartFormat {
+    Svg,
+    Png,
+}
+
+impl ChartFormat {
+    /// Picks the format from the file extension
+    pub fn from_path(path: &str) -> Result<Self, Error> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "svg" => Ok(ChartFormat::Svg),
+            "png" => Ok(ChartFormat::Png),
+            _ => Err(Error::StringError(format!(
+                "Unsupported chart format: '{}'. Use a .svg or .png file.",
+                path
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChartSpec {
+    pub kind: ChartKind,
+    pub x: String,
+    pub y: String,
+    // Splits lines and points by this field (e.g.: "model")
+    #[serde(default)]
+    pub series: Option<String>,
+    // Heatmaps only: the field shown as the cell color
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+}
+
+fn default_width() -> u32 {
+    800
+}
+
+fn default_height() -> u32 {
+    500
+}
+
+impl ChartSpec {
+    fn title(&self) -> String {
+        if let Some(title) = self.title.as_ref().filter(|t| !t.is_empty()) {
+            return title.clone();
+        }
+        match (self.kind, &self.value, &self.series) {
+            (ChartKind::Heatmap, Some(value), _) => {
+                format!("{} by {} and {}", value, self.x, self.y)
+            }
+            (_, _, Some(series)) => format!("{} vs {} by {}", self.y, self.x, series),
+            _ => format!("{} vs {}", self.y, self.x),
+        }
+    }
+}
+
+/// Renders a chart as an SVG document.
+pub fn chart_svg(rows: &[ExportRow], spec: &ChartSpec) -> Result<String, Error> {
+    let records = chart_records(rows, spec)?;
+    let mut svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut svg, (spec.width, spec.height)).into_drawing_area();
+        draw_chart(&root, &records, spec)?;
+        root.present().map_err(plot_error)?;
+    }
+    Ok(svg)
+}
+
+/// Renders a chart to `path` in the given format.
+pub fn write_chart(
+    path: &str,
+    format: ChartFormat,
+    rows: &[ExportRow],
+    spec: &ChartSpec,
+) -> Result<(), Error> {
+    match format {
+        ChartFormat::Svg => std::fs::write(path, chart_svg(rows, spec)?)?,
+        ChartFormat::Png => {
+            let records = chart_records(rows, spec)?;
+            let root = BitMapBackend::new(path, (spec.width, spec.height)).into_drawing_area();
+            draw_chart(&root, &records, spec)?;
+            root.present().map_err(plot_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Registers the embedded font under `FONT`, once.
+fn register_font() -> Result<(), Error> {
+    static REGISTERED: OnceLock<bool> = OnceLock::new();
+    let registered = REGISTERED
+        .get_or_init(|| plotters::style::register_font(FONT, FontStyle::Normal, FONT_DATA).is_ok());
+    match registered {
+        true => Ok(()),
+        false => Err(Error::StringError(
+            "Could not load the chart font".to_string(),
+        )),
+    }
+}
+
+fn plot_error(err: impl std::fmt::Display) -> Error {
+    Error::StringError(format!("Could not draw the chart: {}", err))
+}
+
+/// The export rows as JSON objects, plus the derived fields.
+/// Fails if the chart uses a field the rows don't have.
+fn chart_records(rows: &[ExportRow], spec: &ChartSpec) -> Result<Vec<Map<String, Value>>, Error> {
+    let records = rows
+        .iter()
+        .map(|row| {
+            let Value::Object(mut record) = serde_json::to_value(row)? else {
+                return Err(Error::StringError("Unexpected export row".to_string()));
+            };
+            let length = row.response.as_ref().map(|r| r.chars().count());
+            record.insert("response_length".into(), length.into());
+            record.insert("failed".into(), (row.error.is_some() as u8).into());
+            Ok(record)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let field = |name: &str| check_field(&records, name);
+    field(&spec.x)?;
+    field(&spec.y)?;
+    if let Some(series) = &spec.series {
+        field(series)?;
+    }
+    if spec.kind == ChartKind::Heatmap {
+        match &spec.value {
+            Some(value) => field(value)?,
+            None => {
+                return Err(Error::StringError(
+                    "Heatmaps need a 'value' field for the cell color".to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(records)
+}
+
+fn draw_chart<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    records: &[Map<String, Value>],
+    spec: &ChartSpec,
+) -> Result<(), Error> {
+    register_font()?;
+    root.fill(&WHITE).map_err(plot_error)?;
+
+    match spec.kind {
+        ChartKind::Line | ChartKind::Scatter => draw_xy(root, records, spec),
+        ChartKind::Bar => draw_bars(root, records, spec),
+        ChartKind::Heatmap => {
+            let value = spec.value.as_deref().unwrap_or_default();
+            draw_heatmap(root, records, spec, value)
+        }
+    }
+}
+
+fn check_field(records: &[Map<String, Value>], name: &str) -> Result<(), Error> {
+    match records.first() {
+        Some(record) if !record.contains_key(name) => {
+            let fields: Vec<&str> = record.keys().map(String::as_str).collect();
+            Err(Error::StringError(format!(
+                "Unknown field '{}'. Use one of: {}",
+                name,
+                fields.join(", ")
+            )))
+        }
+        Some(_) => Ok(()),
+        None => Err(Error::StringError(
+            "The experiment has no inferences to chart".to_string(),
+        )),
+    }
+}
+
+fn number(record: &Map<String, Value>, field: &str) -> Option<f64> {
+    record.get(field).and_then(Value::as_f64)
+}
+
+fn label(record: &Map<String, Value>, field: &str) -> String {
+    match record.get(field) {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => "-".to_string(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// Distinct labels of a field, numbers sorted numerically, then text in order of appearance
+fn categories(records: &[Map<String, Value>], field: &str) -> Vec<String> {
+    let mut values: Vec<(Option<f64>, String)> = vec![];
+    for record in records {
+        let category = (number(record, field), label(record, field));
+        if !values.iter().any(|(_, l)| *l == category.1) {
+            values.push(category);
+        }
+    }
+    values.sort_by(|a, b| match (a.0, b.0) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+    values.into_iter().map(|(_, l)| l).collect()
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+// Padded axis range; flat data still gets a visible range
+fn range(values: impl Iterator<Item = f64> + Clone, from_zero: bool) -> std::ops::Range<f64> {
+    let min = values.clone().fold(f64::INFINITY, f64::min);
+    let max = values.fold(f64::NEG_INFINITY, f64::max);
+    if !min.is_finite() {
+        return 0.0..1.0;
+    }
+    let min = if from_zero { min.min(0.0) } else { min };
+    let pad = if max > min { (max - min) * 0.05 } else { 1.0 };
+    let low = if from_zero && min >= 0.0 {
+        0.0
+    } else {
+        min - pad
+    };
+    low..max + pad
+}
+
+fn draw_xy<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    records: &[Map<String, Value>],
+    spec: &ChartSpec,
+) -> Result<(), Error> {
+    let series_names = match &spec.series {
+        Some(series) => categories(records, series),
+        None => vec![String::new()],
+    };
+
+    // (series, points), sorted by x
+    let mut series: Vec<(String, Vec<(f64, f64)>)> = vec![];
+    for name in series_names {
+        let in_series: Vec<(f64, f64)> = records
+            .iter()
+            .filter(|r| spec.series.as_ref().is_none_or(|s| label(r, s) == name))
+            .filter_map(|r| Some((number(r, &spec.x)?, number(r, &spec.y)?)))
+            .collect();
+
+        let mut points = if spec.kind == ChartKind::Line {
+            let mut xs: Vec<f64> = in_series.iter().map(|(x, _)| *x).collect();
+            xs.sort_by(f64::total_cmp);
+            xs.dedup();
+            xs.into_iter()
+                .filter_map(|x| {
+                    let y = mean(in_series.iter().filter(|p| p.0 == x).map(|p| p.1))?;
+                    Some((x, y))
+                })
+                .collect()
+        } else {
+            in_series
+        };
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if !points.is_empty() {
+            series.push((name, points));
+        }
+    }
+    if series.is_empty() {
+        return Err(Error::StringError(format!(
+            "No inference has numeric values for '{}' and '{}'",
+            spec.x, spec.y
+        )));
+    }
+
+    let all_points = series.iter().flat_map(|(_, points)| points.iter());
+    let x_range = range(all_points.clone().map(|p| p.0), false);
+    let y_range = range(all_points.map(|p| p.1), true);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(spec.title(), (FONT, 20))
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(x_range, y_range)
+        .map_err(plot_error)?;
+    chart
+        .configure_mesh()
+        .x_desc(spec.x.as_str())
+        .y_desc(spec.y.as_str())
+        .draw()
+        .map_err(plot_error)?;
+
+    for (idx, (name, points)) in series.iter().enumerate() {
+        let color = Palette99::pick(idx).to_rgba();
+        let drawn = if spec.kind == ChartKind::Line {
+            chart
+                .draw_series(LineSeries::new(points.clone(), color.stroke_width(2)))
+                .map_err(plot_error)?;
+            chart.draw_series(points.iter().map(|p| Circle::new(*p, 3, color.filled())))
+        } else {
+            chart.draw_series(points.iter().map(|p| Circle::new(*p, 3, color.filled())))
+        };
+        let drawn = drawn.map_err(plot_error)?;
+        if !name.is_empty() {
+            drawn.label(name.as_str()).legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+            });
+        }
+    }
+
+    if spec.series.is_some() {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()
+            .map_err(plot_error)?;
+    }
+    Ok(())
+}
+
+fn draw_bars<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    records: &[Map<String, Value>],
+    spec: &ChartSpec,
+) -> Result<(), Error> {
+    // With a series, each bar is an (x, series) pair
+    let bar_label = |r: &Map<String, Value>| match &spec.series {
+        Some(series) => format!("{} / {}", label(r, &spec.x), label(r, series)),
+        None => label(r, &spec.x),
+    };
+    let mut names: Vec<String> = vec![];
+    for x in categories(records, &spec.x) {
+        for record in records.iter().filter(|r| label(r, &spec.x) == x) {
+            let name = bar_label(record);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    let bars: Vec<f64> = names
+        .iter()
+        .map(|name| {
+            let values = records
+                .iter()
+                .filter(|r| bar_label(r) == *name)
+                .filter_map(|r| number(r, &spec.y));
+            mean(values).unwrap_or(0.0)
+        })
+        .collect();
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(spec.title(), (FONT, 20))
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            (0..names.len() as i32 - 1).into_segmented(),
+            range(bars.iter().copied(), true),
+        )
+        .map_err(plot_error)?;
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_labels(names.len())
+        .x_label_formatter(&|v| match v {
+            SegmentValue::CenterOf(i) => names.get(*i as usize).cloned().unwrap_or_default(),
+            _ => String::new(),
+        })
+        .x_desc(spec.x.as_str())
+        .y_desc(spec.y.as_str())
+        .draw()
+        .map_err(plot_error)?;
+
+    let color = Palette99::pick(0).to_rgba();
+    chart
+        .draw_series(bars.iter().enumerate().map(|(i, v)| {
+            let i = i as i32;
+            let mut bar = Rectangle::new(
+                [
+                    (SegmentValue::Exact(i), 0.0),
+                    (SegmentValue::Exact(i + 1), *v),
+                ],
+                color.filled(),
+            );
+            bar.set_margin(0, 0, 8, 8);
+            bar
+        }))
+        .map_err(plot_error)?;
+    Ok(())
+}
+
+fn draw_heatmap<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    records: &[Map<String, Value>],
+    spec: &ChartSpec,
+    value: &str,
+) -> Result<(), Error> {
+    let xs = categories(records, &spec.x);
+    let ys = categories(records, &spec.y);
+
+    // (x index, y index, mean value)
+    let mut cells: Vec<(i32, i32, f64)> = vec![];
+    for (i, x) in xs.iter().enumerate() {
+        for (j, y) in ys.iter().enumerate() {
+            let values = records
+                .iter()
+                .filter(|r| label(r, &spec.x) == *x && label(r, &spec.y) == *y)
+                .filter_map(|r| number(r, value));
+            if let Some(mean) = mean(values) {
+                cells.push((i as i32, j as i32, mean));
+            }
+        }
+    }
+    let min = cells.iter().map(|c| c.2).fold(f64::INFINITY, f64::min);
+    let max = cells.iter().map(|c| c.2).fold(f64::NEG_INFINITY, f64::max);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(spec.title(), (FONT, 20))
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d(
+            (0..xs.len() as i32 - 1).into_segmented(),
+            (0..ys.len() as i32 - 1).into_segmented(),
+        )
+        .map_err(plot_error)?;
+    chart
+        .configure_mesh()
+        .disable_mesh()
+        .x_labels(xs.len())
+        .y_labels(ys.len())
+        .x_label_formatter(&|v| match v {
+            SegmentValue::CenterOf(i) => xs.get(*i as usize).cloned().unwrap_or_default(),
+            _ => String::new(),
+        })
+        .y_label_formatter(&|v| match v {
+            SegmentValue::CenterOf(j) => ys.get(*j as usize).cloned().unwrap_or_default(),
+            _ => String::new(),
+        })
+        .x_desc(spec.x.as_str())
+        .y_desc(spec.y.as_str())
+        .draw()
+        .map_err(plot_error)?;
+
+    chart
+        .draw_series(cells.iter().map(|&(i, j, v)| {
+            let t = if max > min {
+                (v - min) / (max - min)
+            } else {
+                0.5
+            };
+            Rectangle::new(
+                [
+                    (SegmentValue::Exact(i), SegmentValue::Exact(j)),
+                    (SegmentValue::Exact(i + 1), SegmentValue::Exact(j + 1)),
+                ],
+                heat_color(t).filled(),
+            )
+        }))
+        .map_err(plot_error)?;
+
+    let text = TextStyle::from((FONT, 13).into_font()).pos(Pos::new(HPos::Center, VPos::Center));
+    chart
+        .draw_series(cells.iter().map(|&(i, j, v)| {
+            Text::new(
+                format!("{:.2}", v),
+                (SegmentValue::CenterOf(i), SegmentValue::CenterOf(j)),
+                text.clone(),
+            )
+        }))
+        .map_err(plot_error)?;
+    Ok(())
+}
+
+// Light yellow (low) to dark blue (high)
+fn heat_color(t: f64) -> RGBColor {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: f64, b: f64| (a + (b - a) * t).round() as u8;
+    RGBColor(lerp(255.0, 37.0), lerp(247.0, 99.0), lerp(188.0, 235.0))
+}
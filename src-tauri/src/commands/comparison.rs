@@ -0,0 +1,371 @@
+use crate::db::DatabaseState;
+use rand::seq::SliceRandom;
+use serde::Serialize;
+use sqlx::{FromRow, Pool, Sqlite};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+use grid_search_desktop::ranking::{bradley_terry, elo_ratings, Preference, VoteWinner};
+use grid_search_desktop::summary::ParamCombination;
+use grid_search_desktop::{fetch_experiment, Error, ExperimentLog, LoggedInference};
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ComparisonSession {
+    pub uuid: String,
+    pub experiment_uuid: String,
+    pub date_created: i64, // Unix timestamp
+    pub vote_count: i64,
+}
+
+/// Two responses to the same prompt. Only opaque inference ids are sent,
+/// so the model and parameters of each side stay hidden.
+#[derive(Debug, Serialize)]
+pub struct BlindPair {
+    pub session_uuid: String,
+    pub system_prompt: String,
+    pub prompt: String,
+    pub left_id: String,
+    pub left_response: String,
+    pub right_id: String,
+    pub right_response: String,
+    // Pairs not yet voted on in this session, including this one
+    pub remaining: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardEntry {
+    pub combination: ParamCombination,
+    pub elo: f64,
+    pub bradley_terry: f64,
+    pub wins: usize,
+    pub losses: usize,
+    pub ties: usize,
+}
+
+#[derive(Debug, FromRow)]
+struct Vote {
+    left_inference_id: String,
+    right_inference_id: String,
+    winner: VoteWinner,
+}
+
+/// Starts a blind comparison session over the responses of an experiment.
+#[tauri::command]
+pub async fn start_comparison_session(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<ComparisonSession, Error> {
+    let pool = &state.0;
+
+    let log = ExperimentLog::parse(&fetch_experiment(pool, &experiment_uuid).await?.contents)?;
+    if comparable_pairs(&log).is_empty() {
+        return Err(Error::StringError(
+            "This experiment has no prompt answered by two different model/parameter configurations"
+                .to_string(),
+        ));
+    }
+
+    let uuid = Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO comparison_sessions (uuid, experiment_uuid) VALUES ($1, $2)")
+        .bind(&uuid)
+        .bind(&experiment_uuid)
+        .execute(pool)
+        .await?;
+
+    get_session(pool, &uuid).await
+}
+
+/// Returns a random pair not yet voted on in the session, or None when all are done.
+#[tauri::command]
+pub async fn next_comparison_pair(
+    state: tauri::State<'_, DatabaseState>,
+    session_uuid: String,
+) -> Result<Option<BlindPair>, Error> {
+    let pool = &state.0;
+    let session = get_session(pool, &session_uuid).await?;
+    let log = ExperimentLog::parse(
+        &fetch_experiment(pool, &session.experiment_uuid)
+            .await?
+            .contents,
+    )?;
+
+    let voted: HashSet<(String, String)> = session_votes(pool, &session_uuid)
+        .await?
+        .into_iter()
+        .map(|v| pair_key(&v.left_inference_id, &v.right_inference_id))
+        .collect();
+
+    let pending: Vec<(&LoggedInference, &LoggedInference)> = comparable_pairs(&log)
+        .into_iter()
+        .filter(|(a, b)| !voted.contains(&pair_key(&a.inference_id, &b.inference_id)))
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    let Some(&(a, b)) = pending.choose(&mut rng) else {
+        return Ok(None);
+    };
+    // Randomize the sides too, so position doesn't give the configuration away
+    let (left, right) = if rand::random::<bool>() {
+        (a, b)
+    } else {
+        (b, a)
+    };
+
+    Ok(Some(BlindPair {
+        session_uuid,
+        system_prompt: left.parameters.system_prompt.clone(),
+        prompt: sent_prompt(left).to_string(),
+        left_id: left.inference_id.clone(),
+        left_response: response_text(left),
+        right_id: right.inference_id.clone(),
+        right_response: response_text(right),
+        remaining: pending.len(),
+    }))
+}
+
+#[tauri::command]
+pub async fn record_comparison_vote(
+    state: tauri::State<'_, DatabaseState>,
+    session_uuid: String,
+    left_id: String,
+    right_id: String,
+    winner: VoteWinner,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let session = get_session(pool, &session_uuid).await?;
+    let log = ExperimentLog::parse(
+        &fetch_experiment(pool, &session.experiment_uuid)
+            .await?
+            .contents,
+    )?;
+
+    for id in [&left_id, &right_id] {
+        if !log.inferences.iter().any(|inf| &inf.inference_id == id) {
+            return Err(Error::StringError(format!(
+                "Inference {} not found in experiment {}",
+                id, session.experiment_uuid
+            )));
+        }
+    }
+
+    // Only responses to the same prompt from different configurations are compared
+    let key = pair_key(&left_id, &right_id);
+    if !comparable_pairs(&log)
+        .iter()
+        .any(|(a, b)| pair_key(&a.inference_id, &b.inference_id) == key)
+    {
+        return Err(Error::StringError(format!(
+            "Inferences {} and {} don't answer the same prompt with different configurations",
+            left_id, right_id
+        )));
+    }
+    if session_votes(pool, &session_uuid)
+        .await?
+        .iter()
+        .any(|v| pair_key(&v.left_inference_id, &v.right_inference_id) == key)
+    {
+        return Err(Error::StringError(format!(
+            "This pair was already voted on in session {}",
+            session_uuid
+        )));
+    }
+
+    let stmt = r#"
+        INSERT INTO comparison_votes (session_uuid, left_inference_id, right_inference_id, winner)
+        VALUES ($1, $2, $3, $4)
+    "#;
+    sqlx::query(stmt)
+        .bind(&session_uuid)
+        .bind(&left_id)
+        .bind(&right_id)
+        .bind(winner)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_comparison_sessions(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Vec<ComparisonSession>, Error> {
+    let stmt = format!(
+        "{} WHERE s.experiment_uuid = $1 GROUP BY s.uuid ORDER BY s.date_created DESC",
+        SESSION_SELECT
+    );
+
+    let pool = &state.0;
+    Ok(sqlx::query_as::<_, ComparisonSession>(&stmt)
+        .bind(&experiment_uuid)
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Ranks the model/parameter configurations of an experiment
+/// from the votes of all its comparison sessions.
+#[tauri::command]
+pub async fn comparison_leaderboard(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Vec<LeaderboardEntry>, Error> {
+    let pool = &state.0;
+    let log = ExperimentLog::parse(&fetch_experiment(pool, &experiment_uuid).await?.contents)?;
+
+    let stmt = r#"
+        SELECT v.left_inference_id, v.right_inference_id, v.winner
+        FROM comparison_votes v
+        JOIN comparison_sessions s ON s.uuid = v.session_uuid
+        WHERE s.experiment_uuid = $1
+        ORDER BY v.id ASC
+    "#;
+    let votes = sqlx::query_as::<_, Vote>(stmt)
+        .bind(&experiment_uuid)
+        .fetch_all(pool)
+        .await?;
+
+    // Index the configurations that were voted on
+    let configs: HashMap<&str, ParamCombination> = log
+        .inferences
+        .iter()
+        .map(|inf| {
+            (
+                inf.inference_id.as_str(),
+                ParamCombination::from(&inf.parameters),
+            )
+        })
+        .collect();
+    let mut combinations: Vec<ParamCombination> = vec![];
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut config_index = |id: &str| -> Option<usize> {
+        let combination = configs.get(id)?;
+        Some(*index.entry(combination.key()).or_insert_with(|| {
+            combinations.push(combination.clone());
+            combinations.len() - 1
+        }))
+    };
+
+    let preferences: Vec<Preference> = votes
+        .iter()
+        .filter_map(|v| {
+            Some(Preference {
+                left: config_index(&v.left_inference_id)?,
+                right: config_index(&v.right_inference_id)?,
+                winner: v.winner,
+            })
+        })
+        .collect();
+
+    let n = combinations.len();
+    let elo = elo_ratings(n, &preferences);
+    let bt = bradley_terry(n, &preferences);
+
+    let mut entries: Vec<LeaderboardEntry> = combinations
+        .into_iter()
+        .enumerate()
+        .map(|(i, combination)| {
+            let (mut wins, mut losses, mut ties) = (0, 0, 0);
+            for pref in preferences.iter().filter(|p| p.left == i || p.right == i) {
+                match (pref.winner, pref.left == i) {
+                    (VoteWinner::Tie, _) => ties += 1,
+                    (VoteWinner::Left, true) | (VoteWinner::Right, false) => wins += 1,
+                    _ => losses += 1,
+                }
+            }
+            LeaderboardEntry {
+                combination,
+                elo: elo[i],
+                bradley_terry: bt[i],
+                wins,
+                losses,
+                ties,
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.bradley_terry.total_cmp(&a.bradley_terry));
+
+    Ok(entries)
+}
+
+const SESSION_SELECT: &str = r#"
+    SELECT
+        s.uuid,
+        s.experiment_uuid,
+        s.date_created,
+        COUNT(v.id) AS vote_count
+    FROM comparison_sessions s
+    LEFT JOIN comparison_votes v ON v.session_uuid = s.uuid
+"#;
+
+async fn get_session(pool: &Pool<Sqlite>, uuid: &str) -> Result<ComparisonSession, Error> {
+    let stmt = format!("{} WHERE s.uuid = $1 GROUP BY s.uuid", SESSION_SELECT);
+
+    sqlx::query_as::<_, ComparisonSession>(&stmt)
+        .bind(uuid)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::StringError(format!("Comparison session {} not found", uuid)))
+}
+
+async fn session_votes(pool: &Pool<Sqlite>, session_uuid: &str) -> Result<Vec<Vote>, Error> {
+    let stmt = r#"
+        SELECT left_inference_id, right_inference_id, winner
+        FROM comparison_votes
+        WHERE session_uuid = $1
+    "#;
+
+    Ok(sqlx::query_as::<_, Vote>(stmt)
+        .bind(session_uuid)
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Pairs of successful responses to the same prompt from different configurations
+fn comparable_pairs(log: &ExperimentLog) -> Vec<(&LoggedInference, &LoggedInference)> {
+    let mut by_prompt: HashMap<(&str, &str), Vec<(&LoggedInference, String)>> = HashMap::new();
+    for inference in log.inferences.iter().filter(|inf| inf.result.is_some()) {
+        by_prompt
+            .entry((&inference.parameters.system_prompt, sent_prompt(inference)))
+            .or_default()
+            .push((
+                inference,
+                ParamCombination::from(&inference.parameters).key(),
+            ));
+    }
+
+    let mut pairs = vec![];
+    for group in by_prompt.values() {
+        for (i, (a, config_a)) in group.iter().enumerate() {
+            for (b, config_b) in &group[i + 1..] {
+                if config_a != config_b {
+                    pairs.push((*a, *b));
+                }
+            }
+        }
+    }
+    pairs
+}
+
+// The same pair of inferences, regardless of the side they were shown on
+fn pair_key(a: &str, b: &str) -> (String, String) {
+    if a <= b {
+        (a.to_string(), b.to_string())
+    } else {
+        (b.to_string(), a.to_string())
+    }
+}
+
+fn sent_prompt(inference: &LoggedInference) -> &str {
+    inference
+        .rendered_prompt
+        .as_deref()
+        .unwrap_or(&inference.parameters.prompt)
+}
+
+fn response_text(inference: &LoggedInference) -> String {
+    inference
+        .result
+        .as_ref()
+        .map(|res| res.response.clone())
+        .unwrap_or_default()
+}
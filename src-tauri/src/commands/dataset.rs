@@ -0,0 +1,20 @@
+use grid_search_desktop::dataset::{self, Dataset};
+use grid_search_desktop::Error;
+
+#[tauri::command]
+pub async fn load_dataset(path: String) -> Result<Dataset, Error> {
+    let dataset = dataset::load_dataset(&path)?;
+    println!(
+        "Loaded dataset {} ({} rows, columns: {:?})",
+        path,
+        dataset.rows.len(),
+        dataset.columns
+    );
+    Ok(dataset)
+}
+
+#[tauri::command]
+pub async fn validate_dataset_prompts(path: String, prompts: Vec<String>) -> Result<(), Error> {
+    let dataset = dataset::load_dataset(&path)?;
+    dataset::validate_prompts(&prompts, &dataset)
+}
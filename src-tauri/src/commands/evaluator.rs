@@ -0,0 +1,147 @@
+use crate::db::DatabaseState;
+
+use grid_search_desktop::evaluator::{
+    attach_evaluators, evaluate_inference, experiment_evaluators, Evaluator, EvaluatorInput,
+    EVALUATOR_SOURCE,
+};
+use grid_search_desktop::{fetch_experiment, Error, ExperimentLog};
+
+/// Registers an evaluator, or replaces the one with the same name.
+#[tauri::command]
+pub async fn save_evaluator(
+    state: tauri::State<'_, DatabaseState>,
+    input: EvaluatorInput,
+) -> Result<(), Error> {
+    input.validate()?;
+
+    let pool = &state.0;
+    let stmt = r#"
+        INSERT INTO evaluators (name, command, args, working_dir, timeout, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (name) DO UPDATE SET
+            command = excluded.command,
+            args = excluded.args,
+            working_dir = excluded.working_dir,
+            timeout = excluded.timeout,
+            enabled = excluded.enabled,
+            last_modified = unixepoch('now')
+    "#;
+    sqlx::query(stmt)
+        .bind(input.name.trim())
+        .bind(input.command.trim())
+        .bind(serde_json::to_string(&input.args)?)
+        .bind(input.working_dir.trim())
+        .bind(input.timeout)
+        .bind(input.enabled)
+        .execute(pool)
+        .await?;
+
+    println!("Saved evaluator: {}", input.name.trim());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_evaluators(
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<Vec<Evaluator>, Error> {
+    let pool = &state.0;
+    Ok(
+        sqlx::query_as::<_, Evaluator>("SELECT * FROM evaluators ORDER BY name")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+/// Removes an evaluator; the scores it gave are kept.
+#[tauri::command]
+pub async fn delete_evaluator(
+    state: tauri::State<'_, DatabaseState>,
+    name: String,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let result = sqlx::query("DELETE FROM evaluators WHERE name = $1")
+        .bind(&name)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError(format!(
+            "Evaluator '{}' not found",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Runs the enabled evaluators attached to an experiment on every successful
+/// inference of the experiment, replacing the previous evaluator scores. Returns the number
+/// of scores with a value.
+#[tauri::command]
+pub async fn evaluate_experiment(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<usize, Error> {
+    let pool = &state.0;
+    if experiment_evaluators(pool, &experiment_uuid)
+        .await?
+        .is_empty()
+    {
+        return Err(Error::StringError(
+            "No enabled evaluator is attached to the experiment".to_string(),
+        ));
+    }
+    let experiment = fetch_experiment(pool, &experiment_uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+
+    let mut count = 0;
+    for inference in &log.inferences {
+        let Some(result) = &inference.result else {
+            continue;
+        };
+        let prompt = inference
+            .rendered_prompt
+            .as_deref()
+            .unwrap_or(&inference.parameters.prompt);
+        let scores = evaluate_inference(
+            pool,
+            &experiment_uuid,
+            &inference.inference_id,
+            &inference.parameters,
+            prompt,
+            &result.response,
+        )
+        .await?;
+        count += scores.iter().filter(|s| s.score.is_some()).count();
+    }
+
+    println!(
+        "Evaluated experiment {}: {} {} scores",
+        experiment_uuid, count, EVALUATOR_SOURCE
+    );
+    Ok(count)
+}
+
+/// Sets (replaces) the evaluators attached to an experiment. Can be called
+/// before the experiment's first inference is logged.
+#[tauri::command]
+pub async fn set_experiment_evaluators(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+    names: Vec<String>,
+) -> Result<(), Error> {
+    attach_evaluators(&state.0, &experiment_uuid, &names).await
+}
+
+/// Returns the names of the enabled evaluators attached to an experiment.
+#[tauri::command]
+pub async fn get_experiment_evaluators(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Vec<String>, Error> {
+    let pool = &state.0;
+    Ok(experiment_evaluators(pool, &experiment_uuid)
+        .await?
+        .into_iter()
+        .map(|evaluator| evaluator.name)
+        .collect())
+}
@@ -1,26 +1,500 @@
-use grid_search_desktop::{Error, ExperimentFile};
+use grid_search_desktop::chart::{write_chart, ChartFormat, ChartSpec};
+use grid_search_desktop::diversity::{analyze_diversity, DiversityReport};
+use grid_search_desktop::export::{export_rows, write_rows, ExportFormat};
+use grid_search_desktop::report::{render_report, ReportFormat};
+use grid_search_desktop::scoring::get_scores;
+use grid_search_desktop::summary::{summarize_experiment, ExperimentStats};
+use grid_search_desktop::{
+    fetch_experiment, Error, ExperimentFile, ExperimentLog, IDefaultConfigs,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, Pool, QueryBuilder, Sqlite};
 
+use crate::commands::{get_ratings, normalize_tags, TagCount};
 use crate::db::DatabaseState;
 
+const DEFAULT_PAGE_SIZE: u32 = 25;
+const MAX_PAGE_SIZE: u32 = 200;
+
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExperimentSortBy {
+    #[default]
+    DateCreated,
+    Name,
+    InferenceCount,
+}
+
+#[derive(Debug, Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct ExperimentQuery {
+    pub page: u32,
+    pub page_size: Option<u32>,
+    pub sort_by: ExperimentSortBy,
+    pub sort_dir: SortDirection,
+    // Unix timestamps, inclusive
+    pub date_from: Option<i64>,
+    pub date_to: Option<i64>,
+    pub model: Option<String>,
+    pub favorite: Option<bool>,
+    // grid | regression
+    pub run_type: Option<String>,
+    // Experiments must have every one of these tags
+    pub tags: Vec<String>,
+    // Matched against the name, the uuid, the description and the prompts
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Serialize, FromRow)]
+pub struct ExperimentSummary {
+    pub name: String,
+    pub experiment_uuid: String,
+    pub created: String,
+    pub date_created: i64,
+    pub is_favorite: bool,
+    pub description: String,
+    // grid | regression
+    pub run_type: String,
+    #[sqlx(json)]
+    pub tags: Vec<String>,
+    pub inference_count: i64,
+    pub error_count: i64,
+    #[sqlx(json)]
+    pub models: Vec<String>,
+    // completed | partial | failed
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentPage {
+    pub items: Vec<ExperimentSummary>,
+    pub total: i64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// Lists lightweight experiment summaries, one page at a time.
+/// The experiment contents are never sent; use `get_experiment` for those.
 #[tauri::command]
-pub async fn get_experiments(
+pub async fn list_experiments(
     state: tauri::State<'_, DatabaseState>,
-) -> Result<Vec<ExperimentFile>, Error> {
-    let stmt = r#"
+    query: Option<ExperimentQuery>,
+) -> Result<ExperimentPage, Error> {
+    let query = query.unwrap_or_default();
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let pool = &state.0;
+
+    let mut count_qb: QueryBuilder<Sqlite> =
+        QueryBuilder::new("SELECT COUNT(*) FROM experiments e");
+    push_filters(&mut count_qb, &query);
+    let (total,): (i64,) = count_qb.build_query_as().fetch_one(pool).await?;
+
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+        r#"
         SELECT
-            name,
-            created,
-            contents
-        FROM experiments
-        ORDER BY id DESC
-    "#;
+            *,
+            CASE
+                WHEN error_count = 0 THEN 'completed'
+                WHEN error_count < inference_count THEN 'partial'
+                ELSE 'failed'
+            END AS status
+        FROM (
+            SELECT
+                e.id,
+                e.name,
+                e.experiment_uuid,
+                e.created,
+                e.date_created,
+                e.is_favorite,
+                e.description,
+                e.run_type,
+                (
+                    SELECT json_group_array(tag)
+                    FROM experiment_tags t
+                    WHERE t.experiment_uuid = e.experiment_uuid
+                ) AS tags,
+                (
+                    SELECT COUNT(*)
+                    FROM json_each(e.contents, '$.inferences')
+                ) AS inference_count,
+                (
+                    SELECT COUNT(*)
+                    FROM json_each(e.contents, '$.inferences')
+                    WHERE json_type(value, '$.error') IS NOT NULL
+                ) AS error_count,
+                (
+                    SELECT json_group_array(DISTINCT json_extract(value, '$.parameters.model'))
+                    FROM json_each(e.contents, '$.inferences')
+                ) AS models
+            FROM experiments e
+        "#,
+    );
+    push_filters(&mut qb, &query);
+    qb.push(") ORDER BY ");
+    qb.push(match query.sort_by {
+        ExperimentSortBy::DateCreated => "date_created",
+        ExperimentSortBy::Name => "lower(name)",
+        ExperimentSortBy::InferenceCount => "inference_count",
+    });
+    let direction = match query.sort_dir {
+        SortDirection::Asc => " ASC",
+        SortDirection::Desc => " DESC",
+    };
+    qb.push(direction);
+    // Keep the order stable between pages
+    qb.push(", id");
+    qb.push(direction);
+    qb.push(" LIMIT ");
+    qb.push_bind(page_size as i64);
+    qb.push(" OFFSET ");
+    qb.push_bind(query.page as i64 * page_size as i64);
+
+    let items = qb
+        .build_query_as::<ExperimentSummary>()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(ExperimentPage {
+        items,
+        total,
+        page: query.page,
+        page_size,
+    })
+}
+
+fn push_filters(qb: &mut QueryBuilder<Sqlite>, query: &ExperimentQuery) {
+    qb.push(" WHERE 1 = 1");
+
+    if let Some(date_from) = query.date_from {
+        qb.push(" AND e.date_created >= ");
+        qb.push_bind(date_from);
+    }
+    if let Some(date_to) = query.date_to {
+        qb.push(" AND e.date_created <= ");
+        qb.push_bind(date_to);
+    }
+    if let Some(favorite) = query.favorite {
+        qb.push(" AND e.is_favorite = ");
+        qb.push_bind(favorite);
+    }
+    if let Some(run_type) = query.run_type.as_deref().filter(|t| !t.is_empty()) {
+        qb.push(" AND e.run_type = ");
+        qb.push_bind(run_type.to_string());
+    }
+    if let Some(model) = query.model.as_deref().filter(|m| !m.is_empty()) {
+        qb.push(
+            " AND EXISTS (SELECT 1 FROM json_each(e.contents, '$.inferences') \
+             WHERE json_extract(value, '$.parameters.model') = ",
+        );
+        qb.push_bind(model.to_string());
+        qb.push(")");
+    }
+    for tag in normalize_tags(&query.tags) {
+        qb.push(
+            " AND EXISTS (SELECT 1 FROM experiment_tags t \
+             WHERE t.experiment_uuid = e.experiment_uuid AND t.tag = ",
+        );
+        qb.push_bind(tag);
+        qb.push(")");
+    }
+    if let Some(text) = query
+        .text
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        // % and _ in the text match themselves, not any characters
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+        qb.push(" AND (e.name LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" ESCAPE '\\' OR e.experiment_uuid LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(" ESCAPE '\\' OR e.description LIKE ");
+        qb.push_bind(pattern.clone());
+        qb.push(
+            " ESCAPE '\\' OR EXISTS (SELECT 1 FROM json_each(e.contents, '$.inferences') \
+             WHERE json_extract(value, '$.parameters.prompt') LIKE ",
+        );
+        qb.push_bind(pattern);
+        qb.push(" ESCAPE '\\'))");
+    }
+}
+
+/// Returns the full contents of a single experiment.
+#[tauri::command]
+pub async fn get_experiment(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+) -> Result<ExperimentFile, Error> {
+    let pool = &state.0;
+    fetch_experiment(pool, &uuid).await
+}
+
+/// Summarizes an experiment per model and parameter combination,
+/// including the aggregated ratings and scores of its inferences.
+/// The combinations are ranked by the mean of the `rank_by` score.
+#[tauri::command]
+pub async fn experiment_summary(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    rank_by: Option<String>,
+) -> Result<ExperimentStats, Error> {
+    let pool = &state.0;
+    let experiment = fetch_experiment(pool, &uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+
+    let ratings = get_ratings(pool, &uuid).await?;
+    let scores = get_scores(pool, &uuid).await?;
+
+    Ok(summarize_experiment(
+        &log,
+        &ratings,
+        &scores,
+        rank_by.as_deref(),
+    ))
+}
+
+/// Measures how different the responses of each grid cell are across
+/// generations, optionally comparing their embeddings from `embedding_model`.
+#[tauri::command]
+pub async fn experiment_diversity(
+    state: tauri::State<'_, DatabaseState>,
+    config: IDefaultConfigs,
+    uuid: String,
+    embedding_model: Option<String>,
+) -> Result<DiversityReport, Error> {
+    let pool = &state.0;
+    let experiment = fetch_experiment(pool, &uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+
+    let embedding_model = embedding_model.filter(|model| !model.trim().is_empty());
+    analyze_diversity(&config, &log, embedding_model.as_deref()).await
+}
+
+/// Exports an experiment as one row per inference.
+/// The format is picked from the file extension when not given.
+/// Returns the number of exported rows.
+#[tauri::command]
+pub async fn export_experiment(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    path: String,
+    format: Option<ExportFormat>,
+) -> Result<usize, Error> {
+    let format = match format {
+        Some(format) => format,
+        None => ExportFormat::from_path(&path)?,
+    };
+
+    let pool = &state.0;
+    let experiment = fetch_experiment(pool, &uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+    let ratings = get_ratings(pool, &uuid).await?;
+    let scores = get_scores(pool, &uuid).await?;
+
+    let rows = export_rows(&log, &experiment.name, &ratings, &scores);
+    write_rows(&path, format, &rows)?;
+
+    println!("Exported {} inferences of {} to {}", rows.len(), uuid, path);
+    Ok(rows.len())
+}
+
+/// Renders an experiment report as a self-contained HTML or Markdown file.
+/// The format is picked from the file extension when not given.
+#[tauri::command]
+pub async fn generate_report(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    path: String,
+    format: Option<ReportFormat>,
+) -> Result<(), Error> {
+    let format = match format {
+        Some(format) => format,
+        None => ReportFormat::from_path(&path)?,
+    };
+
+    let pool = &state.0;
+    let experiment = fetch_experiment(pool, &uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+    let ratings = get_ratings(pool, &uuid).await?;
+    let scores = get_scores(pool, &uuid).await?;
+
+    std::fs::write(
+        &path,
+        render_report(&log, &experiment.name, &ratings, &scores, format),
+    )?;
+
+    println!("Wrote the report of {} to {}", uuid, path);
+    Ok(())
+}
+
+/// Renders a chart of the experiment's inferences as SVG or PNG.
+/// The format is picked from the file extension when not given.
+#[tauri::command]
+pub async fn generate_chart(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    path: String,
+    spec: ChartSpec,
+    format: Option<ChartFormat>,
+) -> Result<(), Error> {
+    let format = match format {
+        Some(format) => format,
+        None => ChartFormat::from_path(&path)?,
+    };
+
+    let pool = &state.0;
+    let experiment = fetch_experiment(pool, &uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+    let ratings = get_ratings(pool, &uuid).await?;
+    let scores = get_scores(pool, &uuid).await?;
+
+    let rows = export_rows(&log, &experiment.name, &ratings, &scores);
+    write_chart(&path, format, &rows, &spec)?;
+
+    println!("Wrote a chart of {} to {}", uuid, path);
+    Ok(())
+}
+
+/// Renames an experiment. Names are unique.
+#[tauri::command]
+pub async fn rename_experiment(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    name: String,
+) -> Result<(), Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::StringError(
+            "The experiment name can't be empty".to_string(),
+        ));
+    }
 
-    let query = sqlx::query_as::<_, ExperimentFile>(stmt);
     let pool = &state.0;
-    let experiments = query.fetch_all(pool).await?;
 
-    println!("\nRetrieved {} experiments:", experiments.len());
-    Ok(experiments)
+    let (taken,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM experiments WHERE name = $1 AND experiment_uuid <> $2",
+    )
+    .bind(name)
+    .bind(&uuid)
+    .fetch_one(pool)
+    .await?;
+    if taken > 0 {
+        return Err(Error::StringError(format!(
+            "An experiment named '{}' already exists",
+            name
+        )));
+    }
+
+    let result = sqlx::query("UPDATE experiments SET name = $1 WHERE experiment_uuid = $2")
+        .bind(name)
+        .bind(&uuid)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError(format!("Experiment {} not found", uuid)));
+    }
+
+    println!("Renamed experiment {} to '{}'", uuid, name);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_experiment_favorite(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    is_favorite: bool,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let result = sqlx::query("UPDATE experiments SET is_favorite = $1 WHERE experiment_uuid = $2")
+        .bind(is_favorite)
+        .bind(&uuid)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError(format!("Experiment {} not found", uuid)));
+    }
+
+    Ok(())
+}
+
+/// Sets the markdown description of an experiment.
+#[tauri::command]
+pub async fn set_experiment_description(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    description: String,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let result = sqlx::query("UPDATE experiments SET description = $1 WHERE experiment_uuid = $2")
+        .bind(description.trim())
+        .bind(&uuid)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError(format!("Experiment {} not found", uuid)));
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_experiment_tags(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    tags: Vec<String>,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM experiment_tags WHERE experiment_uuid = $1")
+        .bind(&uuid)
+        .execute(&mut *tx)
+        .await?;
+
+    for tag in normalize_tags(&tags) {
+        sqlx::query("INSERT INTO experiment_tags (experiment_uuid, tag) VALUES ($1, $2)")
+            .bind(&uuid)
+            .bind(tag)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    println!("Set tags of experiment {}: {:?}", uuid, tags);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_experiment_tags(
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<Vec<TagCount>, Error> {
+    let stmt = r#"
+        SELECT tag, COUNT(*) AS count
+        FROM experiment_tags
+        GROUP BY tag
+        ORDER BY lower(tag) ASC
+    "#;
+
+    let pool = &state.0;
+    Ok(sqlx::query_as::<_, TagCount>(stmt).fetch_all(pool).await?)
 }
 
 #[tauri::command]
@@ -34,8 +508,26 @@ pub async fn delete_experiments(
         "*" => "DELETE FROM experiments",
         _ => "DELETE FROM experiments WHERE experiment_uuid = $1",
     };
+    // Judges, evaluators and scripts aren't tied to the experiments table
+    // (see `set_experiment_judge`, `set_experiment_evaluators` and
+    // `set_experiment_scripts`)
+    let attached_stmts: [&str; 3] = match uuid.as_str() {
+        "*" => [
+            "DELETE FROM experiment_judges",
+            "DELETE FROM experiment_evaluators",
+            "DELETE FROM experiment_scripts",
+        ],
+        _ => [
+            "DELETE FROM experiment_judges WHERE experiment_uuid = $1",
+            "DELETE FROM experiment_evaluators WHERE experiment_uuid = $1",
+            "DELETE FROM experiment_scripts WHERE experiment_uuid = $1",
+        ],
+    };
 
     let _ = sqlx::query(stmt).bind(&uuid).execute(pool).await?;
+    for attached_stmt in attached_stmts {
+        let _ = sqlx::query(attached_stmt).bind(&uuid).execute(pool).await?;
+    }
     print!("Deleted experiment with UUID: {}", uuid);
 
     Ok(())
@@ -0,0 +1,135 @@
+use crate::db::DatabaseState;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use grid_search_desktop::bundle::ConflictPolicy;
+use grid_search_desktop::migrate::{log_timestamp, read_experiment_file};
+use grid_search_desktop::{create_experiment_name, Error};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    Created,
+    Renamed,
+    Overwritten,
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentImportReport {
+    pub experiment_uuid: String,
+    pub name: String,
+    pub status: ImportStatus,
+    pub inference_count: usize,
+    /// `log_version` of the imported file ("unversioned" before 0.4.0)
+    pub migrated_from: String,
+}
+
+/// Imports an experiment log (of any past `log_version`) into the history.
+/// An experiment with the same uuid is resolved with `policy`; names that are
+/// already taken get a numbered suffix.
+#[tauri::command]
+pub async fn import_experiment(
+    state: tauri::State<'_, DatabaseState>,
+    path: String,
+    policy: ConflictPolicy,
+) -> Result<ExperimentImportReport, Error> {
+    let import = read_experiment_file(&path)?;
+    let mut log = import.migrated.log;
+
+    let pool = &state.0;
+    let mut tx = pool.begin().await?;
+
+    let (exists,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM experiments WHERE experiment_uuid = $1")
+            .bind(&log.experiment_uuid)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    let status = match (exists > 0, policy) {
+        (false, _) => ImportStatus::Created,
+        (true, ConflictPolicy::Skip) => ImportStatus::Skipped,
+        (true, ConflictPolicy::Overwrite) => {
+            // Ratings, tags and comparisons of the replaced experiment cascade
+            sqlx::query("DELETE FROM experiments WHERE experiment_uuid = $1")
+                .bind(&log.experiment_uuid)
+                .execute(&mut *tx)
+                .await?;
+            ImportStatus::Overwritten
+        }
+        (true, ConflictPolicy::Rename) => {
+            log.experiment_uuid = Uuid::new_v4().to_string();
+            for inference in log.inferences.iter_mut() {
+                inference.parameters.experiment_uuid = log.experiment_uuid.clone();
+            }
+            ImportStatus::Renamed
+        }
+    };
+
+    let mut report = ExperimentImportReport {
+        experiment_uuid: log.experiment_uuid.clone(),
+        name: import.name,
+        status,
+        inference_count: log.inferences.len(),
+        migrated_from: import.migrated.original_version,
+    };
+
+    if status == ImportStatus::Skipped {
+        println!("Skipped importing {}: {:?}", path, report);
+        return Ok(report);
+    }
+
+    report.name = unique_experiment_name(&mut tx, &report.name).await?;
+
+    let now = Utc::now();
+    let created = if log.datetime.is_empty() {
+        now.to_string()
+    } else {
+        log.datetime.clone()
+    };
+    let date_created = log_timestamp(&log.datetime).unwrap_or(now.timestamp());
+
+    let stmt = r#"
+        INSERT INTO experiments (name, contents, experiment_uuid, created, is_favorite, date_created)
+        VALUES ($1, $2, $3, $4, $5, $6)
+    "#;
+
+    sqlx::query(stmt)
+        .bind(&report.name)
+        .bind(serde_json::to_string(&log)?)
+        .bind(&log.experiment_uuid)
+        .bind(created)
+        .bind(false)
+        .bind(date_created)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    println!("Imported experiment from {}: {:?}", path, report);
+    Ok(report)
+}
+
+async fn unique_experiment_name(conn: &mut SqliteConnection, name: &str) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(create_experiment_name());
+    }
+
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    loop {
+        let (taken,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM experiments WHERE name = $1")
+            .bind(&candidate)
+            .fetch_one(&mut *conn)
+            .await?;
+
+        if taken == 0 {
+            return Ok(candidate);
+        }
+        candidate = format!("{} ({})", name, n);
+        n += 1;
+    }
+}
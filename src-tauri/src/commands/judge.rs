@@ -0,0 +1,86 @@
+use crate::db::DatabaseState;
+
+use grid_search_desktop::judge::{
+    get_judge, judge_inference, set_judge, ExperimentJudge, JudgeInput, JUDGE_SOURCE,
+};
+use grid_search_desktop::{fetch_experiment, Error, ExperimentLog, IDefaultConfigs};
+
+/// Sets (replaces) the judge model and rubric of an experiment.
+/// Can be called before the experiment's first inference is logged.
+#[tauri::command]
+pub async fn set_experiment_judge(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+    judge: JudgeInput,
+) -> Result<(), Error> {
+    set_judge(&state.0, &experiment_uuid, &judge).await
+}
+
+#[tauri::command]
+pub async fn get_experiment_judge(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Option<ExperimentJudge>, Error> {
+    let pool = &state.0;
+    get_judge(pool, &experiment_uuid).await
+}
+
+/// Removes the judge of an experiment; the scores it gave are kept.
+#[tauri::command]
+pub async fn delete_experiment_judge(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    sqlx::query("DELETE FROM experiment_judges WHERE experiment_uuid = $1")
+        .bind(&experiment_uuid)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Judges every successful inference of an experiment, replacing the previous
+/// judge scores. Returns the number of inferences the judge could score.
+#[tauri::command]
+pub async fn judge_experiment(
+    state: tauri::State<'_, DatabaseState>,
+    config: IDefaultConfigs,
+    experiment_uuid: String,
+) -> Result<usize, Error> {
+    let pool = &state.0;
+    let judge = get_judge(pool, &experiment_uuid)
+        .await?
+        .ok_or_else(|| Error::StringError("The experiment has no judge".to_string()))?;
+    let experiment = fetch_experiment(pool, &experiment_uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+
+    let mut count = 0;
+    for inference in &log.inferences {
+        let Some(result) = &inference.result else {
+            continue;
+        };
+        let prompt = inference
+            .rendered_prompt
+            .as_deref()
+            .unwrap_or(&inference.parameters.prompt);
+        let score = judge_inference(
+            pool,
+            &config,
+            &judge,
+            &inference.inference_id,
+            &inference.parameters,
+            prompt,
+            &result.response,
+        )
+        .await?;
+        if score.score.is_some() {
+            count += 1;
+        }
+    }
+
+    println!(
+        "Judged experiment {} with {}: {} {} scores",
+        experiment_uuid, judge.model, count, JUDGE_SOURCE
+    );
+    Ok(count)
+}
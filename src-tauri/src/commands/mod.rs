@@ -1,7 +1,35 @@
+mod comparison;
+mod dataset;
+mod evaluator;
 mod experiment;
+mod experiment_import;
+mod judge;
 mod llm;
 mod prompt;
+mod prompt_bundle;
+mod rating;
+mod regression;
+mod reproducibility;
+mod scoring;
+mod script;
+mod spec;
+mod system_prompt;
+mod template;
 
+pub use comparison::*;
+pub use dataset::*;
+pub use evaluator::*;
 pub use experiment::*;
+pub use experiment_import::*;
+pub use judge::*;
 pub use llm::*;
 pub use prompt::*;
+pub use prompt_bundle::*;
+pub use rating::*;
+pub use regression::*;
+pub use reproducibility::*;
+pub use scoring::*;
+pub use script::*;
+pub use spec::*;
+pub use system_prompt::*;
+pub use template::*;
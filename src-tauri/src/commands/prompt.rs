@@ -2,7 +2,9 @@ use crate::db::DatabaseState;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::*;
+use sqlx::{Pool, QueryBuilder, Sqlite, SqliteConnection};
 
+use grid_search_desktop::diff::{diff_lines, DiffLine};
 use grid_search_desktop::Error;
 
 #[derive(Debug, FromRow, Serialize)]
@@ -11,16 +13,73 @@ pub struct Prompt {
     pub name: String,
     pub slug: String,
     pub prompt: String,
+    pub folder: String,
+    #[sqlx(json)]
+    pub tags: Vec<String>,
     pub date_created: i64,  // Unix timestamp
     pub last_modified: i64, // Unix timestamp
 }
 
+#[derive(Debug, FromRow, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct PromptVersion {
+    pub id: i64,
+    pub prompt_uuid: String,
+    pub version: i64,
+    pub prompt: String,
+    pub date_created: i64, // Unix timestamp
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PromptInput {
     pub uuid: String,
     pub name: String,
     pub slug: String,
     pub prompt: String,
+    #[serde(default)]
+    pub folder: Option<String>,
+    // Tags are left untouched when not sent
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
+/// Trims, dedupes and drops empty tags
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = vec![];
+    for tag in tags.iter().map(|tag| tag.trim()) {
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+/// Replaces the tags of a prompt.
+/// Takes a connection so callers can run it inside their own transaction.
+pub async fn replace_prompt_tags(
+    conn: &mut SqliteConnection,
+    uuid: &str,
+    tags: &[String],
+) -> Result<(), Error> {
+    sqlx::query("DELETE FROM prompt_tags WHERE prompt_uuid = $1")
+        .bind(uuid)
+        .execute(&mut *conn)
+        .await?;
+
+    for tag in normalize_tags(tags) {
+        sqlx::query("INSERT INTO prompt_tags (prompt_uuid, tag) VALUES ($1, $2)")
+            .bind(uuid)
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
 }
 
 #[tauri::command]
@@ -30,10 +89,12 @@ pub async fn create_prompt(
 ) -> Result<(), Error> {
     let pool = &state.0;
     let now = Utc::now().timestamp();
+    // The prompt and its tags are written together
+    let mut tx = pool.begin().await?;
 
     let stmt = r#"
-        INSERT INTO prompts (uuid, name, slug, prompt, date_created, last_modified)
-        VALUES ($1, $2, $3, $4, $5, $6)
+        INSERT INTO prompts (uuid, name, slug, prompt, folder, date_created, last_modified)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
     "#;
 
     sqlx::query(stmt)
@@ -41,11 +102,17 @@ pub async fn create_prompt(
         .bind(&input.name)
         .bind(&input.slug)
         .bind(&input.prompt)
+        .bind(input.folder.clone().unwrap_or_default())
         .bind(now)
         .bind(now)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
+    if let Some(tags) = &input.tags {
+        replace_prompt_tags(&mut tx, &input.uuid, tags).await?;
+    }
+    tx.commit().await?;
+
     println!("Created new prompt: {} ({})", input.name, input.uuid);
     Ok(())
 }
@@ -57,29 +124,38 @@ pub async fn update_prompt(
 ) -> Result<(), Error> {
     let pool = &state.0;
     let now = Utc::now().timestamp();
+    // The prompt and its tags are written together
+    let mut tx = pool.begin().await?;
 
     let stmt = r#"
         UPDATE prompts 
         SET name = $1, 
             slug = $2, 
             prompt = $3,
-            last_modified = $4
-        WHERE uuid = $5
+            folder = COALESCE($4, folder),
+            last_modified = $5
+        WHERE uuid = $6
     "#;
 
     let result = sqlx::query(stmt)
         .bind(&input.name)
         .bind(&input.slug)
         .bind(&input.prompt)
+        .bind(&input.folder)
         .bind(now)
         .bind(&input.uuid)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
 
     if result.rows_affected() == 0 {
         return Err(Error::StringError("Prompt not found".to_string()));
     }
 
+    if let Some(tags) = &input.tags {
+        replace_prompt_tags(&mut tx, &input.uuid, tags).await?;
+    }
+    tx.commit().await?;
+
     println!("Updated prompt: {} ({})", input.name, input.uuid);
     Ok(())
 }
@@ -92,6 +168,12 @@ pub async fn get_all_prompts(state: tauri::State<'_, DatabaseState>) -> Result<V
             name,
             slug,
             prompt,
+            folder,
+            (
+                SELECT json_group_array(tag)
+                FROM prompt_tags
+                WHERE prompt_uuid = uuid
+            ) AS tags,
             date_created,
             last_modified
         FROM prompts
@@ -125,3 +207,251 @@ pub async fn delete_prompt(
     println!("Deleted prompt with UUID: {}", uuid);
     Ok(())
 }
+
+#[tauri::command]
+pub async fn list_prompt_versions(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+) -> Result<Vec<PromptVersion>, Error> {
+    let stmt = r#"
+        SELECT
+            id,
+            prompt_uuid,
+            version,
+            prompt,
+            date_created
+        FROM prompt_versions
+        WHERE prompt_uuid = $1
+        ORDER BY version DESC
+    "#;
+
+    let pool = &state.0;
+    let versions = sqlx::query_as::<_, PromptVersion>(stmt)
+        .bind(&uuid)
+        .fetch_all(pool)
+        .await?;
+
+    println!("\nRetrieved {} versions of prompt {}", versions.len(), uuid);
+    Ok(versions)
+}
+
+async fn get_prompt_version(
+    pool: &Pool<Sqlite>,
+    uuid: &str,
+    version: i64,
+) -> Result<PromptVersion, Error> {
+    let stmt = r#"
+        SELECT
+            id,
+            prompt_uuid,
+            version,
+            prompt,
+            date_created
+        FROM prompt_versions
+        WHERE prompt_uuid = $1 AND version = $2
+    "#;
+
+    sqlx::query_as::<_, PromptVersion>(stmt)
+        .bind(uuid)
+        .bind(version)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::StringError(format!("Prompt version {} not found", version)))
+}
+
+#[tauri::command]
+pub async fn diff_prompt_versions(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    from_version: i64,
+    to_version: i64,
+) -> Result<Vec<DiffLine>, Error> {
+    let pool = &state.0;
+    let from = get_prompt_version(pool, &uuid, from_version).await?;
+    let to = get_prompt_version(pool, &uuid, to_version).await?;
+
+    Ok(diff_lines(&from.prompt, &to.prompt))
+}
+
+/// Restores the text of an older version.
+/// The restored text is recorded as a new version, so no history is lost.
+#[tauri::command]
+pub async fn restore_prompt_version(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    version: i64,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let restored = get_prompt_version(pool, &uuid, version).await?;
+
+    let stmt = r#"
+        UPDATE prompts
+        SET prompt = $1,
+            last_modified = $2
+        WHERE uuid = $3
+    "#;
+
+    let result = sqlx::query(stmt)
+        .bind(&restored.prompt)
+        .bind(Utc::now().timestamp())
+        .bind(&uuid)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError("Prompt not found".to_string()));
+    }
+
+    println!("Restored prompt {} to version {}", uuid, version);
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct PromptSearch {
+    // Full-text query over prompt names and bodies
+    #[serde(default)]
+    pub query: Option<String>,
+    // Prompts must have all of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Matches the folder and its subfolders
+    #[serde(default)]
+    pub folder: Option<String>,
+}
+
+/// Converts free text into an FTS5 query that matches all words as prefixes,
+/// so user input is never parsed as FTS5 syntax. Each word is quoted, and
+/// words with nothing to match (e.g.: a lone `"` or `-`) are left out.
+fn fts_query(text: &str) -> String {
+    text.replace('"', " ")
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[tauri::command]
+pub async fn search_prompts(
+    state: tauri::State<'_, DatabaseState>,
+    search: PromptSearch,
+) -> Result<Vec<Prompt>, Error> {
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+        r#"
+        SELECT
+            p.uuid,
+            p.name,
+            p.slug,
+            p.prompt,
+            p.folder,
+            (
+                SELECT json_group_array(tag)
+                FROM prompt_tags
+                WHERE prompt_uuid = p.uuid
+            ) AS tags,
+            p.date_created,
+            p.last_modified
+        FROM prompts p
+        "#,
+    );
+
+    let text = search.query.as_deref().map(fts_query).unwrap_or_default();
+    if !text.is_empty() {
+        qb.push(" JOIN prompts_fts ON prompts_fts.rowid = p.rowid AND prompts_fts MATCH ");
+        qb.push_bind(text.clone());
+    }
+
+    qb.push(" WHERE 1 = 1");
+
+    if let Some(folder) = search.folder.as_deref().filter(|f| !f.is_empty()) {
+        qb.push(" AND (p.folder = ");
+        qb.push_bind(folder.to_string());
+        // % and _ in the folder name match themselves, not any characters
+        let escaped = folder
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        qb.push(" OR p.folder LIKE ");
+        qb.push_bind(format!("{}/%", escaped));
+        qb.push(" ESCAPE '\\')");
+    }
+
+    for tag in normalize_tags(&search.tags) {
+        qb.push(
+            " AND EXISTS (SELECT 1 FROM prompt_tags t WHERE t.prompt_uuid = p.uuid AND t.tag = ",
+        );
+        qb.push_bind(tag);
+        qb.push(")");
+    }
+
+    // Best matches first when searching text
+    if text.is_empty() {
+        qb.push(" ORDER BY lower(p.name) ASC");
+    } else {
+        qb.push(" ORDER BY prompts_fts.rank, lower(p.name) ASC");
+    }
+
+    let pool = &state.0;
+    let prompts = qb.build_query_as::<Prompt>().fetch_all(pool).await?;
+
+    println!("\nFound {} prompts matching {:?}", prompts.len(), search);
+    Ok(prompts)
+}
+
+#[tauri::command]
+pub async fn set_prompt_tags(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    tags: Vec<String>,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let mut tx = pool.begin().await?;
+    replace_prompt_tags(&mut tx, &uuid, &tags).await?;
+    tx.commit().await?;
+
+    println!("Set tags of prompt {}: {:?}", uuid, tags);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_prompt_tags(
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<Vec<TagCount>, Error> {
+    let stmt = r#"
+        SELECT tag, COUNT(*) AS count
+        FROM prompt_tags
+        GROUP BY tag
+        ORDER BY lower(tag) ASC
+    "#;
+
+    let pool = &state.0;
+    Ok(sqlx::query_as::<_, TagCount>(stmt).fetch_all(pool).await?)
+}
+
+#[tauri::command]
+pub async fn get_prompt_folders(
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<Vec<String>, Error> {
+    let stmt = r#"
+        SELECT DISTINCT folder
+        FROM prompts
+        WHERE folder <> ''
+        ORDER BY lower(folder) ASC
+    "#;
+
+    let pool = &state.0;
+    let folders: Vec<(String,)> = sqlx::query_as(stmt).fetch_all(pool).await?;
+    Ok(folders.into_iter().map(|(folder,)| folder).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fts_query_quotes_every_word() {
+        assert_eq!(fts_query("don't foo-bar"), r#""don't"* "foo-bar"*"#);
+        assert_eq!(fts_query(r#"say "hi - NEAR("#), r#""say"* "hi"* "NEAR("*"#);
+        assert_eq!(fts_query(r#"" -"#), "");
+    }
+}
@@ -0,0 +1,242 @@
+use crate::commands::{replace_prompt_tags, Prompt};
+use crate::db::DatabaseState;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection};
+use uuid::Uuid;
+
+use grid_search_desktop::bundle::{
+    read_bundle, write_bundle, BundledPrompt, ConflictPolicy, PromptBundle, PROMPT_BUNDLE_VERSION,
+};
+use grid_search_desktop::Error;
+
+#[derive(Debug, Serialize, Default)]
+pub struct PromptImportReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub renamed: Vec<String>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// Exports the prompt library (or only the selected prompts) to a JSON or YAML bundle.
+/// Returns the number of exported prompts.
+#[tauri::command]
+pub async fn export_prompts(
+    state: tauri::State<'_, DatabaseState>,
+    path: String,
+    uuids: Option<Vec<String>>,
+) -> Result<usize, Error> {
+    let mut qb: QueryBuilder<Sqlite> = QueryBuilder::new(
+        r#"
+        SELECT
+            uuid,
+            name,
+            slug,
+            prompt,
+            folder,
+            (
+                SELECT json_group_array(tag)
+                FROM prompt_tags
+                WHERE prompt_uuid = uuid
+            ) AS tags,
+            date_created,
+            last_modified
+        FROM prompts
+        "#,
+    );
+
+    if let Some(uuids) = &uuids {
+        qb.push(" WHERE uuid IN (");
+        let mut separated = qb.separated(", ");
+        for uuid in uuids {
+            separated.push_bind(uuid);
+        }
+        // An empty selection exports nothing
+        if uuids.is_empty() {
+            separated.push("NULL");
+        }
+        qb.push(")");
+    }
+    qb.push(" ORDER BY lower(name) ASC");
+
+    let pool = &state.0;
+    let prompts = qb.build_query_as::<Prompt>().fetch_all(pool).await?;
+
+    let bundle = PromptBundle {
+        bundle_version: PROMPT_BUNDLE_VERSION,
+        exported: Utc::now().to_rfc3339(),
+        prompts: prompts
+            .into_iter()
+            .map(|p| BundledPrompt {
+                uuid: p.uuid,
+                name: p.name,
+                slug: p.slug,
+                body: p.prompt,
+                folder: p.folder,
+                tags: p.tags,
+                date_created: Some(p.date_created),
+                last_modified: Some(p.last_modified),
+            })
+            .collect(),
+    };
+
+    write_bundle(&path, &bundle)?;
+
+    println!("Exported {} prompts to {}", bundle.prompts.len(), path);
+    Ok(bundle.prompts.len())
+}
+
+/// Imports a JSON or YAML prompt bundle.
+/// Prompts with the same uuid, name or slug as a stored one are resolved with `policy`.
+#[tauri::command]
+pub async fn import_prompts(
+    state: tauri::State<'_, DatabaseState>,
+    path: String,
+    policy: ConflictPolicy,
+) -> Result<PromptImportReport, Error> {
+    let bundle = read_bundle(&path)?;
+    let mut report = PromptImportReport::default();
+
+    let pool = &state.0;
+    let mut tx = pool.begin().await?;
+
+    for entry in bundle.prompts {
+        let conflicts: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT uuid, name, slug FROM prompts WHERE uuid = $1 OR name = $2 OR slug = $3",
+        )
+        .bind(&entry.uuid)
+        .bind(&entry.name)
+        .bind(&entry.slug)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        if conflicts.is_empty() {
+            insert_prompt(&mut tx, &entry, &entry.uuid, &entry.name, &entry.slug).await?;
+            report.created.push(entry.name);
+            continue;
+        }
+
+        match policy {
+            ConflictPolicy::Skip => report.skipped.push(entry.name),
+            ConflictPolicy::Overwrite => {
+                // The name and slug may belong to two different stored prompts
+                if conflicts.len() > 1 {
+                    report.errors.push(format!(
+                        "'{}' conflicts with {} stored prompts, not overwritten",
+                        entry.name,
+                        conflicts.len()
+                    ));
+                    continue;
+                }
+                let (existing_uuid, _, _) = &conflicts[0];
+                overwrite_prompt(&mut tx, &entry, existing_uuid).await?;
+                report.updated.push(entry.name);
+            }
+            ConflictPolicy::Rename => {
+                let uuid = if conflicts.iter().any(|(uuid, _, _)| uuid == &entry.uuid) {
+                    Uuid::new_v4().to_string()
+                } else {
+                    entry.uuid.clone()
+                };
+                // When only the uuid is taken, the copy keeps its name and slug
+                let name_taken = conflicts
+                    .iter()
+                    .any(|(_, name, slug)| name == &entry.name || slug == &entry.slug);
+                if !name_taken {
+                    insert_prompt(&mut tx, &entry, &uuid, &entry.name, &entry.slug).await?;
+                    report.created.push(entry.name);
+                    continue;
+                }
+                let (name, slug) = unique_name_and_slug(&mut tx, &entry).await?;
+                insert_prompt(&mut tx, &entry, &uuid, &name, &slug).await?;
+                report.renamed.push(format!("{} -> {}", entry.name, name));
+            }
+        }
+    }
+
+    tx.commit().await?;
+
+    println!("Imported prompts from {}: {:?}", path, report);
+    Ok(report)
+}
+
+async fn insert_prompt(
+    conn: &mut SqliteConnection,
+    entry: &BundledPrompt,
+    uuid: &str,
+    name: &str,
+    slug: &str,
+) -> Result<(), Error> {
+    let now = Utc::now().timestamp();
+
+    let stmt = r#"
+        INSERT INTO prompts (uuid, name, slug, prompt, folder, date_created, last_modified)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+    "#;
+
+    sqlx::query(stmt)
+        .bind(uuid)
+        .bind(name)
+        .bind(slug)
+        .bind(&entry.body)
+        .bind(&entry.folder)
+        .bind(entry.date_created.unwrap_or(now))
+        .bind(entry.last_modified.unwrap_or(now))
+        .execute(&mut *conn)
+        .await?;
+
+    replace_prompt_tags(conn, uuid, &entry.tags).await
+}
+
+async fn overwrite_prompt(
+    conn: &mut SqliteConnection,
+    entry: &BundledPrompt,
+    existing_uuid: &str,
+) -> Result<(), Error> {
+    let stmt = r#"
+        UPDATE prompts
+        SET name = $1,
+            slug = $2,
+            prompt = $3,
+            folder = $4,
+            last_modified = $5
+        WHERE uuid = $6
+    "#;
+
+    sqlx::query(stmt)
+        .bind(&entry.name)
+        .bind(&entry.slug)
+        .bind(&entry.body)
+        .bind(&entry.folder)
+        .bind(Utc::now().timestamp())
+        .bind(existing_uuid)
+        .execute(&mut *conn)
+        .await?;
+
+    replace_prompt_tags(conn, existing_uuid, &entry.tags).await
+}
+
+/// Appends a counter to the name and slug until neither is taken
+async fn unique_name_and_slug(
+    conn: &mut SqliteConnection,
+    entry: &BundledPrompt,
+) -> Result<(String, String), Error> {
+    let mut n = 2;
+    loop {
+        let name = format!("{} ({})", entry.name, n);
+        let slug = format!("{}-{}", entry.slug, n);
+
+        let (taken,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM prompts WHERE name = $1 OR slug = $2")
+                .bind(&name)
+                .bind(&slug)
+                .fetch_one(&mut *conn)
+                .await?;
+
+        if taken == 0 {
+            return Ok((name, slug));
+        }
+        n += 1;
+    }
+}
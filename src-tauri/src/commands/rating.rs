@@ -0,0 +1,139 @@
+use crate::db::DatabaseState;
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
+
+use grid_search_desktop::summary::InferenceRating;
+use grid_search_desktop::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct RatingInput {
+    // 1 = thumbs up, -1 = thumbs down
+    #[serde(default)]
+    pub thumb: Option<i64>,
+    // 1 to 5
+    #[serde(default)]
+    pub rating: Option<i64>,
+    #[serde(default)]
+    pub rubric: BTreeMap<String, f64>,
+    #[serde(default)]
+    pub note: String,
+}
+
+/// Sets (replaces) the rating and note of an inference.
+#[tauri::command]
+pub async fn set_inference_rating(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+    inference_id: String,
+    input: RatingInput,
+) -> Result<(), Error> {
+    if let Some(thumb) = input.thumb {
+        if thumb != 1 && thumb != -1 {
+            return Err(Error::StringError(format!(
+                "Invalid thumb value {}, use 1 (up) or -1 (down)",
+                thumb
+            )));
+        }
+    }
+    if let Some(rating) = input.rating {
+        if !(1..=5).contains(&rating) {
+            return Err(Error::StringError(format!(
+                "Invalid rating {}, ratings go from 1 to 5",
+                rating
+            )));
+        }
+    }
+
+    let pool = &state.0;
+
+    // Make sure the inference exists, so ratings don't point to nothing
+    let (found,): (i64,) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*)
+        FROM experiments e, json_each(e.contents, '$.inferences')
+        WHERE e.experiment_uuid = $1
+            AND json_extract(value, '$.inference_id') = $2
+        "#,
+    )
+    .bind(&experiment_uuid)
+    .bind(&inference_id)
+    .fetch_one(pool)
+    .await?;
+    if found == 0 {
+        return Err(Error::StringError(format!(
+            "Inference {} not found in experiment {}",
+            inference_id, experiment_uuid
+        )));
+    }
+
+    let stmt = r#"
+        INSERT INTO inference_ratings (
+            experiment_uuid, inference_id, thumb, rating, rubric, note, last_modified
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (experiment_uuid, inference_id) DO UPDATE SET
+            thumb = excluded.thumb,
+            rating = excluded.rating,
+            rubric = excluded.rubric,
+            note = excluded.note,
+            last_modified = excluded.last_modified
+    "#;
+
+    sqlx::query(stmt)
+        .bind(&experiment_uuid)
+        .bind(&inference_id)
+        .bind(input.thumb)
+        .bind(input.rating)
+        .bind(serde_json::to_string(&input.rubric)?)
+        .bind(input.note.trim())
+        .bind(Utc::now().timestamp())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn clear_inference_rating(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+    inference_id: String,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    sqlx::query("DELETE FROM inference_ratings WHERE experiment_uuid = $1 AND inference_id = $2")
+        .bind(&experiment_uuid)
+        .bind(&inference_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Returns the ratings of an experiment's inferences.
+#[tauri::command]
+pub async fn get_inference_ratings(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Vec<InferenceRating>, Error> {
+    let pool = &state.0;
+    get_ratings(pool, &experiment_uuid).await
+}
+
+pub async fn get_ratings(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+) -> Result<Vec<InferenceRating>, Error> {
+    let stmt = r#"
+        SELECT *
+        FROM inference_ratings
+        WHERE experiment_uuid = $1
+        ORDER BY last_modified DESC
+    "#;
+
+    Ok(sqlx::query_as::<_, InferenceRating>(stmt)
+        .bind(experiment_uuid)
+        .fetch_all(pool)
+        .await?)
+}
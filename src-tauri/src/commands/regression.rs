@@ -0,0 +1,143 @@
+use crate::db::DatabaseState;
+
+use grid_search_desktop::regression::{
+    add_baselines, get_baselines, get_runs, run_suite, GoldenBaseline, GoldenSuite,
+    GoldenSuiteInput, RegressionRun,
+};
+use grid_search_desktop::{fetch_experiment, Error, ExperimentLog, IDefaultConfigs};
+
+/// Creates a golden suite, or updates the description and thresholds of the
+/// one with the same name.
+#[tauri::command]
+pub async fn save_golden_suite(
+    state: tauri::State<'_, DatabaseState>,
+    input: GoldenSuiteInput,
+) -> Result<(), Error> {
+    input.validate()?;
+
+    let pool = &state.0;
+    let stmt = r#"
+        INSERT INTO golden_suites (name, description, thresholds)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (name) DO UPDATE SET
+            description = excluded.description,
+            thresholds = excluded.thresholds,
+            last_modified = unixepoch('now')
+    "#;
+    sqlx::query(stmt)
+        .bind(input.name.trim())
+        .bind(input.description.trim())
+        .bind(serde_json::to_string(&input.thresholds)?)
+        .execute(pool)
+        .await?;
+
+    println!("Saved golden suite: {}", input.name.trim());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_golden_suites(
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<Vec<GoldenSuite>, Error> {
+    let pool = &state.0;
+    let stmt = r#"
+        SELECT
+            s.*,
+            (SELECT COUNT(*) FROM golden_baselines b WHERE b.suite_name = s.name) AS baseline_count
+        FROM golden_suites s
+        ORDER BY s.name
+    "#;
+    Ok(sqlx::query_as::<_, GoldenSuite>(stmt)
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Deletes a golden suite and its baselines. Its regression runs are kept.
+#[tauri::command]
+pub async fn delete_golden_suite(
+    state: tauri::State<'_, DatabaseState>,
+    name: String,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let result = sqlx::query("DELETE FROM golden_suites WHERE name = $1")
+        .bind(&name)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError(format!(
+            "Golden suite '{}' not found",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Adds inferences of an experiment to a golden suite as baselines.
+/// Returns the number added; inferences already in the suite are skipped.
+#[tauri::command]
+pub async fn add_golden_baselines(
+    state: tauri::State<'_, DatabaseState>,
+    suite_name: String,
+    experiment_uuid: String,
+    inference_ids: Vec<String>,
+) -> Result<usize, Error> {
+    let pool = &state.0;
+    let log = ExperimentLog::parse(&fetch_experiment(pool, &experiment_uuid).await?.contents)?;
+
+    let added = add_baselines(pool, &suite_name, &log, &inference_ids).await?;
+    println!("Added {} baselines to golden suite {}", added, suite_name);
+    Ok(added)
+}
+
+#[tauri::command]
+pub async fn get_golden_baselines(
+    state: tauri::State<'_, DatabaseState>,
+    suite_name: String,
+) -> Result<Vec<GoldenBaseline>, Error> {
+    get_baselines(&state.0, &suite_name).await
+}
+
+#[tauri::command]
+pub async fn delete_golden_baseline(
+    state: tauri::State<'_, DatabaseState>,
+    id: i64,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let result = sqlx::query("DELETE FROM golden_baselines WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError(format!("Baseline {} not found", id)));
+    }
+    Ok(())
+}
+
+/// Re-runs the baselines of a golden suite, on `model` instead of their own
+/// if set, and compares the responses with the baselines.
+#[tauri::command]
+pub async fn run_golden_suite(
+    state: tauri::State<'_, DatabaseState>,
+    config: IDefaultConfigs,
+    suite_name: String,
+    model: Option<String>,
+) -> Result<RegressionRun, Error> {
+    let pool = &state.0;
+    let run = run_suite(pool, &config, &suite_name, model.as_deref()).await?;
+    println!(
+        "Ran golden suite {}: {}/{} passed",
+        suite_name, run.passed_count, run.baseline_count
+    );
+    Ok(run)
+}
+
+/// The regression runs of a golden suite, or of every suite, latest first.
+#[tauri::command]
+pub async fn get_regression_runs(
+    state: tauri::State<'_, DatabaseState>,
+    suite_name: Option<String>,
+) -> Result<Vec<RegressionRun>, Error> {
+    get_runs(&state.0, suite_name.as_deref()).await
+}
@@ -0,0 +1,47 @@
+use crate::db::DatabaseState;
+
+use grid_search_desktop::reproducibility::{get_checks, verify_inferences, ReproducibilityCheck};
+use grid_search_desktop::{fetch_experiment, Error, ExperimentLog, IDefaultConfigs};
+
+const DEFAULT_RUNS: usize = 3;
+
+/// Re-runs inferences of an experiment with the same seed and options and
+/// compares the outputs with the logged ones.
+/// Runs are sent one after another unless `concurrent` is set.
+#[tauri::command]
+pub async fn verify_reproducibility(
+    state: tauri::State<'_, DatabaseState>,
+    config: IDefaultConfigs,
+    experiment_uuid: String,
+    inference_ids: Vec<String>,
+    runs: Option<usize>,
+    concurrent: Option<bool>,
+) -> Result<Vec<ReproducibilityCheck>, Error> {
+    let pool = &state.0;
+    let log = ExperimentLog::parse(&fetch_experiment(pool, &experiment_uuid).await?.contents)?;
+
+    let checks = verify_inferences(
+        pool,
+        &config,
+        &log,
+        &inference_ids,
+        runs.unwrap_or(DEFAULT_RUNS),
+        concurrent.unwrap_or(false),
+    )
+    .await?;
+    println!(
+        "Verified {} inferences of {}",
+        checks.len(),
+        experiment_uuid
+    );
+    Ok(checks)
+}
+
+/// The reproducibility checks of an experiment, latest first.
+#[tauri::command]
+pub async fn get_reproducibility_checks(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Vec<ReproducibilityCheck>, Error> {
+    get_checks(&state.0, &experiment_uuid).await
+}
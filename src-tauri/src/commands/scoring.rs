@@ -0,0 +1,89 @@
+use crate::db::DatabaseState;
+
+use grid_search_desktop::scoring::{
+    get_scores, score_inference, Assertion, InferenceScore, ASSERTION_SOURCE,
+};
+use grid_search_desktop::{fetch_experiment, Error, ExperimentLog};
+
+/// Sets (replaces) the assertions checked against every response to a prompt.
+#[tauri::command]
+pub async fn set_prompt_assertions(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+    assertions: Vec<Assertion>,
+) -> Result<(), Error> {
+    for assertion in &assertions {
+        assertion.validate()?;
+    }
+
+    let pool = &state.0;
+    let result = sqlx::query("UPDATE prompts SET assertions = $1 WHERE uuid = $2")
+        .bind(serde_json::to_string(&assertions)?)
+        .bind(&uuid)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError("Prompt not found".to_string()));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_prompt_assertions(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+) -> Result<Vec<Assertion>, Error> {
+    let pool = &state.0;
+    let (assertions,): (String,) = sqlx::query_as("SELECT assertions FROM prompts WHERE uuid = $1")
+        .bind(&uuid)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::StringError("Prompt not found".to_string()))?;
+
+    Ok(serde_json::from_str(&assertions)?)
+}
+
+/// Scores every successful inference of an experiment again, e.g.: after
+/// changing the assertions of its prompts. Returns the number of scores.
+#[tauri::command]
+pub async fn score_experiment(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<usize, Error> {
+    let pool = &state.0;
+    let experiment = fetch_experiment(pool, &experiment_uuid).await?;
+    let log = ExperimentLog::parse(&experiment.contents)?;
+
+    let mut count = 0;
+    for inference in &log.inferences {
+        let Some(result) = &inference.result else {
+            continue;
+        };
+        let scores = score_inference(
+            pool,
+            &experiment_uuid,
+            &inference.inference_id,
+            &inference.parameters,
+            &result.response,
+        )
+        .await?;
+        count += scores.len();
+    }
+
+    println!(
+        "Scored experiment {}: {} {} scores",
+        experiment_uuid, count, ASSERTION_SOURCE
+    );
+    Ok(count)
+}
+
+/// Returns the scores of an experiment's inferences, from every source.
+#[tauri::command]
+pub async fn get_inference_scores(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Vec<InferenceScore>, Error> {
+    let pool = &state.0;
+    get_scores(pool, &experiment_uuid).await
+}
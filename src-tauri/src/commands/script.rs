@@ -0,0 +1,86 @@
+use crate::db::DatabaseState;
+
+use grid_search_desktop::hooks::{attach_scripts, experiment_scripts, Script, ScriptInput};
+use grid_search_desktop::Error;
+
+/// Creates a script, or replaces the one with the same name.
+/// Scripts must compile and define at least one hook.
+#[tauri::command]
+pub async fn save_script(
+    state: tauri::State<'_, DatabaseState>,
+    input: ScriptInput,
+) -> Result<(), Error> {
+    input.validate()?;
+
+    let pool = &state.0;
+    let stmt = r#"
+        INSERT INTO scripts (name, description, source)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (name) DO UPDATE SET
+            description = excluded.description,
+            source = excluded.source,
+            last_modified = unixepoch('now')
+    "#;
+    sqlx::query(stmt)
+        .bind(input.name.trim())
+        .bind(input.description.trim())
+        .bind(&input.source)
+        .execute(pool)
+        .await?;
+
+    println!("Saved script: {}", input.name.trim());
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_scripts(state: tauri::State<'_, DatabaseState>) -> Result<Vec<Script>, Error> {
+    let pool = &state.0;
+    Ok(
+        sqlx::query_as::<_, Script>("SELECT * FROM scripts ORDER BY name")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+/// Deletes a script, detaching it from every experiment.
+#[tauri::command]
+pub async fn delete_script(
+    state: tauri::State<'_, DatabaseState>,
+    name: String,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let result = sqlx::query("DELETE FROM scripts WHERE name = $1")
+        .bind(&name)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError(format!("Script '{}' not found", name)));
+    }
+    Ok(())
+}
+
+/// Sets (replaces) the scripts attached to an experiment; they run in the
+/// order given. Can be called before the experiment's first inference is logged.
+#[tauri::command]
+pub async fn set_experiment_scripts(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+    names: Vec<String>,
+) -> Result<(), Error> {
+    attach_scripts(&state.0, &experiment_uuid, &names).await
+}
+
+/// Returns the names of the scripts attached to an experiment, in order.
+#[tauri::command]
+pub async fn get_experiment_scripts(
+    state: tauri::State<'_, DatabaseState>,
+    experiment_uuid: String,
+) -> Result<Vec<String>, Error> {
+    let pool = &state.0;
+    Ok(experiment_scripts(pool, &experiment_uuid)
+        .await?
+        .into_iter()
+        .map(|script| script.name)
+        .collect())
+}
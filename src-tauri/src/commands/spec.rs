@@ -0,0 +1,43 @@
+use crate::db::DatabaseState;
+use serde::Serialize;
+use std::path::Path;
+
+use grid_search_desktop::spec::{ExperimentSpec, SpecPrompt};
+use grid_search_desktop::Error;
+
+/// A spec loaded into the grid form, with the archived prompt (if any) each
+/// of its prompts was read from
+#[derive(Debug, Serialize)]
+pub struct LoadedExperimentSpec {
+    #[serde(flatten)]
+    pub spec: ExperimentSpec,
+    pub prompt_uuids: Vec<Option<String>>,
+}
+
+/// Reads a JSON, TOML or YAML experiment spec to fill the grid form.
+/// Prompts given by slug are replaced by their text, as the form holds the text.
+#[tauri::command]
+pub async fn load_experiment_spec(
+    state: tauri::State<'_, DatabaseState>,
+    path: String,
+) -> Result<LoadedExperimentSpec, Error> {
+    let mut spec = ExperimentSpec::load(Path::new(&path))?;
+    let resolved = spec.resolve_prompts(&state.0).await?;
+    let prompt_uuids = resolved.iter().map(|p| p.uuid.clone()).collect();
+    spec.prompts = resolved
+        .into_iter()
+        .map(|p| SpecPrompt::Text(p.prompt))
+        .collect();
+
+    println!("Loaded experiment spec: {}", path);
+    Ok(LoadedExperimentSpec { spec, prompt_uuids })
+}
+
+/// Writes the grid form as a JSON, TOML or YAML experiment spec.
+#[tauri::command]
+pub async fn save_experiment_spec(path: String, spec: ExperimentSpec) -> Result<(), Error> {
+    spec.save(Path::new(&path))?;
+
+    println!("Saved experiment spec: {}", path);
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use crate::commands::PromptInput;
+use crate::db::DatabaseState;
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+
+use grid_search_desktop::Error;
+
+// System prompts have no folders or tags, unlike the prompt library
+#[derive(Debug, FromRow, Serialize)]
+pub struct SystemPrompt {
+    pub uuid: String,
+    pub name: String,
+    pub slug: String,
+    pub prompt: String,
+    pub date_created: i64,  // Unix timestamp
+    pub last_modified: i64, // Unix timestamp
+}
+
+#[tauri::command]
+pub async fn create_system_prompt(
+    state: tauri::State<'_, DatabaseState>,
+    input: PromptInput,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let now = Utc::now().timestamp();
+
+    let stmt = r#"
+        INSERT INTO system_prompts (uuid, name, slug, prompt, date_created, last_modified)
+        VALUES ($1, $2, $3, $4, $5, $6)
+    "#;
+
+    sqlx::query(stmt)
+        .bind(&input.uuid)
+        .bind(&input.name)
+        .bind(&input.slug)
+        .bind(&input.prompt)
+        .bind(now)
+        .bind(now)
+        .execute(pool)
+        .await?;
+
+    println!("Created new system prompt: {} ({})", input.name, input.uuid);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_system_prompt(
+    state: tauri::State<'_, DatabaseState>,
+    input: PromptInput,
+) -> Result<(), Error> {
+    let pool = &state.0;
+    let now = Utc::now().timestamp();
+
+    let stmt = r#"
+        UPDATE system_prompts
+        SET name = $1,
+            slug = $2,
+            prompt = $3,
+            last_modified = $4
+        WHERE uuid = $5
+    "#;
+
+    let result = sqlx::query(stmt)
+        .bind(&input.name)
+        .bind(&input.slug)
+        .bind(&input.prompt)
+        .bind(now)
+        .bind(&input.uuid)
+        .execute(pool)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError("System prompt not found".to_string()));
+    }
+
+    println!("Updated system prompt: {} ({})", input.name, input.uuid);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_all_system_prompts(
+    state: tauri::State<'_, DatabaseState>,
+) -> Result<Vec<SystemPrompt>, Error> {
+    let stmt = r#"
+        SELECT
+            uuid,
+            name,
+            slug,
+            prompt,
+            date_created,
+            last_modified
+        FROM system_prompts
+        ORDER BY lower(name) ASC
+    "#;
+
+    let query = sqlx::query_as::<_, SystemPrompt>(stmt);
+    let pool = &state.0;
+    let prompts = query.fetch_all(pool).await?;
+
+    println!("\nRetrieved {} system prompts:", prompts.len());
+
+    Ok(prompts)
+}
+
+#[tauri::command]
+pub async fn delete_system_prompt(
+    state: tauri::State<'_, DatabaseState>,
+    uuid: String,
+) -> Result<(), Error> {
+    let pool = &state.0;
+
+    let stmt = "DELETE FROM system_prompts WHERE uuid = $1";
+
+    let result = sqlx::query(stmt).bind(&uuid).execute(pool).await?;
+
+    if result.rows_affected() == 0 {
+        return Err(Error::StringError("System prompt not found".to_string()));
+    }
+
+    println!("Deleted system prompt with UUID: {}", uuid);
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use crate::db::DatabaseState;
+use serde_json::{Map, Value};
+
+use grid_search_desktop::template::{self, prompt_includes};
+use grid_search_desktop::Error;
+
+/// Renders a prompt template so it can be previewed before running an experiment.
+#[tauri::command]
+pub async fn render_prompt(
+    state: tauri::State<'_, DatabaseState>,
+    template: String,
+    variables: Option<Map<String, Value>>,
+) -> Result<String, Error> {
+    let pool = &state.0;
+    let includes = prompt_includes(pool).await?;
+    template::render_prompt(&template, variables.as_ref(), &includes)
+}
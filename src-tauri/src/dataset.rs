@@ -0,0 +1,212 @@
+/*
+Datasets attached to an experiment.
+
+A dataset is a CSV or JSONL file where each row supplies values for the
+[placeholders] found in prompt templates (e.g.: "[input]", "[topic]").
+The grid is expanded as prompts x rows, so each inference is run with
+the placeholders of its prompt replaced by the values of a single row.
+*/
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::template::template_parts;
+use crate::Error;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Dataset {
+    pub path: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Map<String, Value>>,
+}
+
+// Same pattern used by the prompt textarea to navigate over placeholders.
+// Names start with a letter, so indexes in code (`arr[0]`, `m[i]`) are kept.
+pub(crate) fn placeholder_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\[([A-Za-z_]\w+)\]").unwrap())
+}
+
+/// Loads a dataset file, using its extension to pick the parser.
+pub fn load_dataset(path: &str) -> Result<Dataset, Error> {
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    let (columns, rows) = match extension.as_str() {
+        "csv" => read_csv(path)?,
+        "jsonl" | "ndjson" => read_jsonl(path)?,
+        _ => {
+            return Err(Error::StringError(format!(
+                "Unsupported dataset format: '{}'. Use a .csv or .jsonl file.",
+                path
+            )))
+        }
+    };
+
+    if rows.is_empty() {
+        return Err(Error::StringError(format!(
+            "Dataset '{}' has no rows.",
+            path
+        )));
+    }
+
+    Ok(Dataset {
+        path: path.to_string(),
+        columns,
+        rows,
+    })
+}
+
+type Rows = (Vec<String>, Vec<Map<String, Value>>);
+
+fn read_csv(path: &str) -> Result<Rows, Error> {
+    let mut reader = csv::Reader::from_path(path)?;
+    let columns: Vec<String> = reader.headers()?.iter().map(|h| h.to_string()).collect();
+
+    let mut rows = vec![];
+    for record in reader.records() {
+        let record = record?;
+        let row = columns
+            .iter()
+            .cloned()
+            .zip(record.iter().map(|value| Value::String(value.to_string())))
+            .collect();
+        rows.push(row);
+    }
+
+    Ok((columns, rows))
+}
+
+fn read_jsonl(path: &str) -> Result<Rows, Error> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut columns: Vec<String> = vec![];
+    let mut rows = vec![];
+
+    for (line_no, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let object = match serde_json::from_str::<Value>(&line)? {
+            Value::Object(object) => object,
+            _ => {
+                return Err(Error::StringError(format!(
+                    "Line {} of '{}' is not a JSON object.",
+                    line_no + 1,
+                    path
+                )))
+            }
+        };
+
+        // Values are kept as JSON so templates can loop over lists
+        for key in object.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+        rows.push(object);
+    }
+
+    Ok((columns, rows))
+}
+
+/// Returns the unique placeholder names used in a prompt template,
+/// outside of its template tags (e.g.: `{{ items[0] }}`).
+pub fn find_placeholders(template: &str) -> BTreeSet<String> {
+    template_parts(template)
+        .into_iter()
+        .filter(|(is_tag, _)| !is_tag)
+        .flat_map(|(_, text)| placeholder_regex().captures_iter(text))
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
+
+// Strings are used as-is, anything else is inserted as JSON text
+pub(crate) fn placeholder_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Replaces every [placeholder] in the template with its value.
+/// Placeholders without a matching value are reported as an error.
+pub fn fill_placeholders(template: &str, variables: &Map<String, Value>) -> Result<String, Error> {
+    let missing: Vec<String> = find_placeholders(template)
+        .into_iter()
+        .filter(|name| !variables.contains_key(name))
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(Error::StringError(format!(
+            "No value for placeholder(s) [{}] in prompt.",
+            missing.join("], [")
+        )));
+    }
+
+    Ok(placeholder_regex()
+        .replace_all(template, |cap: &regex::Captures| {
+            placeholder_text(&variables[&cap[1]])
+        })
+        .into_owned())
+}
+
+/// Checks that every placeholder in every prompt is a column in the dataset.
+pub fn validate_prompts(prompts: &[String], dataset: &Dataset) -> Result<(), Error> {
+    let mut errors = vec![];
+
+    for (idx, prompt) in prompts.iter().enumerate() {
+        let missing: Vec<String> = find_placeholders(prompt)
+            .into_iter()
+            .filter(|name| !dataset.columns.contains(name))
+            .collect();
+
+        if !missing.is_empty() {
+            errors.push(format!(
+                "Prompt #{} uses [{}], not found in the dataset columns.",
+                idx + 1,
+                missing.join("], [")
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::StringError(errors.join("\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholders_are_identifiers() {
+        let found = find_placeholders("Explain [topic] to a [level_2] reader: arr[0], m[i]");
+        assert_eq!(
+            found.into_iter().collect::<Vec<_>>(),
+            vec!["level_2".to_string(), "topic".to_string()]
+        );
+    }
+
+    #[test]
+    fn fill_reports_missing_placeholders() {
+        let variables: Map<String, Value> =
+            serde_json::from_str(r#"{"topic": "rust", "count": 3}"#).unwrap();
+        assert_eq!(
+            fill_placeholders("[topic] x[0] [count]", &variables).unwrap(),
+            "rust x[0] 3"
+        );
+        assert!(fill_placeholders("[topic] [level]", &variables).is_err());
+    }
+}
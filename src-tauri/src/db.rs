@@ -1,5 +1,6 @@
 use anyhow::Result;
-use sqlx::{sqlite::SqlitePool, Pool, Sqlite};
+use grid_search_desktop::open_database;
+use sqlx::{Pool, Sqlite};
 use std::env;
 use std::fs;
 use tauri::AppHandle;
@@ -27,18 +28,8 @@ impl Database {
         println!("Initializing database at: {:?}", db_path);
         println!("-----------------------------------------------");
 
-        // Create the connection options
-        let connection_options = sqlx::sqlite::SqliteConnectOptions::new()
-            .filename(&db_path)
-            .create_if_missing(true)
-            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
-
-        // Create and initialize the database pool
-        let pool = SqlitePool::connect_with(connection_options).await?;
-
         // Run migrations regardless of whether the database is new
-        // SQLx will track which migrations have been run
-        sqlx::migrate!("./migrations").run(&pool).await?;
+        let pool = open_database(&db_path).await?;
 
         Ok(Self { pool })
     }
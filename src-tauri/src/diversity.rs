@@ -0,0 +1,241 @@
+/*
+Output diversity across generations.
+
+The responses of a grid cell (a model and parameter combination, with the
+same prompt, system prompt and dataset row, over every generation) are
+compared pairwise:
+
+* jaccard: the overlap of the sets of words of two responses.
+* edit: 1 - the normalized Levenshtein distance, see `scoring`.
+* embedding: the cosine similarity of the embeddings of two responses, when
+  an embedding model is chosen.
+
+The diversity of a cell is 1 - its mean embedding similarity, or 1 - the
+mean of its lexical similarities without an embedding model. Cells are also
+averaged per parameter combination, so sweeps over temperature or top_p show
+how consistent each setting is.
+*/
+use ollama_rs::generation::embeddings::request::GenerateEmbeddingsRequest;
+use ollama_rs::Ollama;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tokio::time::{self, Duration};
+
+use crate::scoring::{edit_similarity, tokens};
+use crate::summary::ParamCombination;
+use crate::{split_host_port, Error, ExperimentLog, IDefaultConfigs};
+
+#[derive(Debug, Serialize)]
+pub struct CellDiversity {
+    pub combination: ParamCombination,
+    pub prompt: String,
+    pub system_prompt: String,
+    pub dataset_row: Option<usize>,
+    pub sample_count: usize,
+    // Number of different responses, ignoring surrounding whitespace
+    pub distinct_count: usize,
+    // Means over every pair of responses, from 0 to 1
+    pub jaccard_similarity: f64,
+    pub edit_similarity: f64,
+    pub embedding_similarity: Option<f64>,
+    pub diversity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CombinationDiversity {
+    pub combination: ParamCombination,
+    pub cell_count: usize,
+    // Mean of its cells
+    pub diversity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiversityReport {
+    pub experiment_uuid: String,
+    pub embedding_model: Option<String>,
+    // Only cells with at least two successful responses
+    pub cells: Vec<CellDiversity>,
+    // In the order each combination first appears in the experiment
+    pub combinations: Vec<CombinationDiversity>,
+}
+
+struct Cell<'a> {
+    combination: ParamCombination,
+    prompt: &'a str,
+    system_prompt: &'a str,
+    dataset_row: Option<usize>,
+    responses: Vec<&'a str>,
+}
+
+/// Groups the successful responses of an experiment by grid cell, in the
+/// order each cell first appears.
+fn cells(log: &ExperimentLog) -> Vec<Cell<'_>> {
+    let mut cells: Vec<Cell> = vec![];
+    let mut cell_index: HashMap<String, usize> = HashMap::new();
+
+    for inference in &log.inferences {
+        let Some(result) = &inference.result else {
+            continue;
+        };
+        let params = &inference.parameters;
+        let combination = ParamCombination::from(params);
+        let key = format!(
+            "{}\u{0}{}\u{0}{}\u{0}{:?}",
+            combination.key(),
+            params.prompt,
+            params.system_prompt,
+            params.dataset_row
+        );
+        let idx = *cell_index.entry(key).or_insert_with(|| {
+            cells.push(Cell {
+                combination,
+                prompt: &params.prompt,
+                system_prompt: &params.system_prompt,
+                dataset_row: params.dataset_row,
+                responses: vec![],
+            });
+            cells.len() - 1
+        });
+        cells[idx].responses.push(&result.response);
+    }
+
+    cells.retain(|cell| cell.responses.len() > 1);
+    cells
+}
+
+/// The overlap of two sets of words, from 0 to 1
+pub fn jaccard_similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(b).count() as f64 / union as f64
+}
+
+/// The cosine similarity of two embeddings
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        return 0.0;
+    }
+    dot / norms
+}
+
+/// Mean of a similarity over every pair of items
+fn mean_pairwise<T>(items: &[T], similarity: impl Fn(&T, &T) -> f64) -> f64 {
+    let mut sum = 0.0;
+    let mut pairs = 0;
+    for (i, a) in items.iter().enumerate() {
+        for b in &items[i + 1..] {
+            sum += similarity(a, b);
+            pairs += 1;
+        }
+    }
+    sum / pairs as f64
+}
+
+/// Embeds texts with a model of the server of `config`, giving up after its
+/// `request_timeout`.
+pub async fn embed(
+    config: &IDefaultConfigs,
+    model: &str,
+    texts: &[&str],
+) -> Result<Vec<Vec<f32>>, String> {
+    let (host_url, port) = split_host_port(&config.server_url).map_err(|err| err.to_string())?;
+    let ollama = Ollama::new(host_url, port);
+    let input: Vec<String> = texts.iter().map(|t| t.to_string()).collect();
+    let req = GenerateEmbeddingsRequest::new(model.to_string(), input.into());
+
+    let timeout = Duration::from_secs(config.request_timeout);
+    let embeddings = match time::timeout(timeout, ollama.generate_embeddings(req)).await {
+        Ok(result) => result.map_err(|err| err.to_string())?.embeddings,
+        Err(_) => {
+            return Err(format!(
+                "Embedding request timed out after {} seconds",
+                timeout.as_secs()
+            ))
+        }
+    };
+
+    if embeddings.len() != texts.len() {
+        return Err(format!(
+            "Expected {} embeddings from {}, got {}",
+            texts.len(),
+            model,
+            embeddings.len()
+        ));
+    }
+    Ok(embeddings)
+}
+
+/// Measures how different the responses of each grid cell are, optionally
+/// embedding them with `embedding_model`.
+pub async fn analyze_diversity(
+    config: &IDefaultConfigs,
+    log: &ExperimentLog,
+    embedding_model: Option<&str>,
+) -> Result<DiversityReport, Error> {
+    let mut cell_results = vec![];
+    for cell in cells(log) {
+        let words: Vec<HashSet<String>> = cell
+            .responses
+            .iter()
+            .map(|r| tokens(r).into_iter().collect())
+            .collect();
+        let jaccard = mean_pairwise(&words, jaccard_similarity);
+        let edit = mean_pairwise(&cell.responses, |a, b| edit_similarity(a, b));
+
+        let embedding = match embedding_model {
+            Some(model) => {
+                let embeddings = embed(config, model, &cell.responses).await.map_err(|err| {
+                    Error::StringError(format!("Could not embed responses: {}", err))
+                })?;
+                Some(mean_pairwise(&embeddings, |a, b| cosine_similarity(a, b)))
+            }
+            None => None,
+        };
+
+        let distinct: HashSet<&str> = cell.responses.iter().map(|r| r.trim()).collect();
+        cell_results.push(CellDiversity {
+            combination: cell.combination,
+            prompt: cell.prompt.to_string(),
+            system_prompt: cell.system_prompt.to_string(),
+            dataset_row: cell.dataset_row,
+            sample_count: cell.responses.len(),
+            distinct_count: distinct.len(),
+            jaccard_similarity: jaccard,
+            edit_similarity: edit,
+            embedding_similarity: embedding,
+            // Cosine similarity can be slightly negative
+            diversity: (1.0 - embedding.unwrap_or((jaccard + edit) / 2.0)).clamp(0.0, 1.0),
+        });
+    }
+
+    let mut combinations: Vec<(ParamCombination, Vec<f64>)> = vec![];
+    let mut combination_index: HashMap<String, usize> = HashMap::new();
+    for cell in &cell_results {
+        let idx = *combination_index
+            .entry(cell.combination.key())
+            .or_insert_with(|| {
+                combinations.push((cell.combination.clone(), vec![]));
+                combinations.len() - 1
+            });
+        combinations[idx].1.push(cell.diversity);
+    }
+
+    Ok(DiversityReport {
+        experiment_uuid: log.experiment_uuid.clone(),
+        embedding_model: embedding_model.map(String::from),
+        cells: cell_results,
+        combinations: combinations
+            .into_iter()
+            .map(|(combination, values)| CombinationDiversity {
+                combination,
+                cell_count: values.len(),
+                diversity: values.iter().sum::<f64>() / values.len() as f64,
+            })
+            .collect(),
+    })
+}
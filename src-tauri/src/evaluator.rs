@@ -0,0 +1,334 @@
+/*
+External evaluators.
+
+An evaluator is a command (e.g.: `python3 score.py`) run once per inference
+of the experiments it's attached to. It receives a JSON document on stdin:
+
+    {"experiment_uuid": "...", "inference_id": "...", "prompt": "...",
+     "params": {...}, "response": "...", "expected": "..." | null}
+
+and prints a JSON object on stdout:
+
+    {"score": 0.8, "passed": true, "detail": "...",
+     "metrics": {"fluency": 0.9, "accuracy": 0.7}}
+
+Every key is optional. `score` is stored under the name of the evaluator and
+each metric as "<evaluator>.<metric>", all from 0 to 1, in
+`inference_scores` with the "evaluator" source. A command that times out,
+exits with an error or prints something else gets a score without a value,
+the error as detail.
+*/
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::prelude::FromRow;
+use sqlx::{Pool, Sqlite};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::{self, Duration};
+
+use crate::scoring::{row_assertions, save_scores, ScoreResult};
+use crate::{Error, TParamIteration};
+
+/// Where a stored score comes from
+pub const EVALUATOR_SOURCE: &str = "evaluator";
+
+// Only the end of stderr is kept in the score detail
+const STDERR_TAIL: usize = 500;
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct Evaluator {
+    pub name: String,
+    pub command: String,
+    #[sqlx(json)]
+    pub args: Vec<String>,
+    pub working_dir: String,
+    pub timeout: i64, // In seconds
+    pub enabled: bool,
+    pub date_created: i64,
+    pub last_modified: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EvaluatorInput {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub working_dir: String,
+    #[serde(default = "default_timeout")]
+    pub timeout: i64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_timeout() -> i64 {
+    30
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl EvaluatorInput {
+    pub fn validate(&self) -> Result<(), Error> {
+        let name = self.name.trim();
+        // Metric names are "<evaluator>.<metric>"
+        if name.is_empty() || name.contains('.') {
+            return Err(Error::StringError(format!(
+                "Invalid evaluator name '{}', use a non-empty name without dots",
+                self.name
+            )));
+        }
+        if self.command.trim().is_empty() {
+            return Err(Error::StringError(
+                "The evaluator command can't be empty".to_string(),
+            ));
+        }
+        if self.timeout < 1 {
+            return Err(Error::StringError(format!(
+                "Invalid evaluator timeout {}, use at least 1 second",
+                self.timeout
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// What an evaluator receives on stdin
+#[derive(Debug, Serialize)]
+pub struct EvaluationInput<'a> {
+    pub experiment_uuid: &'a str,
+    pub inference_id: &'a str,
+    // As sent to the model
+    pub prompt: &'a str,
+    pub params: &'a TParamIteration,
+    pub response: &'a str,
+    pub expected: Option<String>,
+}
+
+/// What an evaluator prints on stdout
+#[derive(Debug, Deserialize)]
+struct EvaluationOutput {
+    #[serde(default)]
+    score: Option<f64>,
+    #[serde(default)]
+    passed: Option<bool>,
+    #[serde(default)]
+    detail: String,
+    #[serde(default)]
+    metrics: Map<String, Value>,
+}
+
+pub async fn enabled_evaluators(pool: &Pool<Sqlite>) -> Result<Vec<Evaluator>, Error> {
+    Ok(
+        sqlx::query_as::<_, Evaluator>("SELECT * FROM evaluators WHERE enabled = 1 ORDER BY name")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+/// The enabled evaluators attached to an experiment, by name.
+pub async fn experiment_evaluators(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+) -> Result<Vec<Evaluator>, Error> {
+    let stmt = r#"
+        SELECT e.*
+        FROM experiment_evaluators ee
+        JOIN evaluators e ON e.name = ee.evaluator_name
+        WHERE ee.experiment_uuid = $1 AND e.enabled = 1
+        ORDER BY e.name
+    "#;
+
+    Ok(sqlx::query_as::<_, Evaluator>(stmt)
+        .bind(experiment_uuid)
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Sets (replaces) the evaluators attached to an experiment.
+pub async fn attach_evaluators(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+    names: &[String],
+) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM experiment_evaluators WHERE experiment_uuid = $1")
+        .bind(experiment_uuid)
+        .execute(&mut *tx)
+        .await?;
+
+    for name in names {
+        let (found,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM evaluators WHERE name = $1")
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+        if found == 0 {
+            return Err(Error::StringError(format!(
+                "Evaluator '{}' not found",
+                name
+            )));
+        }
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO experiment_evaluators (experiment_uuid, evaluator_name) VALUES ($1, $2)",
+        )
+        .bind(experiment_uuid)
+        .bind(name)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Runs an evaluator on one inference. Failures are returned as a single
+/// score without a value.
+pub async fn run_evaluator(evaluator: &Evaluator, input: &EvaluationInput<'_>) -> Vec<ScoreResult> {
+    match run_command(evaluator, input).await {
+        Ok(output) => parse_output(&evaluator.name, &output),
+        Err(detail) => Err(detail),
+    }
+    .unwrap_or_else(|detail| {
+        vec![ScoreResult {
+            name: evaluator.name.clone(),
+            score: None,
+            passed: None,
+            detail,
+        }]
+    })
+}
+
+async fn run_command(evaluator: &Evaluator, input: &EvaluationInput<'_>) -> Result<String, String> {
+    let stdin = serde_json::to_vec(input).map_err(|err| err.to_string())?;
+
+    let mut command = Command::new(&evaluator.command);
+    command
+        .args(&evaluator.args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // A command that times out is killed when its future is dropped
+        .kill_on_drop(true);
+    if !evaluator.working_dir.is_empty() {
+        command.current_dir(&evaluator.working_dir);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|err| format!("Could not run '{}': {}", evaluator.command, err))?;
+    // Written from its own task, so a command that writes a lot before reading
+    // all its input doesn't block on a full stdout pipe
+    if let Some(mut pipe) = child.stdin.take() {
+        tokio::spawn(async move {
+            // The command may exit without reading its input; its exit status tells
+            let _ = pipe.write_all(&stdin).await;
+        });
+    }
+    let run = child.wait_with_output();
+
+    let timeout = Duration::from_secs(evaluator.timeout as u64);
+    let output = match time::timeout(timeout, run).await {
+        Ok(output) => output.map_err(|err| err.to_string())?,
+        Err(_) => return Err(format!("Timed out after {} seconds", timeout.as_secs())),
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        let tail_start = stderr
+            .char_indices()
+            .rev()
+            .nth(STDERR_TAIL)
+            .map_or(0, |(idx, _)| idx);
+        return Err(format!("{}: {}", output.status, &stderr[tail_start..]));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn parse_output(name: &str, stdout: &str) -> Result<Vec<ScoreResult>, String> {
+    let output: EvaluationOutput = serde_json::from_str(stdout.trim())
+        .map_err(|err| format!("Invalid evaluator output ({}): {}", err, stdout.trim()))?;
+    let check = |metric: &str, score: f64| {
+        if (0.0..=1.0).contains(&score) {
+            Ok(score)
+        } else {
+            Err(format!(
+                "The {} score {} is not between 0 and 1",
+                metric, score
+            ))
+        }
+    };
+
+    let mut results = vec![];
+    if output.score.is_some() || output.passed.is_some() {
+        results.push(ScoreResult {
+            name: name.to_string(),
+            score: output.score.map(|s| check(name, s)).transpose()?,
+            passed: output.passed,
+            detail: output.detail,
+        });
+    }
+    for (metric, value) in &output.metrics {
+        let metric_name = format!("{}.{}", name, metric);
+        let score = value
+            .as_f64()
+            .ok_or_else(|| format!("The {} metric isn't a number: {}", metric_name, value))?;
+        results.push(ScoreResult {
+            score: Some(check(&metric_name, score)?),
+            name: metric_name,
+            passed: None,
+            detail: String::new(),
+        });
+    }
+
+    if results.is_empty() {
+        return Err(format!("The evaluator printed no score: {}", stdout.trim()));
+    }
+    Ok(results)
+}
+
+/// Runs the enabled evaluators attached to the experiment on an inference
+/// and stores their scores, replacing the previous evaluator scores.
+pub async fn evaluate_inference(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+    inference_id: &str,
+    params: &TParamIteration,
+    prompt: &str,
+    response: &str,
+) -> Result<Vec<ScoreResult>, Error> {
+    let evaluators = experiment_evaluators(pool, experiment_uuid).await?;
+    if evaluators.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let (_, expected) = row_assertions(params.variables.as_ref())?;
+    let input = EvaluationInput {
+        experiment_uuid,
+        inference_id,
+        prompt,
+        params,
+        response,
+        expected,
+    };
+
+    let mut results = vec![];
+    for evaluator in &evaluators {
+        results.extend(run_evaluator(evaluator, &input).await);
+    }
+
+    save_scores(
+        pool,
+        experiment_uuid,
+        inference_id,
+        EVALUATOR_SOURCE,
+        &results,
+    )
+    .await?;
+    Ok(results)
+}
@@ -0,0 +1,421 @@
+/*
+Experiment exports.
+
+Experiments are flattened to one row per inference, with every parameter,
+the response, token counts, durations, derived performance metrics,
+ratings, scores and errors as columns. Rows can be written as CSV, JSONL or
+Parquet, ready to be loaded with pandas or DuckDB.
+*/
+use parquet::basic::Compression;
+use parquet::column::writer::ColumnWriterImpl;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, DataType, DoubleType, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::judge::JUDGE_SOURCE;
+use crate::reasoning::token_split;
+use crate::scoring::InferenceScore;
+use crate::summary::{InferenceMetrics, InferenceRating};
+use crate::{Error, ExperimentLog};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Picks the format from the file extension
+    pub fn from_path(path: &str) -> Result<Self, Error> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "jsonl" | "ndjson" => Ok(ExportFormat::Jsonl),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(Error::StringError(format!(
+                "Unsupported export format: '{}'. Use a .csv, .jsonl or .parquet file.",
+                path
+            ))),
+        }
+    }
+}
+
+/// One inference, flattened
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ExportRow {
+    pub experiment_uuid: String,
+    pub experiment_name: String,
+    pub inference_id: String,
+    pub inference_index: i64,
+    pub model: String,
+    pub system_prompt: String,
+    pub prompt: String,
+    pub rendered_prompt: String,
+    pub dataset_row: Option<i64>,
+    pub temperature: f64,
+    pub repeat_penalty: f64,
+    pub top_k: i64,
+    pub top_p: f64,
+    pub repeat_last_n: i64,
+    pub tfs_z: f64,
+    pub mirostat: i64,
+    pub mirostat_tau: f64,
+    pub mirostat_eta: f64,
+    pub seed: i64,
+    pub think: Option<bool>,
+    pub response: Option<String>,
+    // The reasoning of reasoning models, apart from the response
+    pub thinking: Option<String>,
+    pub error: Option<String>,
+    pub created_at: Option<String>,
+    pub prompt_eval_count: Option<i64>,
+    pub eval_count: Option<i64>,
+    // eval_count split between reasoning and answer, see `reasoning`
+    pub reasoning_tokens: Option<i64>,
+    pub answer_tokens: Option<i64>,
+    // Durations are in nanoseconds, as reported by Ollama
+    pub load_duration: Option<i64>,
+    pub prompt_eval_duration: Option<i64>,
+    pub eval_duration: Option<i64>,
+    pub total_duration: Option<i64>,
+    // eval_count / total_duration, same as the results pane
+    pub tokens_per_second: Option<f64>,
+    // See `InferenceMetrics`; times are in seconds
+    pub prompt_tokens_per_second: Option<f64>,
+    pub generation_tokens_per_second: Option<f64>,
+    pub time_to_first_token: Option<f64>,
+    pub load_time: Option<f64>,
+    pub thumb: Option<i64>,
+    pub rating: Option<i64>,
+    pub rubric: Option<String>,
+    pub note: Option<String>,
+    // JSON object of score name -> score, from every source
+    pub scores: Option<String>,
+    pub mean_score: Option<f64>,
+    // Normalized to 0-1, see `judge`
+    pub judge_score: Option<f64>,
+    pub judge_rationale: Option<String>,
+}
+
+/// Flattens an experiment log into one row per inference.
+pub fn export_rows(
+    log: &ExperimentLog,
+    experiment_name: &str,
+    ratings: &[InferenceRating],
+    scores: &[InferenceScore],
+) -> Vec<ExportRow> {
+    let ratings: HashMap<&str, &InferenceRating> = ratings
+        .iter()
+        .map(|r| (r.inference_id.as_str(), r))
+        .collect();
+    let mut scores_by_inference: HashMap<&str, BTreeMap<&str, f64>> = HashMap::new();
+    let mut verdicts: HashMap<&str, &InferenceScore> = HashMap::new();
+    for score in scores {
+        if score.source == JUDGE_SOURCE {
+            verdicts.insert(score.inference_id.as_str(), score);
+        }
+        if let Some(value) = score.score {
+            scores_by_inference
+                .entry(score.inference_id.as_str())
+                .or_default()
+                .insert(score.name.as_str(), value);
+        }
+    }
+
+    log.inferences
+        .iter()
+        .enumerate()
+        .map(|(idx, inference)| {
+            let params = &inference.parameters;
+            let result = inference.result.as_ref();
+            let rating = ratings.get(inference.inference_id.as_str());
+            let scores = scores_by_inference.get(inference.inference_id.as_str());
+            let verdict = verdicts.get(inference.inference_id.as_str());
+            let metrics = result
+                .map(InferenceMetrics::from_response)
+                .unwrap_or_default();
+            let nanos = |value: Option<u64>| value.map(|v| v as i64);
+            let tokens = result.and_then(token_split);
+
+            ExportRow {
+                experiment_uuid: log.experiment_uuid.clone(),
+                experiment_name: experiment_name.to_string(),
+                inference_id: inference.inference_id.clone(),
+                inference_index: idx as i64,
+                model: params.model.clone(),
+                system_prompt: params.system_prompt.clone(),
+                prompt: params.prompt.clone(),
+                rendered_prompt: inference
+                    .rendered_prompt
+                    .clone()
+                    .unwrap_or_else(|| params.prompt.clone()),
+                dataset_row: params.dataset_row.map(|row| row as i64),
+                temperature: widen(params.temperature),
+                repeat_penalty: widen(params.repeat_penalty),
+                top_k: params.top_k as i64,
+                top_p: widen(params.top_p),
+                repeat_last_n: params.repeat_last_n as i64,
+                tfs_z: widen(params.tfs_z),
+                mirostat: params.mirostat as i64,
+                mirostat_tau: widen(params.mirostat_tau),
+                mirostat_eta: widen(params.mirostat_eta),
+                seed: params.seed as i64,
+                think: params.think,
+                response: result.map(|r| r.response.clone()),
+                thinking: result.and_then(|r| r.thinking.clone()),
+                error: inference.error.clone(),
+                created_at: result.map(|r| r.created_at.clone()),
+                prompt_eval_count: result.and_then(|r| r.prompt_eval_count).map(|v| v as i64),
+                eval_count: result.and_then(|r| r.eval_count).map(|v| v as i64),
+                reasoning_tokens: tokens.map(|(reasoning, _)| reasoning as i64),
+                answer_tokens: tokens.map(|(_, answer)| answer as i64),
+                load_duration: nanos(result.and_then(|r| r.load_duration)),
+                prompt_eval_duration: nanos(result.and_then(|r| r.prompt_eval_duration)),
+                eval_duration: nanos(result.and_then(|r| r.eval_duration)),
+                total_duration: nanos(result.and_then(|r| r.total_duration)),
+                tokens_per_second: result.and_then(|r| match (r.eval_count, r.total_duration) {
+                    (Some(count), Some(duration)) if duration > 0 => {
+                        Some(count as f64 / (duration as f64 / 1e9))
+                    }
+                    _ => None,
+                }),
+                prompt_tokens_per_second: metrics.prompt_tokens_per_second,
+                generation_tokens_per_second: metrics.generation_tokens_per_second,
+                time_to_first_token: metrics.time_to_first_token,
+                load_time: metrics.load_time,
+                thumb: rating.and_then(|r| r.thumb),
+                rating: rating.and_then(|r| r.rating),
+                rubric: rating
+                    .filter(|r| !r.rubric.is_empty())
+                    .and_then(|r| serde_json::to_string(&r.rubric).ok()),
+                note: rating.map(|r| r.note.clone()).filter(|n| !n.is_empty()),
+                scores: scores.and_then(|s| serde_json::to_string(s).ok()),
+                mean_score: scores.map(|s| s.values().sum::<f64>() / s.len() as f64),
+                judge_score: verdict.and_then(|v| v.score),
+                judge_rationale: verdict.map(|v| v.detail.clone()),
+            }
+        })
+        .collect()
+}
+
+// f32 -> f64 without the noise of a plain cast (0.1 instead of 0.10000000149011612)
+fn widen(value: f32) -> f64 {
+    value.to_string().parse().unwrap_or(value as f64)
+}
+
+/// Writes the rows to `path` in the given format.
+pub fn write_rows(path: &str, format: ExportFormat, rows: &[ExportRow]) -> Result<(), Error> {
+    match format {
+        ExportFormat::Csv => {
+            let mut writer = csv::Writer::from_path(path)?;
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Jsonl => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            for row in rows {
+                serde_json::to_writer(&mut writer, row)?;
+                writer.write_all(b"\n")?;
+            }
+            writer.flush()?;
+        }
+        ExportFormat::Parquet => write_parquet(path, rows)?,
+    }
+
+    Ok(())
+}
+
+const PARQUET_SCHEMA: &str = "
+message inference {
+    REQUIRED BYTE_ARRAY experiment_uuid (UTF8);
+    REQUIRED BYTE_ARRAY experiment_name (UTF8);
+    REQUIRED BYTE_ARRAY inference_id (UTF8);
+    REQUIRED INT64 inference_index;
+    REQUIRED BYTE_ARRAY model (UTF8);
+    REQUIRED BYTE_ARRAY system_prompt (UTF8);
+    REQUIRED BYTE_ARRAY prompt (UTF8);
+    REQUIRED BYTE_ARRAY rendered_prompt (UTF8);
+    OPTIONAL INT64 dataset_row;
+    REQUIRED DOUBLE temperature;
+    REQUIRED DOUBLE repeat_penalty;
+    REQUIRED INT64 top_k;
+    REQUIRED DOUBLE top_p;
+    REQUIRED INT64 repeat_last_n;
+    REQUIRED DOUBLE tfs_z;
+    REQUIRED INT64 mirostat;
+    REQUIRED DOUBLE mirostat_tau;
+    REQUIRED DOUBLE mirostat_eta;
+    REQUIRED INT64 seed;
+    OPTIONAL BOOLEAN think;
+    OPTIONAL BYTE_ARRAY response (UTF8);
+    OPTIONAL BYTE_ARRAY thinking (UTF8);
+    OPTIONAL BYTE_ARRAY error (UTF8);
+    OPTIONAL BYTE_ARRAY created_at (UTF8);
+    OPTIONAL INT64 prompt_eval_count;
+    OPTIONAL INT64 eval_count;
+    OPTIONAL INT64 reasoning_tokens;
+    OPTIONAL INT64 answer_tokens;
+    OPTIONAL INT64 load_duration;
+    OPTIONAL INT64 prompt_eval_duration;
+    OPTIONAL INT64 eval_duration;
+    OPTIONAL INT64 total_duration;
+    OPTIONAL DOUBLE tokens_per_second;
+    OPTIONAL DOUBLE prompt_tokens_per_second;
+    OPTIONAL DOUBLE generation_tokens_per_second;
+    OPTIONAL DOUBLE time_to_first_token;
+    OPTIONAL DOUBLE load_time;
+    OPTIONAL INT64 thumb;
+    OPTIONAL INT64 rating;
+    OPTIONAL BYTE_ARRAY rubric (UTF8);
+    OPTIONAL BYTE_ARRAY note (UTF8);
+    OPTIONAL BYTE_ARRAY scores (UTF8);
+    OPTIONAL DOUBLE mean_score;
+    OPTIONAL DOUBLE judge_score;
+    OPTIONAL BYTE_ARRAY judge_rationale (UTF8);
+}
+";
+
+// A column of values, in the same order as PARQUET_SCHEMA
+enum Column {
+    Text(Vec<Option<String>>),
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Bool(Vec<Option<bool>>),
+}
+
+fn parquet_columns(rows: &[ExportRow]) -> Vec<Column> {
+    let text = |f: fn(&ExportRow) -> Option<String>| Column::Text(rows.iter().map(f).collect());
+    let int = |f: fn(&ExportRow) -> Option<i64>| Column::Int(rows.iter().map(f).collect());
+    let float = |f: fn(&ExportRow) -> Option<f64>| Column::Float(rows.iter().map(f).collect());
+    let boolean = |f: fn(&ExportRow) -> Option<bool>| Column::Bool(rows.iter().map(f).collect());
+
+    vec![
+        text(|r| Some(r.experiment_uuid.clone())),
+        text(|r| Some(r.experiment_name.clone())),
+        text(|r| Some(r.inference_id.clone())),
+        int(|r| Some(r.inference_index)),
+        text(|r| Some(r.model.clone())),
+        text(|r| Some(r.system_prompt.clone())),
+        text(|r| Some(r.prompt.clone())),
+        text(|r| Some(r.rendered_prompt.clone())),
+        int(|r| r.dataset_row),
+        float(|r| Some(r.temperature)),
+        float(|r| Some(r.repeat_penalty)),
+        int(|r| Some(r.top_k)),
+        float(|r| Some(r.top_p)),
+        int(|r| Some(r.repeat_last_n)),
+        float(|r| Some(r.tfs_z)),
+        int(|r| Some(r.mirostat)),
+        float(|r| Some(r.mirostat_tau)),
+        float(|r| Some(r.mirostat_eta)),
+        int(|r| Some(r.seed)),
+        boolean(|r| r.think),
+        text(|r| r.response.clone()),
+        text(|r| r.thinking.clone()),
+        text(|r| r.error.clone()),
+        text(|r| r.created_at.clone()),
+        int(|r| r.prompt_eval_count),
+        int(|r| r.eval_count),
+        int(|r| r.reasoning_tokens),
+        int(|r| r.answer_tokens),
+        int(|r| r.load_duration),
+        int(|r| r.prompt_eval_duration),
+        int(|r| r.eval_duration),
+        int(|r| r.total_duration),
+        float(|r| r.tokens_per_second),
+        float(|r| r.prompt_tokens_per_second),
+        float(|r| r.generation_tokens_per_second),
+        float(|r| r.time_to_first_token),
+        float(|r| r.load_time),
+        int(|r| r.thumb),
+        int(|r| r.rating),
+        text(|r| r.rubric.clone()),
+        text(|r| r.note.clone()),
+        text(|r| r.scores.clone()),
+        float(|r| r.mean_score),
+        float(|r| r.judge_score),
+        text(|r| r.judge_rationale.clone()),
+    ]
+}
+
+// Splits optional values into the non-null values and their definition levels
+fn levels<T: Clone>(values: &[Option<T>]) -> (Vec<T>, Vec<i16>) {
+    let defined = values.iter().flatten().cloned().collect();
+    let def_levels = values.iter().map(|v| v.is_some() as i16).collect();
+    (defined, def_levels)
+}
+
+fn write_parquet(path: &str, rows: &[ExportRow]) -> Result<(), Error> {
+    let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+    let props = Arc::new(
+        WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build(),
+    );
+
+    let mut writer = SerializedFileWriter::new(File::create(path)?, schema, props)?;
+    let mut row_group = writer.next_row_group()?;
+    let mut columns = parquet_columns(rows).into_iter();
+
+    while let Some(mut column_writer) = row_group.next_column()? {
+        match columns.next() {
+            Some(Column::Text(values)) => {
+                let (values, def_levels) = levels(&values);
+                let values: Vec<ByteArray> = values
+                    .into_iter()
+                    .map(|v| ByteArray::from(v.into_bytes()))
+                    .collect();
+                write_column(column_writer.typed::<ByteArrayType>(), &values, &def_levels)?;
+            }
+            Some(Column::Int(values)) => {
+                let (values, def_levels) = levels(&values);
+                write_column(column_writer.typed::<Int64Type>(), &values, &def_levels)?;
+            }
+            Some(Column::Float(values)) => {
+                let (values, def_levels) = levels(&values);
+                write_column(column_writer.typed::<DoubleType>(), &values, &def_levels)?;
+            }
+            Some(Column::Bool(values)) => {
+                let (values, def_levels) = levels(&values);
+                write_column(column_writer.typed::<BoolType>(), &values, &def_levels)?;
+            }
+            None => {
+                return Err(Error::StringError(
+                    "Parquet schema and export columns don't match".to_string(),
+                ))
+            }
+        }
+        column_writer.close()?;
+    }
+
+    row_group.close()?;
+    writer.close()?;
+    Ok(())
+}
+
+fn write_column<T: DataType>(
+    writer: &mut ColumnWriterImpl<T>,
+    values: &[T::T],
+    def_levels: &[i16],
+) -> Result<(), Error> {
+    // Required columns take no definition levels
+    let optional = writer.get_descriptor().max_def_level() > 0;
+    writer.write_batch(values, optional.then_some(def_levels), None)?;
+    Ok(())
+}
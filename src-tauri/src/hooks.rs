@@ -0,0 +1,310 @@
+/*
+Scripting hooks.
+
+Scripts are small Rhai programs stored in the database and attached to
+experiments, in order. A script defines one or both hook functions:
+
+    // Before the request is sent; `params` is the TParamIteration, with the
+    // rendered prompt. Returns the params to use.
+    fn pre_inference(params) {
+        params.prompt = "Answer briefly. " + params.prompt;
+        params
+    }
+
+    // Before the response is logged; `response` is the GenerationResponse.
+    // Returns the response to log. Scores (0 to 1) set in `response.scores`
+    // are stored with the "hook" source.
+    fn post_inference(response, params) {
+        response.response = regex_replace(response.response, "(?s)<think>.*?</think>", "");
+        response.scores = #{ has_code: if regex_match(response.response, "```") { 1.0 } else { 0.0 } };
+        response
+    }
+
+Besides the Rhai standard library, scripts can call `regex_match(text,
+pattern)`, `regex_replace(text, pattern, replacement)` and
+`regex_captures(text, pattern)`. Scripts run with limits on operations and
+sizes, so a runaway loop fails the inference instead of hanging it.
+*/
+use ollama_rs::generation::completion::GenerationResponse;
+use regex::Regex;
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+use sqlx::{Pool, Sqlite};
+use std::collections::BTreeMap;
+
+use crate::scoring::ScoreResult;
+use crate::{Error, TParamIteration};
+
+/// Where a stored score comes from
+pub const HOOK_SOURCE: &str = "hook";
+
+pub const PRE_HOOK: &str = "pre_inference";
+pub const POST_HOOK: &str = "post_inference";
+
+// The key of the scores a post-hook returns with the response
+const SCORES_KEY: &str = "scores";
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct Script {
+    pub name: String,
+    pub description: String,
+    pub source: String,
+    pub date_created: i64,  // Unix timestamp
+    pub last_modified: i64, // Unix timestamp
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScriptInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub source: String,
+}
+
+impl ScriptInput {
+    /// The name must be set and the script must compile and define a hook
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::StringError(
+                "The script name can't be empty".to_string(),
+            ));
+        }
+        let hooks = CompiledScript::new(&self.name, &self.source).map_err(Error::StringError)?;
+        if !hooks.has(PRE_HOOK) && !hooks.has(POST_HOOK) {
+            return Err(Error::StringError(format!(
+                "Script '{}' must define {}(params) or {}(response, params)",
+                self.name, PRE_HOOK, POST_HOOK
+            )));
+        }
+        Ok(())
+    }
+}
+
+struct CompiledScript<'a> {
+    name: &'a str,
+    engine: Engine,
+    ast: AST,
+}
+
+impl<'a> CompiledScript<'a> {
+    fn new(name: &'a str, source: &str) -> Result<Self, String> {
+        let engine = engine();
+        let ast = engine
+            .compile(source)
+            .map_err(|err| format!("Script '{}': {}", name, err))?;
+        Ok(Self { name, engine, ast })
+    }
+
+    fn has(&self, hook: &str) -> bool {
+        self.ast.iter_functions().any(|f| f.name == hook)
+    }
+
+    fn call(&self, hook: &str, args: impl rhai::FuncArgs) -> Result<Value, String> {
+        let result: Dynamic = self
+            .engine
+            .call_fn(&mut Scope::new(), &self.ast, hook, args)
+            .map_err(|err| self.error(hook, err))?;
+        from_dynamic(&result).map_err(|err| self.error(hook, err))
+    }
+
+    fn error(&self, hook: &str, err: impl std::fmt::Display) -> String {
+        format!("Script '{}', {}: {}", self.name, hook, err)
+    }
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(1_000_000)
+        .set_max_call_levels(32)
+        .set_max_string_size(10_000_000)
+        .set_max_array_size(100_000)
+        .set_max_map_size(10_000);
+
+    engine
+        .register_fn(
+            "regex_match",
+            |text: &str, pattern: &str| -> Result<bool, Box<EvalAltResult>> {
+                Ok(regex(pattern)?.is_match(text))
+            },
+        )
+        .register_fn(
+            "regex_replace",
+            |text: &str, pattern: &str, replacement: &str| -> Result<String, Box<EvalAltResult>> {
+                Ok(regex(pattern)?.replace_all(text, replacement).into_owned())
+            },
+        )
+        .register_fn(
+            "regex_captures",
+            |text: &str, pattern: &str| -> Result<Array, Box<EvalAltResult>> {
+                // The groups of the first match; an empty array without a match
+                Ok(regex(pattern)?
+                    .captures(text)
+                    .map(|caps| {
+                        caps.iter()
+                            .map(|group| {
+                                Dynamic::from(
+                                    group.map_or(String::new(), |g| g.as_str().to_string()),
+                                )
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default())
+            },
+        );
+    engine
+}
+
+fn regex(pattern: &str) -> Result<Regex, Box<EvalAltResult>> {
+    Regex::new(pattern).map_err(|err| format!("Invalid regex '{}': {}", pattern, err).into())
+}
+
+/// The scripts attached to an experiment, in the order they run.
+pub async fn experiment_scripts(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+) -> Result<Vec<Script>, Error> {
+    let stmt = r#"
+        SELECT s.*
+        FROM experiment_scripts es
+        JOIN scripts s ON s.name = es.script_name
+        WHERE es.experiment_uuid = $1
+        ORDER BY es.position
+    "#;
+
+    Ok(sqlx::query_as::<_, Script>(stmt)
+        .bind(experiment_uuid)
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Scripts by name, in the given order.
+pub async fn named_scripts(pool: &Pool<Sqlite>, names: &[String]) -> Result<Vec<Script>, Error> {
+    let mut scripts = Vec::with_capacity(names.len());
+    for name in names {
+        let script = sqlx::query_as::<_, Script>("SELECT * FROM scripts WHERE name = $1")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .ok_or_else(|| Error::StringError(format!("Script '{}' not found", name)))?;
+        scripts.push(script);
+    }
+    Ok(scripts)
+}
+
+/// Sets (replaces) the scripts attached to an experiment, in the order they run.
+pub async fn attach_scripts(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+    names: &[String],
+) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM experiment_scripts WHERE experiment_uuid = $1")
+        .bind(experiment_uuid)
+        .execute(&mut *tx)
+        .await?;
+
+    for (position, name) in names.iter().enumerate() {
+        let (found,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scripts WHERE name = $1")
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+        if found == 0 {
+            return Err(Error::StringError(format!("Script '{}' not found", name)));
+        }
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO experiment_scripts (experiment_uuid, script_name, position) VALUES ($1, $2, $3)",
+        )
+        .bind(experiment_uuid)
+        .bind(name)
+        .bind(position as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Runs the pre-hooks of the scripts, in order. `params.prompt` is the
+/// rendered prompt. The experiment and dataset row can't be changed.
+/// Errors are the messages logged with failed inferences.
+pub fn run_pre_hooks(
+    scripts: &[Script],
+    params: &TParamIteration,
+) -> Result<TParamIteration, String> {
+    let mut current = params.clone();
+    for script in scripts {
+        let compiled = CompiledScript::new(&script.name, &script.source)?;
+        if !compiled.has(PRE_HOOK) {
+            continue;
+        }
+
+        let args = (to_dynamic(&current).map_err(|err| compiled.error(PRE_HOOK, err))?,);
+        let result = compiled.call(PRE_HOOK, args)?;
+        current = serde_json::from_value(result).map_err(|err| compiled.error(PRE_HOOK, err))?;
+    }
+
+    current.experiment_uuid = params.experiment_uuid.clone();
+    current.dataset_row = params.dataset_row;
+    current.variables = params.variables.clone();
+    Ok(current)
+}
+
+/// Runs the post-hooks of the scripts, in order, returning the response to
+/// log and the scores the scripts gave.
+pub fn run_post_hooks(
+    scripts: &[Script],
+    params: &TParamIteration,
+    response: GenerationResponse,
+) -> Result<(GenerationResponse, Vec<ScoreResult>), String> {
+    let mut current = response;
+    let mut scores: BTreeMap<String, ScoreResult> = BTreeMap::new();
+    for script in scripts {
+        let compiled = CompiledScript::new(&script.name, &script.source)?;
+        if !compiled.has(POST_HOOK) {
+            continue;
+        }
+
+        let args = (
+            to_dynamic(&current).map_err(|err| compiled.error(POST_HOOK, err))?,
+            to_dynamic(params).map_err(|err| compiled.error(POST_HOOK, err))?,
+        );
+        let mut result = compiled.call(POST_HOOK, args)?;
+
+        if let Some(returned) = result.as_object_mut().and_then(|r| r.remove(SCORES_KEY)) {
+            let Value::Object(returned) = returned else {
+                return Err(compiled.error(
+                    POST_HOOK,
+                    format!("'{}' must be a map of name -> score", SCORES_KEY),
+                ));
+            };
+            // A later script can overwrite the score of an earlier one
+            for (name, value) in returned {
+                scores.insert(name.clone(), hook_score(name, &value));
+            }
+        }
+        current = serde_json::from_value(result).map_err(|err| compiled.error(POST_HOOK, err))?;
+    }
+
+    Ok((current, scores.into_values().collect()))
+}
+
+fn hook_score(name: String, value: &Value) -> ScoreResult {
+    let (score, detail) = match value.as_f64() {
+        Some(score) if (0.0..=1.0).contains(&score) => (Some(score), String::new()),
+        Some(score) => (None, format!("The score {} is not between 0 and 1", score)),
+        None => (None, format!("The score isn't a number: {}", value)),
+    };
+    ScoreResult {
+        name,
+        score,
+        passed: None,
+        detail,
+    }
+}
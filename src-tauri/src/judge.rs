@@ -0,0 +1,244 @@
+/*
+LLM-as-judge evaluation.
+
+A judge model and a rubric prompt are attached to an experiment. The judge
+grades each response against the rubric and answers with a JSON object,
+e.g.: {"score": 7, "rationale": "Correct, but verbose."}, on the scale of
+the experiment. Scores are normalized to 0-1 and stored in
+`inference_scores` with the "judge" source, the rationale as their detail.
+
+Judge requests go to the same server, with the same timeout, as the
+inferences (see `generate`). A judge that fails or answers with something
+that can't be parsed gives a score without a value, the error as detail.
+*/
+use ollama_rs::generation::completion::request::GenerationRequest;
+use ollama_rs::generation::parameters::FormatType;
+use ollama_rs::models::ModelOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+use sqlx::{Pool, Sqlite};
+use std::fmt::Write;
+
+use crate::scoring::{row_assertions, save_scores, ScoreResult};
+use crate::{generate, Error, IDefaultConfigs, TParamIteration};
+
+/// Where a stored score comes from
+pub const JUDGE_SOURCE: &str = "judge";
+
+const JUDGE_SYSTEM_PROMPT: &str = "You are a strict and impartial evaluator. \
+    You grade responses against a rubric and answer only with JSON.";
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct ExperimentJudge {
+    pub experiment_uuid: String,
+    pub model: String,
+    pub rubric: String,
+    pub min_score: f64,
+    pub max_score: f64,
+    // Judge each inference right after it's generated, instead of in a batch
+    pub auto: bool,
+    pub last_modified: i64, // Unix timestamp
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct JudgeInput {
+    pub model: String,
+    pub rubric: String,
+    #[serde(default = "default_min_score")]
+    pub min_score: f64,
+    #[serde(default = "default_max_score")]
+    pub max_score: f64,
+    #[serde(default = "default_auto")]
+    pub auto: bool,
+}
+
+fn default_min_score() -> f64 {
+    1.0
+}
+
+fn default_max_score() -> f64 {
+    10.0
+}
+
+fn default_auto() -> bool {
+    true
+}
+
+impl JudgeInput {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.model.trim().is_empty() {
+            return Err(Error::StringError("Select a judge model".to_string()));
+        }
+        if self.rubric.trim().is_empty() {
+            return Err(Error::StringError(
+                "The judge rubric can't be empty".to_string(),
+            ));
+        }
+        if self.min_score >= self.max_score {
+            return Err(Error::StringError(format!(
+                "Invalid judge scale {} to {}, the minimum must be lower than the maximum",
+                self.min_score, self.max_score
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Sets (replaces) the judge model and rubric of an experiment.
+pub async fn set_judge(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+    judge: &JudgeInput,
+) -> Result<(), Error> {
+    judge.validate()?;
+
+    let stmt = r#"
+        INSERT INTO experiment_judges (experiment_uuid, model, rubric, min_score, max_score, auto)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (experiment_uuid) DO UPDATE SET
+            model = excluded.model,
+            rubric = excluded.rubric,
+            min_score = excluded.min_score,
+            max_score = excluded.max_score,
+            auto = excluded.auto,
+            last_modified = unixepoch('now')
+    "#;
+    sqlx::query(stmt)
+        .bind(experiment_uuid)
+        .bind(judge.model.trim())
+        .bind(judge.rubric.trim())
+        .bind(judge.min_score)
+        .bind(judge.max_score)
+        .bind(judge.auto)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// The judge of an experiment, if it has one.
+pub async fn get_judge(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+) -> Result<Option<ExperimentJudge>, Error> {
+    Ok(sqlx::query_as::<_, ExperimentJudge>(
+        "SELECT * FROM experiment_judges WHERE experiment_uuid = $1",
+    )
+    .bind(experiment_uuid)
+    .fetch_optional(pool)
+    .await?)
+}
+
+/// The request sent to the judge for one response.
+pub fn judge_prompt(
+    judge: &ExperimentJudge,
+    prompt: &str,
+    response: &str,
+    expected: Option<&str>,
+) -> String {
+    let mut text = format!(
+        "Grade the response below according to this rubric:\n\n{}\n\n## Prompt\n\n{}\n\n",
+        judge.rubric.trim(),
+        prompt.trim()
+    );
+    if let Some(expected) = expected {
+        let _ = write!(text, "## Expected output\n\n{}\n\n", expected.trim());
+    }
+    let _ = write!(
+        text,
+        "## Response\n\n{}\n\n\
+        Answer with a JSON object with two keys: \"score\", a number from {} to {} \
+        ({} is the best), and \"rationale\", one or two sentences explaining the score.",
+        response.trim(),
+        judge.min_score,
+        judge.max_score,
+        judge.max_score
+    );
+    text
+}
+
+/// Reads the score and rationale of a judge answer, normalizing the score
+/// to 0-1. The detail keeps the raw score: "7/10: Correct, but verbose."
+pub fn parse_verdict(judge: &ExperimentJudge, answer: &str) -> Result<ScoreResult, String> {
+    // Some models wrap the object in text or code fences, despite the JSON format
+    let json = match (answer.find('{'), answer.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &answer[start..=end],
+        _ => {
+            return Err(format!(
+                "The judge didn't answer with JSON: {}",
+                answer.trim()
+            ))
+        }
+    };
+    let verdict: Value =
+        serde_json::from_str(json).map_err(|err| format!("Invalid judge answer: {}", err))?;
+
+    let score = match verdict.get("score") {
+        Some(Value::Number(number)) => number.as_f64(),
+        Some(Value::String(text)) => text.trim().parse().ok(),
+        _ => None,
+    }
+    .ok_or_else(|| format!("The judge answer has no numeric score: {}", json))?;
+    if score < judge.min_score || score > judge.max_score {
+        return Err(format!(
+            "The judge score {} is outside the scale {} to {}",
+            score, judge.min_score, judge.max_score
+        ));
+    }
+
+    let rationale = verdict
+        .get("rationale")
+        .and_then(|r| r.as_str())
+        .unwrap_or_default()
+        .trim();
+
+    Ok(ScoreResult {
+        name: JUDGE_SOURCE.to_string(),
+        score: Some((score - judge.min_score) / (judge.max_score - judge.min_score)),
+        passed: None,
+        detail: format!("{}/{}: {}", score, judge.max_score, rationale),
+    })
+}
+
+/// Asks the judge to grade an inference and stores its score, replacing the
+/// previous one. `prompt` is the prompt as sent to the model.
+pub async fn judge_inference(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    judge: &ExperimentJudge,
+    inference_id: &str,
+    params: &TParamIteration,
+    prompt: &str,
+    response: &str,
+) -> Result<ScoreResult, Error> {
+    let (_, expected) = row_assertions(params.variables.as_ref())?;
+    let text = judge_prompt(judge, prompt, response, expected.as_deref());
+
+    // Greedy decoding, so judging the same response twice gives the same score
+    let req = GenerationRequest::new(judge.model.clone(), text)
+        .system(JUDGE_SYSTEM_PROMPT)
+        .format(FormatType::Json)
+        .options(ModelOptions::default().temperature(0.0).seed(0));
+
+    let result = match generate(config, req).await {
+        Ok(answer) => parse_verdict(judge, &answer.response),
+        Err(err_msg) => Err(format!("Judge request failed: {}", err_msg)),
+    }
+    .unwrap_or_else(|detail| ScoreResult {
+        name: JUDGE_SOURCE.to_string(),
+        score: None,
+        passed: None,
+        detail,
+    });
+
+    save_scores(
+        pool,
+        &judge.experiment_uuid,
+        inference_id,
+        JUDGE_SOURCE,
+        std::slice::from_ref(&result),
+    )
+    .await?;
+    Ok(result)
+}
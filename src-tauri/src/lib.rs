@@ -15,7 +15,10 @@ The Error enum, therefore, has to implement a variant for "OllamaError"
 */
 use chrono::Utc;
 use ollama_rs::error::OllamaError;
+use ollama_rs::generation::completion::request::GenerationRequest;
 use ollama_rs::generation::completion::GenerationResponse;
+use ollama_rs::models::ModelOptions;
+use ollama_rs::Ollama;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use serde_json::Value;
@@ -25,10 +28,35 @@ use sqlx::Sqlite;
 use std::collections::HashMap;
 use thiserror::Error;
 use url::{ParseError, Url};
+use uuid::Uuid;
 
 use eff_wordlist::short::random_word;
+use sqlx::migrate::MigrateError;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool};
 use sqlx::Error as SqlxError;
-use tokio::time::{sleep, Duration};
+use std::path::Path;
+use tokio::time::{self, sleep, Duration};
+
+pub mod bundle;
+pub mod chart;
+pub mod dataset;
+pub mod diff;
+pub mod diversity;
+pub mod evaluator;
+pub mod export;
+pub mod hooks;
+pub mod judge;
+pub mod migrate;
+pub mod ranking;
+pub mod reasoning;
+pub mod regression;
+pub mod report;
+pub mod reproducibility;
+pub mod runner;
+pub mod scoring;
+pub mod spec;
+pub mod summary;
+pub mod template;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TParamIteration {
@@ -46,6 +74,17 @@ pub struct TParamIteration {
     pub mirostat_tau: f32,
     pub mirostat_eta: f32,
     pub seed: i32,
+    // Think mode of reasoning models; None leaves it to the model
+    #[serde(default)]
+    pub think: Option<bool>,
+    // Set when the grid is expanded over the rows of a dataset
+    #[serde(default)]
+    pub dataset_row: Option<usize>,
+    #[serde(default)]
+    pub variables: Option<serde_json::Map<String, Value>>,
+    // The library prompt the prompt was taken from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_uuid: Option<String>,
 }
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
@@ -95,6 +134,48 @@ impl Experiment {
     }
 }
 
+/// The `contents` of an experiment, as written by `log_experiment`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExperimentLog {
+    pub experiment_uuid: String,
+    pub datetime: String,
+    pub log_version: String,
+    pub config: Value,
+    pub inferences: Vec<LoggedInference>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LoggedInference {
+    #[serde(default)]
+    pub inference_id: String,
+    pub parameters: TParamIteration,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rendered_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_version: Option<PromptVersionRef>,
+    // Exactly one of result/error is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<GenerationResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ExperimentLog {
+    pub fn parse(contents: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(contents)?)
+    }
+}
+
+/// The model response returned to the frontend, with the id it was logged under
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InferenceResponse {
+    pub inference_id: String,
+    // Estimated, see `reasoning::token_split`
+    pub reasoning_tokens: Option<u64>,
+    #[serde(flatten)]
+    pub result: GenerationResponse,
+}
+
 // Use thiserror::Error to implement serializable errors
 // that are returned by commands
 #[derive(Debug, Error)]
@@ -109,10 +190,28 @@ pub enum Error {
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
 
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    SerdeYaml(#[from] serde_yaml::Error),
+
+    #[error(transparent)]
+    TomlDe(#[from] toml::de::Error),
+
+    #[error(transparent)]
+    TomlSer(#[from] toml::ser::Error),
+
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+
     // New variant for database errors
     #[error(transparent)]
     Database(#[from] SqlxError),
 
+    #[error(transparent)]
+    Migrate(#[from] MigrateError),
+
     // New variant for string-related errors
     #[error("String error: {0}")]
     StringError(String), // Include a String to represent the error message
@@ -128,6 +227,20 @@ impl serde::Serialize for Error {
     }
 }
 
+/// Opens (creating it if missing) the SQLite database at `db_path` and runs
+/// any pending migrations. Used by the app and by the command-line runner.
+pub async fn open_database(db_path: &Path) -> Result<Pool<Sqlite>, Error> {
+    let connection_options = SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(true)
+        .journal_mode(SqliteJournalMode::Wal);
+    let pool = SqlitePool::connect_with(connection_options).await?;
+
+    // SQLx tracks which migrations have been run
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
 pub fn create_experiment_name() -> String {
     // uses eff_short_wordlist to create a name
     // composed of three random words for an experiment
@@ -165,26 +278,252 @@ pub fn split_host_port(url: &str) -> Result<(String, u16), ParseError> {
     ))
 }
 
+/// Builds the request of an inference: the default options of `config`, the
+/// parameters of the iteration and `prompt`, the prompt as sent to the model.
+pub fn generation_request(
+    config: &IDefaultConfigs,
+    params: &TParamIteration,
+    prompt: &str,
+) -> GenerationRequest<'static> {
+    // Build generation options object
+    // First the ones that are default values set in "settings"
+    let mut options_builder = ModelOptions::default();
+
+    for &option_name in &[
+        "num_ctx",
+        "num_gqa",
+        "num_gpu",
+        "num_thread",
+        "stop",
+        "num_predict",
+    ] {
+        if let Some(value) = config.default_options.get(option_name) {
+            match option_name {
+                "num_ctx" => {
+                    let parsed_value = value
+                        .to_string()
+                        .parse::<u64>()
+                        .expect("Failed to parse num_ctx as u64");
+                    options_builder = options_builder.num_ctx(parsed_value);
+                }
+                "num_gqa" => {
+                    let parsed_value = value
+                        .to_string()
+                        .parse::<u32>()
+                        .expect("Failed to parse num_gqa as u32");
+                    options_builder = options_builder.num_gqa(parsed_value);
+                }
+                "num_gpu" => {
+                    let parsed_value = value
+                        .to_string()
+                        .parse::<u32>()
+                        .expect("Failed to parse num_gpu as u32");
+                    options_builder = options_builder.num_gpu(parsed_value);
+                }
+                "num_thread" => {
+                    let parsed_value = value
+                        .to_string()
+                        .parse::<u32>()
+                        .expect("Failed to parse num_thread as u32");
+                    options_builder = options_builder.num_thread(parsed_value);
+                }
+                // Commented since a different seed is used at each generation (for the same model/params)
+                // "seed" => {
+                //     let parsed_value = value
+                //         .to_string()
+                //         .parse::<i32>()
+                //         .expect("Failed to parse seed as i32");
+                //     options_builder = options_builder.seed(parsed_value);
+                // }
+                "stop" => {
+                    let parsed_value = vec![value.to_string()];
+                    options_builder = options_builder.stop(parsed_value);
+                }
+
+                "num_predict" => {
+                    let parsed_value = value
+                        .to_string()
+                        .parse::<i32>()
+                        .expect("Failed to parse num_predictnum_predict as i32");
+                    options_builder = options_builder.num_predict(parsed_value);
+                }
+                _ => {
+                    println!("Unknown option: {}", option_name);
+                }
+            }
+        }
+    }
+
+    // Set mandatory options based on user input
+    let options = options_builder
+        .temperature(params.temperature)
+        .repeat_penalty(params.repeat_penalty)
+        .top_k(params.top_k)
+        .top_p(params.top_p)
+        .repeat_last_n(params.repeat_last_n)
+        .tfs_z(params.tfs_z)
+        .mirostat(params.mirostat)
+        .mirostat_tau(params.mirostat_tau)
+        .mirostat_eta(params.mirostat_eta)
+        .seed(params.seed);
+
+    // dbg!(&options);
+
+    let mut req = GenerationRequest::new(params.model.clone(), prompt.to_string())
+        .options(options)
+        .system(params.system_prompt.clone());
+    if let Some(think) = params.think {
+        req = req.think(think);
+    }
+
+    req
+}
+
+/// Sends a generation request to the server of `config`, giving up after its
+/// `request_timeout`. Reasoning is moved out of the response, see
+/// `reasoning`. Errors are the messages logged with failed inferences.
+pub async fn generate(
+    config: &IDefaultConfigs,
+    req: GenerationRequest<'_>,
+) -> Result<GenerationResponse, String> {
+    let (host_url, port) = split_host_port(&config.server_url).map_err(|err| err.to_string())?;
+    let ollama = Ollama::new(host_url, port);
+
+    let timeout = Duration::from_secs(config.request_timeout);
+    match time::timeout(timeout, ollama.generate(req)).await {
+        Ok(result) => {
+            let mut response = result.map_err(|err| err.to_string())?;
+            reasoning::split_reasoning(&mut response);
+            Ok(response)
+        }
+        Err(_) => {
+            let err_msg = format!("Request timed out after {} seconds", timeout.as_secs());
+            println!("{}", err_msg);
+            Err(err_msg)
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct PromptVersionRef {
+    pub prompt_uuid: String,
+    pub version: i64,
+}
+
+/// Finds the latest version of a library prompt with exactly this text.
+/// A prompt edited after it was taken from the library matches none.
+pub async fn find_prompt_version(
+    pool: &Pool<Sqlite>,
+    prompt_uuid: &str,
+    prompt: &str,
+) -> Result<Option<PromptVersionRef>, Error> {
+    let stmt = r#"
+        SELECT prompt_uuid, version
+        FROM prompt_versions
+        WHERE prompt_uuid = $1 AND prompt = $2
+        ORDER BY version DESC
+        LIMIT 1
+    "#;
+
+    Ok(sqlx::query_as::<_, PromptVersionRef>(stmt)
+        .bind(prompt_uuid)
+        .bind(prompt)
+        .fetch_optional(pool)
+        .await?)
+}
+
+/// Builds the log entry for one inference, without its result
+async fn inference_entry(
+    pool: &Pool<Sqlite>,
+    params: &TParamIteration,
+    rendered_prompt: &str,
+) -> Result<Value, Error> {
+    // The id lets ratings and scores refer to a single inference
+    let mut inference = json!({
+        "inference_id": Uuid::new_v4().to_string(),
+        "parameters": params,
+    });
+
+    // Only store the prompt sent to the model when it differs from the template
+    if rendered_prompt != params.prompt {
+        inference["rendered_prompt"] = json!(rendered_prompt);
+    }
+
+    // Link the inference to the exact prompt version it ran, if it came from the library
+    if let Some(prompt_uuid) = &params.prompt_uuid {
+        if let Some(prompt_version) = find_prompt_version(pool, prompt_uuid, &params.prompt).await?
+        {
+            inference["prompt_version"] = json!(prompt_version);
+        }
+    }
+
+    Ok(inference)
+}
+
+/// Returns the full contents of a single experiment.
+pub async fn fetch_experiment(pool: &Pool<Sqlite>, uuid: &str) -> Result<ExperimentFile, Error> {
+    let stmt = r#"
+        SELECT
+            name,
+            created,
+            contents
+        FROM experiments
+        WHERE experiment_uuid = $1
+    "#;
+
+    sqlx::query_as::<_, ExperimentFile>(stmt)
+        .bind(uuid)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::StringError(format!("Experiment {} not found", uuid)))
+}
+
+/// Logs a successful inference and returns its id
 pub async fn log_experiment(
     pool: &Pool<Sqlite>,
     config: &IDefaultConfigs,
     params: &TParamIteration,
+    rendered_prompt: &str,
     res: &GenerationResponse,
+) -> Result<String, Error> {
+    let mut inference = inference_entry(pool, params, rendered_prompt).await?;
+    inference["result"] = json!(res);
+    let inference_id = inference["inference_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+
+    append_inference(pool, config, &params.experiment_uuid, inference).await?;
+    Ok(inference_id)
+}
+
+/// Logs an inference that failed (e.g.: timed out), so failures are kept in the experiment
+pub async fn log_failed_inference(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    params: &TParamIteration,
+    rendered_prompt: &str,
+    error: &str,
 ) -> Result<(), Error> {
-    let experiment_uuid = &params.experiment_uuid;
+    let mut inference = inference_entry(pool, params, rendered_prompt).await?;
+    inference["error"] = json!(error);
 
+    append_inference(pool, config, &params.experiment_uuid, inference).await
+}
+
+async fn append_inference(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    experiment_uuid: &str,
+    inference: Value,
+) -> Result<(), Error> {
     // Create a log data JSON structure
     let log_data = json!({
         "experiment_uuid": experiment_uuid,
         "datetime": Utc::now().to_string(),
-        "log_version": "0.5.0",
+        "log_version": migrate::LOG_VERSION,
         "config": config,
-        "inferences": [
-            {
-                "parameters": params,
-                "result": res
-            }
-        ]
+        "inferences": [inference]
     });
 
     // query the database to see if the experiment already exists
@@ -197,31 +536,26 @@ pub async fn log_experiment(
         .await
         .map_err(Error::Database)?
     {
-        Some(existing_experiment) => {
-            // Update the existing experiment
-            let mut contents: Value = serde_json::from_str(&existing_experiment.contents)
-                .map_err(|e| Error::StringError(e.to_string()))?;
-
-            contents["inferences"].as_array_mut().unwrap().push(json!({
-                "parameters": params,
-                "result": res
-            }));
-
+        Some(_) => {
+            // Append to the existing experiment.
+            // json_insert() runs in a single statement, so concurrent
+            // inferences of the same experiment don't overwrite each other
             let stmt = r#"
                 UPDATE experiments
-                SET contents = $1, created = $2
+                SET contents = json_insert(contents, '$.inferences[#]', json($1)), created = $2
                 WHERE experiment_uuid = $3
             "#;
 
             sqlx::query(stmt)
-                .bind(serde_json::to_string(&contents)?.to_string())
+                .bind(serde_json::to_string(&log_data["inferences"][0])?)
                 .bind(Utc::now().to_string())
                 .bind(experiment_uuid)
                 .execute(pool)
                 .await?;
         }
         None => {
-            // Create a new experiment record
+            // Create a new experiment record. A concurrent inference may
+            // create it first, in which case this one is appended instead
             let experiment = Experiment::new(
                 create_experiment_name(),
                 serde_json::to_string(&log_data)?.to_string(),
@@ -244,6 +578,13 @@ pub async fn log_experiment(
                     $4,
                     $5
                 )
+                ON CONFLICT (experiment_uuid) DO UPDATE SET
+                    contents = json_insert(
+                        contents,
+                        '$.inferences[#]',
+                        json(json_extract(excluded.contents, '$.inferences[0]'))
+                    ),
+                    created = excluded.created
             "#;
             sqlx::query(stmt)
                 .bind(experiment.name)
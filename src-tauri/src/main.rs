@@ -48,13 +48,80 @@ fn main() {
     app.invoke_handler(tauri::generate_handler![
         commands::get_models,
         commands::get_inference,
-        commands::get_experiments,
+        commands::list_experiments,
+        commands::get_experiment,
+        commands::rename_experiment,
+        commands::set_experiment_favorite,
+        commands::set_experiment_description,
+        commands::set_experiment_tags,
+        commands::get_experiment_tags,
+        commands::experiment_summary,
+        commands::experiment_diversity,
+        commands::export_experiment,
+        commands::import_experiment,
+        commands::generate_report,
+        commands::generate_chart,
+        commands::set_inference_rating,
+        commands::clear_inference_rating,
+        commands::get_inference_ratings,
+        commands::set_prompt_assertions,
+        commands::get_prompt_assertions,
+        commands::score_experiment,
+        commands::get_inference_scores,
+        commands::set_experiment_judge,
+        commands::get_experiment_judge,
+        commands::delete_experiment_judge,
+        commands::judge_experiment,
+        commands::save_evaluator,
+        commands::get_evaluators,
+        commands::delete_evaluator,
+        commands::set_experiment_evaluators,
+        commands::get_experiment_evaluators,
+        commands::evaluate_experiment,
+        commands::save_script,
+        commands::get_scripts,
+        commands::delete_script,
+        commands::set_experiment_scripts,
+        commands::get_experiment_scripts,
+        commands::verify_reproducibility,
+        commands::get_reproducibility_checks,
+        commands::save_golden_suite,
+        commands::get_golden_suites,
+        commands::delete_golden_suite,
+        commands::add_golden_baselines,
+        commands::get_golden_baselines,
+        commands::delete_golden_baseline,
+        commands::run_golden_suite,
+        commands::get_regression_runs,
+        commands::load_experiment_spec,
+        commands::save_experiment_spec,
+        commands::start_comparison_session,
+        commands::next_comparison_pair,
+        commands::record_comparison_vote,
+        commands::get_comparison_sessions,
+        commands::comparison_leaderboard,
         commands::get_ollama_version,
         commands::delete_experiments,
         commands::get_all_prompts,
         commands::create_prompt,
         commands::update_prompt,
         commands::delete_prompt,
+        commands::list_prompt_versions,
+        commands::diff_prompt_versions,
+        commands::restore_prompt_version,
+        commands::search_prompts,
+        commands::set_prompt_tags,
+        commands::get_prompt_tags,
+        commands::get_prompt_folders,
+        commands::export_prompts,
+        commands::import_prompts,
+        commands::get_all_system_prompts,
+        commands::create_system_prompt,
+        commands::update_system_prompt,
+        commands::delete_system_prompt,
+        commands::load_dataset,
+        commands::validate_dataset_prompts,
+        commands::render_prompt,
     ])
     .run(tauri::generate_context!())
     .expect("error while running tauri application");
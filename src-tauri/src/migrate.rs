@@ -0,0 +1,321 @@
+/*
+Experiment log migrations.
+
+Experiments used to be written as JSON files, and their format changed
+over time:
+
+* before 0.4.0: no `log_version`, and no system prompt in the config or
+  the parameters (logs before 0.2.1 also lack tfs_z and the mirostat options).
+* 0.4.x/0.5.x: the response metadata (durations, token counts) may be
+  nested under `result.final_data` (ollama-rs 0.1), and there may be no
+  `inference_id` or `error`.
+* 0.6.0 (current): flat results, plus `inference_id`, failed inferences
+  (`error`), rendered prompts and prompt versions.
+
+The steps applied depend on the log's `log_version`, and fill whatever is
+missing with Ollama's defaults.
+*/
+use std::{fs, path::Path};
+
+use chrono::{DateTime, NaiveDateTime};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::{Error, ExperimentLog};
+
+/// Version written by `log_experiment`
+pub const LOG_VERSION: &str = "0.6.0";
+
+// First version with a system prompt, and with flat results
+const SYSTEM_PROMPT_VERSION: (u64, u64, u64) = (0, 4, 0);
+const FLAT_RESULTS_VERSION: (u64, u64, u64) = (0, 6, 0);
+
+// Ollama's defaults, used for parameters older logs didn't record
+const PARAMETER_DEFAULTS: [(&str, f64); 10] = [
+    ("temperature", 0.8),
+    ("repeat_penalty", 1.1),
+    ("top_k", 40.0),
+    ("top_p", 0.9),
+    ("repeat_last_n", 64.0),
+    ("tfs_z", 1.0),
+    ("mirostat", 0.0),
+    ("mirostat_tau", 5.0),
+    ("mirostat_eta", 0.1),
+    ("seed", 0.0),
+];
+
+/// A migrated log, and the version it was migrated from
+#[derive(Debug)]
+pub struct MigratedLog {
+    pub log: ExperimentLog,
+    pub original_version: String,
+}
+
+/// An experiment read from a file, migrated to the current schema
+#[derive(Debug)]
+pub struct ExperimentImport {
+    /// The stored name, or the file name for downloaded logs
+    pub name: String,
+    pub migrated: MigratedLog,
+}
+
+/// Reads an experiment file: either a log, as downloaded from the history,
+/// or an `ExperimentFile` (`{name, created, contents}`) wrapping one.
+pub fn read_experiment_file(path: &str) -> Result<ExperimentImport, Error> {
+    let data: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+    let (name, log) = match data.get("contents") {
+        Some(Value::String(contents)) => (
+            data.get("name").and_then(Value::as_str).map(String::from),
+            serde_json::from_str(contents)?,
+        ),
+        Some(contents) => (
+            data.get("name").and_then(Value::as_str).map(String::from),
+            contents.clone(),
+        ),
+        None => (None, data),
+    };
+
+    let name = name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| {
+            Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+    Ok(ExperimentImport {
+        name,
+        migrated: migrate_log(log)?,
+    })
+}
+
+/// Unix timestamp of a log's `datetime`, as written by `Utc::now().to_string()`
+/// (e.g.: "2024-04-26 18:01:02.123456 UTC") or in RFC 3339.
+pub fn log_timestamp(datetime: &str) -> Option<i64> {
+    if let Ok(date) = DateTime::parse_from_rfc3339(datetime) {
+        return Some(date.timestamp());
+    }
+    let naive = datetime.trim().trim_end_matches("UTC").trim_end();
+    NaiveDateTime::parse_from_str(naive, "%Y-%m-%d %H:%M:%S%.f")
+        .ok()
+        .map(|date| date.and_utc().timestamp())
+}
+
+/// Migrates an experiment log of any past version to the current schema.
+pub fn migrate_log(mut log: Value) -> Result<MigratedLog, Error> {
+    let Some(root) = log.as_object_mut() else {
+        return Err(Error::StringError(
+            "Not an experiment log: expected a JSON object".to_string(),
+        ));
+    };
+    if !root.get("inferences").is_some_and(Value::is_array) {
+        return Err(Error::StringError(
+            "Not an experiment log: no 'inferences' list".to_string(),
+        ));
+    }
+
+    let original_version = root
+        .get("log_version")
+        .and_then(Value::as_str)
+        .unwrap_or("unversioned")
+        .to_string();
+    let version = match root.get("log_version").and_then(Value::as_str) {
+        Some(version) => parse_version(version)
+            .ok_or_else(|| Error::StringError(format!("Unknown log version '{}'", version)))?,
+        None => (0, 0, 0),
+    };
+    if version > parse_version(LOG_VERSION).unwrap() {
+        return Err(Error::StringError(format!(
+            "The log version {} is newer than this app's ({})",
+            original_version, LOG_VERSION
+        )));
+    }
+
+    let experiment_uuid = match root.get("experiment_uuid").and_then(Value::as_str) {
+        Some(uuid) if !uuid.is_empty() => uuid.to_string(),
+        _ => Uuid::new_v4().to_string(),
+    };
+    root.insert("experiment_uuid".into(), json!(experiment_uuid));
+    root.insert("log_version".into(), json!(LOG_VERSION));
+    if !root.get("datetime").is_some_and(Value::is_string) {
+        root.insert("datetime".into(), json!(""));
+    }
+
+    let config = root
+        .entry("config")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or_else(|| Error::StringError("The log 'config' is not an object".to_string()))?;
+    if version < SYSTEM_PROMPT_VERSION {
+        config.entry("system_prompt").or_insert(json!(""));
+    }
+    config.entry("default_options").or_insert(json!({}));
+    let default_system_prompt = config.get("system_prompt").cloned().unwrap_or(json!(""));
+
+    for inference in root["inferences"].as_array_mut().unwrap() {
+        let Some(inference) = inference.as_object_mut() else {
+            return Err(Error::StringError(
+                "The log has an inference that is not an object".to_string(),
+            ));
+        };
+        migrate_inference(inference, version, &experiment_uuid, &default_system_prompt);
+    }
+
+    Ok(MigratedLog {
+        log: serde_json::from_value(log)?,
+        original_version,
+    })
+}
+
+fn migrate_inference(
+    inference: &mut Map<String, Value>,
+    version: (u64, u64, u64),
+    experiment_uuid: &str,
+    default_system_prompt: &Value,
+) {
+    let params = inference.entry("parameters").or_insert_with(|| json!({}));
+    if let Some(params) = params.as_object_mut() {
+        params.insert("experiment_uuid".into(), json!(experiment_uuid));
+        if version < SYSTEM_PROMPT_VERSION {
+            params.entry("model").or_insert(json!(""));
+            params.entry("prompt").or_insert(json!(""));
+            params
+                .entry("system_prompt")
+                .or_insert(default_system_prompt.clone());
+            for (name, default) in PARAMETER_DEFAULTS {
+                params.entry(name).or_insert(json!(default));
+            }
+        }
+        if version < FLAT_RESULTS_VERSION {
+            // Integer parameters may have been written as floats
+            for name in ["top_k", "repeat_last_n", "mirostat", "seed"] {
+                if let Some(value) = params.get_mut(name) {
+                    if let Some(number) = value.as_f64() {
+                        *value = json!(number as i64);
+                    }
+                }
+            }
+        }
+    }
+    if version >= FLAT_RESULTS_VERSION {
+        return;
+    }
+
+    if !inference.get("inference_id").is_some_and(Value::is_string) {
+        inference.insert("inference_id".into(), json!(Uuid::new_v4().to_string()));
+    }
+
+    // ollama-rs 0.1 nested the response metadata under `final_data`
+    if let Some(result) = inference.get_mut("result").and_then(Value::as_object_mut) {
+        if let Some(Value::Object(final_data)) = result.remove("final_data") {
+            for (key, value) in final_data {
+                result.entry(key).or_insert(value);
+            }
+        }
+        result.entry("model").or_insert(json!(""));
+        result.entry("created_at").or_insert(json!(""));
+        result.entry("response").or_insert(json!(""));
+        result.entry("done").or_insert(json!(true));
+    }
+
+    // Entries with neither a result nor an error are recorded as failed
+    if !inference.contains_key("result") && !inference.contains_key("error") {
+        inference.insert("error".into(), json!("No result in the imported log"));
+    }
+}
+
+// "0.4.1" -> (0, 4, 1); a missing minor or patch number counts as 0
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version.trim().trim_start_matches('v').split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+    let patch = parts.next().map_or(Some(0), |part| part.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(parse_version("0.4.1"), Some((0, 4, 1)));
+        assert_eq!(parse_version(" v0.5 "), Some((0, 5, 0)));
+        assert_eq!(parse_version("1"), Some((1, 0, 0)));
+        assert_eq!(parse_version("0.4.1.2"), None);
+        assert_eq!(parse_version("0.x"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn migrates_unversioned_logs() {
+        let log = json!({
+            "config": {},
+            "inferences": [
+                {
+                    "parameters": { "model": "m", "prompt": "p", "top_k": 40.0 },
+                    "result": { "response": "r" }
+                },
+                { "parameters": { "model": "m", "prompt": "p" } }
+            ]
+        });
+        let migrated = migrate_log(log).unwrap();
+        assert_eq!(migrated.original_version, "unversioned");
+
+        let log = migrated.log;
+        assert_eq!(log.log_version, LOG_VERSION);
+        assert!(!log.experiment_uuid.is_empty());
+        assert_eq!(log.config["system_prompt"], "");
+        let first = &log.inferences[0];
+        assert!(!first.inference_id.is_empty());
+        assert_eq!(first.parameters.experiment_uuid, log.experiment_uuid);
+        assert_eq!(first.parameters.top_k, 40);
+        assert_eq!(first.parameters.temperature, 0.8);
+        assert_eq!(first.result.as_ref().unwrap().response, "r");
+        assert_eq!(
+            log.inferences[1].error.as_deref(),
+            Some("No result in the imported log")
+        );
+    }
+
+    #[test]
+    fn flattens_nested_results() {
+        let log = json!({
+            "experiment_uuid": "e",
+            "datetime": "2024-11-01 10:00:00 UTC",
+            "log_version": "0.5.0",
+            "config": { "system_prompt": "s" },
+            "inferences": [{
+                "parameters": {
+                    "model": "m", "prompt": "p", "system_prompt": "s",
+                    "temperature": 0.5, "repeat_penalty": 1.1, "top_k": 40,
+                    "top_p": 0.9, "repeat_last_n": 64, "tfs_z": 1.0,
+                    "mirostat": 0, "mirostat_tau": 5.0, "mirostat_eta": 0.1,
+                    "seed": 1.0
+                },
+                "result": {
+                    "model": "m", "created_at": "", "response": "r", "done": true,
+                    "final_data": { "eval_count": 12 }
+                }
+            }]
+        });
+        let migrated = migrate_log(log).unwrap();
+        assert_eq!(migrated.original_version, "0.5.0");
+        let inference = &migrated.log.inferences[0];
+        assert_eq!(inference.parameters.seed, 1);
+        assert_eq!(inference.result.as_ref().unwrap().eval_count, Some(12));
+    }
+
+    #[test]
+    fn rejects_unknown_logs() {
+        assert!(migrate_log(json!([])).is_err());
+        assert!(migrate_log(json!({ "config": {} })).is_err());
+        assert!(migrate_log(json!({ "log_version": "9.0.0", "inferences": [] })).is_err());
+        assert!(migrate_log(json!({ "log_version": "new", "inferences": [] })).is_err());
+    }
+}
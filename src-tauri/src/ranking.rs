@@ -0,0 +1,182 @@
+/*
+Rankings from pairwise preferences.
+
+Blind A/B votes compare two responses to the same prompt. Each response
+belongs to a model/parameter configuration, and the votes are turned into
+a ranking of configurations with two methods:
+
+* Elo: votes are replayed in the order they were cast.
+* Bradley-Terry: order-independent maximum likelihood strengths, fitted
+  with the MM algorithm and reported on the Elo scale.
+
+Ties count as half a win for each side.
+*/
+use serde::{Deserialize, Serialize};
+
+pub const INITIAL_ELO: f64 = 1500.0;
+const ELO_K: f64 = 32.0;
+const BT_ITERATIONS: usize = 200;
+const BT_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum VoteWinner {
+    Left,
+    Right,
+    Tie,
+}
+
+impl VoteWinner {
+    /// The score of the left side: 1 for a win, 0.5 for a tie, 0 for a loss
+    pub fn left_score(self) -> f64 {
+        match self {
+            VoteWinner::Left => 1.0,
+            VoteWinner::Right => 0.0,
+            VoteWinner::Tie => 0.5,
+        }
+    }
+}
+
+/// A vote between two configurations, by their index
+#[derive(Debug, Clone, Copy)]
+pub struct Preference {
+    pub left: usize,
+    pub right: usize,
+    pub winner: VoteWinner,
+}
+
+/// Elo ratings of `n` configurations, replaying the votes in order.
+pub fn elo_ratings(n: usize, preferences: &[Preference]) -> Vec<f64> {
+    let mut ratings = vec![INITIAL_ELO; n];
+
+    for pref in preferences {
+        let (l, r) = (ratings[pref.left], ratings[pref.right]);
+        let expected_left = 1.0 / (1.0 + 10f64.powf((r - l) / 400.0));
+        let delta = ELO_K * (pref.winner.left_score() - expected_left);
+        ratings[pref.left] += delta;
+        ratings[pref.right] -= delta;
+    }
+
+    ratings
+}
+
+/// Bradley-Terry strengths of `n` configurations, on the Elo scale
+/// (an average configuration scores 1500).
+///
+/// Every compared pair gets one virtual tie, so configurations that
+/// never won (or never lost) still get a finite score.
+pub fn bradley_terry(n: usize, preferences: &[Preference]) -> Vec<f64> {
+    // wins[i][j]: times i beat j; games[i][j]: times i and j were compared
+    let mut wins = vec![vec![0.0; n]; n];
+    let mut games = vec![vec![0.0; n]; n];
+
+    for pref in preferences {
+        let (l, r) = (pref.left, pref.right);
+        if l == r {
+            continue;
+        }
+        let score = pref.winner.left_score();
+        if games[l][r] == 0.0 {
+            // virtual tie
+            wins[l][r] += 0.5;
+            wins[r][l] += 0.5;
+            games[l][r] += 1.0;
+            games[r][l] += 1.0;
+        }
+        wins[l][r] += score;
+        wins[r][l] += 1.0 - score;
+        games[l][r] += 1.0;
+        games[r][l] += 1.0;
+    }
+
+    let mut strengths = vec![1.0; n];
+    for _ in 0..BT_ITERATIONS {
+        let mut next = strengths.clone();
+        for i in 0..n {
+            let total_wins: f64 = wins[i].iter().sum();
+            let denominator: f64 = (0..n)
+                .filter(|&j| j != i && games[i][j] > 0.0)
+                .map(|j| games[i][j] / (strengths[i] + strengths[j]))
+                .sum();
+            if denominator > 0.0 {
+                next[i] = total_wins / denominator;
+            }
+        }
+
+        // Normalize so the geometric mean is 1
+        let log_mean = next.iter().map(|s| s.ln()).sum::<f64>() / n.max(1) as f64;
+        for s in next.iter_mut() {
+            *s /= log_mean.exp();
+        }
+
+        let change = next
+            .iter()
+            .zip(&strengths)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        strengths = next;
+        if change < BT_TOLERANCE {
+            break;
+        }
+    }
+
+    strengths
+        .iter()
+        .map(|s| INITIAL_ELO + 400.0 * s.log10())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(left: usize, right: usize, winner: VoteWinner) -> Preference {
+        Preference {
+            left,
+            right,
+            winner,
+        }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn elo_replays_votes() {
+        assert_eq!(elo_ratings(2, &[]), vec![INITIAL_ELO; 2]);
+
+        // Even ratings expect half a win, so a win moves K / 2
+        let ratings = elo_ratings(2, &[vote(0, 1, VoteWinner::Left)]);
+        assert!(close(ratings[0], 1516.0));
+        assert!(close(ratings[1], 1484.0));
+
+        let ratings = elo_ratings(2, &[vote(1, 0, VoteWinner::Tie)]);
+        assert_eq!(ratings, vec![INITIAL_ELO; 2]);
+    }
+
+    #[test]
+    fn bradley_terry_strengths() {
+        assert_eq!(bradley_terry(3, &[]), vec![INITIAL_ELO; 3]);
+
+        // With the virtual tie, 0 won 1.5 of 2 games: strengths 3 to 1
+        let scores = bradley_terry(2, &[vote(0, 1, VoteWinner::Left)]);
+        assert!(close(scores[0], INITIAL_ELO + 200.0 * 3f64.log10()));
+        assert!(close(scores[1], INITIAL_ELO - 200.0 * 3f64.log10()));
+
+        let scores = bradley_terry(2, &[vote(0, 1, VoteWinner::Tie)]);
+        assert!(close(scores[0], INITIAL_ELO) && close(scores[1], INITIAL_ELO));
+
+        // Order doesn't matter, and wins carry over
+        let votes = [vote(1, 2, VoteWinner::Left), vote(0, 1, VoteWinner::Left)];
+        let scores = bradley_terry(3, &votes);
+        assert!(scores[0] > scores[1] && scores[1] > scores[2]);
+        let reversed: Vec<Preference> = votes.iter().rev().copied().collect();
+        let scores_reversed = bradley_terry(3, &reversed);
+        assert!(scores
+            .iter()
+            .zip(&scores_reversed)
+            .all(|(a, b)| close(*a, *b)));
+    }
+}
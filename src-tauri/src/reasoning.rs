@@ -0,0 +1,66 @@
+/*
+Reasoning ("thinking") content.
+
+Reasoning models (e.g.: deepseek-r1, qwen3) think before they answer. With
+think mode set on the request, Ollama returns the reasoning in the
+`thinking` field of the response; otherwise, or with older servers, some
+models write it in the response itself, between <think> and </think>.
+
+Either way, the reasoning is kept in `thinking` and the response holds only
+the final answer, so response lengths, scores and comparisons aren't
+polluted by it.
+
+Ollama counts reasoning and answer tokens together in `eval_count`. The
+reasoning tokens are estimated by splitting that count in proportion to the
+length of the reasoning and of the answer.
+*/
+use ollama_rs::generation::completion::GenerationResponse;
+
+const OPEN_TAG: &str = "<think>";
+const CLOSE_TAG: &str = "</think>";
+
+/// Moves reasoning written in the response between <think> tags to
+/// `thinking`, leaving only the final answer in the response.
+pub fn split_reasoning(response: &mut GenerationResponse) {
+    let text = response.response.trim_start();
+    let Some(after_open) = text.strip_prefix(OPEN_TAG) else {
+        return;
+    };
+
+    // A response cut short (e.g.: by num_predict) may have no closing tag;
+    // then it's all reasoning
+    let (reasoning, answer) = match after_open.find(CLOSE_TAG) {
+        Some(end) => (&after_open[..end], &after_open[end + CLOSE_TAG.len()..]),
+        None => (after_open, ""),
+    };
+    let reasoning = reasoning.trim();
+    let answer = answer.trim_start().to_string();
+
+    if !reasoning.is_empty() {
+        response.thinking = Some(match response.thinking.take() {
+            Some(thinking) if !thinking.trim().is_empty() => {
+                format!("{}\n\n{}", thinking.trim_end(), reasoning)
+            }
+            _ => reasoning.to_string(),
+        });
+    }
+    response.response = answer;
+}
+
+/// Estimated number of reasoning and answer tokens of a response, in that
+/// order. None when Ollama didn't report a token count.
+pub fn token_split(response: &GenerationResponse) -> Option<(u64, u64)> {
+    let total = response.eval_count?;
+    let reasoning_chars = response
+        .thinking
+        .as_deref()
+        .map_or(0, |t| t.chars().count());
+    let answer_chars = response.response.chars().count();
+    if reasoning_chars == 0 {
+        return Some((0, total));
+    }
+
+    let share = reasoning_chars as f64 / (reasoning_chars + answer_chars) as f64;
+    let reasoning = (total as f64 * share).round() as u64;
+    Some((reasoning, total - reasoning))
+}
@@ -0,0 +1,478 @@
+/*
+Golden-output regression suites.
+
+A suite is a named set of baselines: copies of logged inferences (their
+parameters, config, rendered prompt and response) that are known to be
+good. Running a suite re-runs every baseline with the same prompt and
+parameters, optionally on another model (e.g.: a new tag), and compares each
+new response with its baseline:
+
+* edit: 1 - the normalized Levenshtein distance, see `scoring`.
+* jaccard: the overlap of the sets of words, see `diversity`.
+* embedding: the cosine similarity of the embeddings, when the suite sets an
+  embedding model.
+
+A rerun passes when it meets every threshold the suite sets. Its drift is
+1 - the embedding similarity, or 1 - the mean of the lexical similarities,
+like the diversity of a grid cell.
+
+Baselines keep the names of the scripts their experiment ran, so reruns go
+through the same post-hooks after the experiment is deleted.
+
+The reruns are logged as the inferences of a new experiment with the
+"regression" run type, so they can be browsed, exported and compared like
+any other experiment. The report is stored in `regression_runs`.
+*/
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::prelude::FromRow;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::diff::{diff_lines, DiffLine};
+use crate::diversity::{cosine_similarity, embed, jaccard_similarity};
+use crate::hooks::{experiment_scripts, named_scripts, run_post_hooks, Script};
+use crate::migrate::LOG_VERSION;
+use crate::reproducibility::{logged_run_config, server_version};
+use crate::scoring::{edit_similarity, tokens};
+use crate::{
+    create_experiment_name, generate, generation_request, log_experiment, log_failed_inference,
+    Error, ExperimentLog, IDefaultConfigs, TParamIteration,
+};
+
+pub const REGRESSION_RUN: &str = "regression";
+
+/// Minimum similarities (0 to 1) of a rerun to its baseline; unset ones
+/// aren't checked.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Thresholds {
+    pub min_edit_similarity: Option<f64>,
+    pub min_jaccard_similarity: Option<f64>,
+    pub embedding_model: Option<String>,
+    pub min_embedding_similarity: Option<f64>,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            min_edit_similarity: Some(0.8),
+            min_jaccard_similarity: None,
+            embedding_model: None,
+            min_embedding_similarity: None,
+        }
+    }
+}
+
+impl Thresholds {
+    pub fn validate(&self) -> Result<(), Error> {
+        let thresholds = [
+            ("edit", self.min_edit_similarity),
+            ("jaccard", self.min_jaccard_similarity),
+            ("embedding", self.min_embedding_similarity),
+        ];
+        for (name, threshold) in thresholds {
+            if let Some(value) = threshold.filter(|v| !(0.0..=1.0).contains(v)) {
+                return Err(Error::StringError(format!(
+                    "Invalid {} similarity threshold {}, use 0 to 1",
+                    name, value
+                )));
+            }
+        }
+        if self.min_embedding_similarity.is_some() && self.embedding_model().is_none() {
+            return Err(Error::StringError(
+                "Select an embedding model for the embedding similarity threshold".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn embedding_model(&self) -> Option<&str> {
+        self.embedding_model
+            .as_deref()
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GoldenSuiteInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub thresholds: Thresholds,
+}
+
+impl GoldenSuiteInput {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::StringError(
+                "The suite name can't be empty".to_string(),
+            ));
+        }
+        self.thresholds.validate()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct GoldenSuite {
+    pub name: String,
+    pub description: String,
+    #[sqlx(json)]
+    pub thresholds: Thresholds,
+    pub baseline_count: i64,
+    pub date_created: i64,  // Unix timestamp
+    pub last_modified: i64, // Unix timestamp
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct GoldenBaseline {
+    pub id: i64,
+    pub suite_name: String,
+    pub source_experiment_uuid: String,
+    pub source_inference_id: String,
+    #[sqlx(json)]
+    pub parameters: TParamIteration,
+    #[sqlx(json)]
+    pub config: Value,
+    pub rendered_prompt: String,
+    pub response: String,
+    // Names of the scripts whose post-hooks produced the response
+    #[sqlx(json)]
+    pub scripts: Vec<String>,
+    pub date_created: i64, // Unix timestamp
+}
+
+/// The comparison of a rerun with its baseline
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BaselineResult {
+    pub baseline_id: i64,
+    pub model: String,
+    pub prompt: String,
+    pub baseline_response: String,
+    // The inference of the regression run; None when it failed
+    pub inference_id: Option<String>,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub edit_similarity: Option<f64>,
+    pub jaccard_similarity: Option<f64>,
+    pub embedding_similarity: Option<f64>,
+    // 0 when the responses are the same, up to 1
+    pub drift: Option<f64>,
+    pub passed: bool,
+    // Line diff from the baseline response; empty when the responses are equal
+    pub diff: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct RegressionRun {
+    pub experiment_uuid: String,
+    pub suite_name: String,
+    pub model: Option<String>,
+    pub ollama_version: Option<String>,
+    #[sqlx(json)]
+    pub thresholds: Thresholds,
+    pub baseline_count: i64,
+    pub passed_count: i64,
+    // Failed and most drifted first
+    #[sqlx(json)]
+    pub results: Vec<BaselineResult>,
+    pub date_created: i64, // Unix timestamp
+}
+
+pub async fn get_suite(pool: &Pool<Sqlite>, name: &str) -> Result<GoldenSuite, Error> {
+    let stmt = r#"
+        SELECT
+            s.*,
+            (SELECT COUNT(*) FROM golden_baselines b WHERE b.suite_name = s.name) AS baseline_count
+        FROM golden_suites s
+        WHERE s.name = $1
+    "#;
+    sqlx::query_as::<_, GoldenSuite>(stmt)
+        .bind(name)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| Error::StringError(format!("Golden suite '{}' not found", name)))
+}
+
+/// The baselines of a suite, in the order they were added.
+pub async fn get_baselines(
+    pool: &Pool<Sqlite>,
+    suite_name: &str,
+) -> Result<Vec<GoldenBaseline>, Error> {
+    Ok(sqlx::query_as::<_, GoldenBaseline>(
+        "SELECT * FROM golden_baselines WHERE suite_name = $1 ORDER BY id",
+    )
+    .bind(suite_name)
+    .fetch_all(pool)
+    .await?)
+}
+
+/// Copies successful inferences of an experiment into a suite, skipping the
+/// ones already in it. Returns the number of baselines added.
+pub async fn add_baselines(
+    pool: &Pool<Sqlite>,
+    suite_name: &str,
+    log: &ExperimentLog,
+    inference_ids: &[String],
+) -> Result<usize, Error> {
+    get_suite(pool, suite_name).await?;
+    let scripts: Vec<String> = experiment_scripts(pool, &log.experiment_uuid)
+        .await?
+        .into_iter()
+        .map(|script| script.name)
+        .collect();
+
+    let mut added = 0;
+    for inference_id in inference_ids {
+        let inference = log
+            .inferences
+            .iter()
+            .find(|i| &i.inference_id == inference_id)
+            .ok_or_else(|| {
+                Error::StringError(format!(
+                    "Inference {} not found in experiment {}",
+                    inference_id, log.experiment_uuid
+                ))
+            })?;
+        let Some(result) = &inference.result else {
+            return Err(Error::StringError(format!(
+                "Inference {} failed, it can't be a baseline",
+                inference_id
+            )));
+        };
+        let params = &inference.parameters;
+
+        let stmt = r#"
+            INSERT INTO golden_baselines (
+                suite_name, source_experiment_uuid, source_inference_id,
+                parameters, config, rendered_prompt, response, scripts
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (suite_name, source_inference_id) DO NOTHING
+        "#;
+        let inserted = sqlx::query(stmt)
+            .bind(suite_name)
+            .bind(&log.experiment_uuid)
+            .bind(inference_id)
+            .bind(serde_json::to_string(params)?)
+            .bind(serde_json::to_string(&log.config)?)
+            .bind(
+                inference
+                    .rendered_prompt
+                    .as_deref()
+                    .unwrap_or(&params.prompt),
+            )
+            .bind(&result.response)
+            .bind(serde_json::to_string(&scripts)?)
+            .execute(pool)
+            .await?;
+        added += inserted.rows_affected() as usize;
+    }
+
+    Ok(added)
+}
+
+/// Compares a rerun with its baseline, embedding both responses when the
+/// thresholds set an embedding model.
+async fn compare(
+    config: &IDefaultConfigs,
+    thresholds: &Thresholds,
+    baseline: &str,
+    response: &str,
+) -> Result<(f64, f64, Option<f64>), String> {
+    let edit = edit_similarity(baseline, response);
+    let words = |text: &str| tokens(text).into_iter().collect::<HashSet<String>>();
+    let jaccard = jaccard_similarity(&words(baseline), &words(response));
+
+    let embedding = match thresholds.embedding_model() {
+        Some(model) => {
+            let embeddings = embed(config, model, &[baseline, response])
+                .await
+                .map_err(|err| format!("Could not embed responses: {}", err))?;
+            Some(cosine_similarity(&embeddings[0], &embeddings[1]))
+        }
+        None => None,
+    };
+    Ok((edit, jaccard, embedding))
+}
+
+fn passes(thresholds: &Thresholds, edit: f64, jaccard: f64, embedding: Option<f64>) -> bool {
+    let meets = |value: Option<f64>, threshold: Option<f64>| match (value, threshold) {
+        (_, None) => true,
+        (Some(value), Some(threshold)) => value >= threshold,
+        (None, Some(_)) => false,
+    };
+    meets(Some(edit), thresholds.min_edit_similarity)
+        && meets(Some(jaccard), thresholds.min_jaccard_similarity)
+        && meets(embedding, thresholds.min_embedding_similarity)
+}
+
+/// Creates the experiment the reruns are logged to. It's a regression run
+/// from the start, so a run that fails part-way isn't listed as a grid search.
+async fn create_regression_experiment(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    experiment_uuid: &str,
+    suite_name: &str,
+) -> Result<(), Error> {
+    let contents = json!({
+        "experiment_uuid": experiment_uuid,
+        "datetime": Utc::now().to_string(),
+        "log_version": LOG_VERSION,
+        "config": config,
+        "inferences": []
+    });
+
+    let stmt = r#"
+        INSERT INTO experiments (name, contents, experiment_uuid, created, run_type, description)
+        VALUES ($1, $2, $3, $4, $5, $6)
+    "#;
+    sqlx::query(stmt)
+        .bind(create_experiment_name())
+        .bind(serde_json::to_string(&contents)?)
+        .bind(experiment_uuid)
+        .bind(Utc::now().to_string())
+        .bind(REGRESSION_RUN)
+        .bind(format!(
+            "Regression run of the golden suite '{}'",
+            suite_name
+        ))
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Re-runs every baseline of a suite, on `model` if set, logging the reruns
+/// to a new regression experiment. Returns the stored report.
+pub async fn run_suite(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    suite_name: &str,
+    model: Option<&str>,
+) -> Result<RegressionRun, Error> {
+    let suite = get_suite(pool, suite_name).await?;
+    suite.thresholds.validate()?;
+    let baselines = get_baselines(pool, suite_name).await?;
+    if baselines.is_empty() {
+        return Err(Error::StringError(format!(
+            "Golden suite '{}' has no baselines",
+            suite_name
+        )));
+    }
+    let model = model.map(str::trim).filter(|m| !m.is_empty());
+
+    // Baselines are post-hook output, so reruns go through the same hooks
+    let mut baseline_scripts: Vec<Vec<Script>> = Vec::with_capacity(baselines.len());
+    for baseline in &baselines {
+        baseline_scripts.push(named_scripts(pool, &baseline.scripts).await?);
+    }
+
+    let experiment_uuid = Uuid::new_v4().to_string();
+    create_regression_experiment(pool, config, &experiment_uuid, suite_name).await?;
+    let ollama_version = server_version(config).await;
+
+    let mut results = Vec::with_capacity(baselines.len());
+    for (baseline, scripts) in baselines.iter().zip(&baseline_scripts) {
+        let run_config = logged_run_config(config, &baseline.config);
+        let params = TParamIteration {
+            experiment_uuid: experiment_uuid.clone(),
+            model: model.unwrap_or(&baseline.parameters.model).to_string(),
+            ..baseline.parameters.clone()
+        };
+        let prompt = &baseline.rendered_prompt;
+
+        let mut result = BaselineResult {
+            baseline_id: baseline.id,
+            model: params.model.clone(),
+            prompt: prompt.clone(),
+            baseline_response: baseline.response.clone(),
+            inference_id: None,
+            response: None,
+            error: None,
+            edit_similarity: None,
+            jaccard_similarity: None,
+            embedding_similarity: None,
+            drift: None,
+            passed: false,
+            diff: vec![],
+        };
+
+        let req = generation_request(&run_config, &params, prompt);
+        let output = generate(&run_config, req)
+            .await
+            .and_then(|res| run_post_hooks(scripts, &params, res));
+        let res = match output {
+            Ok((res, _)) => res,
+            Err(err_msg) => {
+                log_failed_inference(pool, &run_config, &params, prompt, &err_msg).await?;
+                result.error = Some(err_msg);
+                results.push(result);
+                continue;
+            }
+        };
+        result.inference_id = Some(log_experiment(pool, &run_config, &params, prompt, &res).await?);
+
+        match compare(config, &suite.thresholds, &baseline.response, &res.response).await {
+            Ok((edit, jaccard, embedding)) => {
+                result.edit_similarity = Some(edit);
+                result.jaccard_similarity = Some(jaccard);
+                result.embedding_similarity = embedding;
+                result.drift =
+                    Some((1.0 - embedding.unwrap_or((edit + jaccard) / 2.0)).clamp(0.0, 1.0));
+                result.passed = passes(&suite.thresholds, edit, jaccard, embedding);
+            }
+            Err(err_msg) => result.error = Some(err_msg),
+        }
+        if res.response != baseline.response {
+            result.diff = diff_lines(&baseline.response, &res.response);
+        }
+        result.response = Some(res.response);
+        results.push(result);
+    }
+
+    // Failed reruns first, then the most drifted
+    results.sort_by(|a, b| {
+        a.passed
+            .cmp(&b.passed)
+            .then(b.drift.unwrap_or(1.0).total_cmp(&a.drift.unwrap_or(1.0)))
+    });
+    let passed_count = results.iter().filter(|r| r.passed).count();
+
+    let stmt = r#"
+        INSERT INTO regression_runs (
+            experiment_uuid, suite_name, model, ollama_version, thresholds,
+            baseline_count, passed_count, results
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+    "#;
+    Ok(sqlx::query_as::<_, RegressionRun>(stmt)
+        .bind(&experiment_uuid)
+        .bind(suite_name)
+        .bind(model)
+        .bind(ollama_version)
+        .bind(serde_json::to_string(&suite.thresholds)?)
+        .bind(results.len() as i64)
+        .bind(passed_count as i64)
+        .bind(serde_json::to_string(&results)?)
+        .fetch_one(pool)
+        .await?)
+}
+
+/// The runs of a suite, or of every suite, latest first.
+pub async fn get_runs(
+    pool: &Pool<Sqlite>,
+    suite_name: Option<&str>,
+) -> Result<Vec<RegressionRun>, Error> {
+    let stmt = r#"
+        SELECT * FROM regression_runs
+        WHERE $1 IS NULL OR suite_name = $1
+        ORDER BY date_created DESC, rowid DESC
+    "#;
+    Ok(sqlx::query_as::<_, RegressionRun>(stmt)
+        .bind(suite_name)
+        .fetch_all(pool)
+        .await?)
+}
@@ -0,0 +1,611 @@
+/*
+Experiment reports.
+
+An experiment is rendered as a single self-contained document, ready to be
+pasted into a design doc: a static HTML file (inline CSS, SVG charts from
+`chart`) or Markdown. Reports contain the config, the parameter grid,
+per-model metrics, failures, the top-rated outputs and the responses to
+each prompt side by side.
+
+Reports are built from the flattened export rows, so the metrics match
+the CSV/JSONL/Parquet exports.
+*/
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::chart::{chart_svg, ChartKind, ChartSpec};
+use crate::export::{export_rows, ExportRow};
+use crate::scoring::InferenceScore;
+use crate::summary::InferenceRating;
+use crate::{Error, ExperimentLog};
+
+const TOP_RATED_COUNT: usize = 5;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Html,
+    Markdown,
+}
+
+impl ReportFormat {
+    /// Picks the format from the file extension
+    pub fn from_path(path: &str) -> Result<Self, Error> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "html" | "htm" => Ok(ReportFormat::Html),
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            _ => Err(Error::StringError(format!(
+                "Unsupported report format: '{}'. Use a .html or .md file.",
+                path
+            ))),
+        }
+    }
+}
+
+/// Metrics of all the inferences of a model
+#[derive(Debug, Default)]
+struct ModelMetrics {
+    model: String,
+    inference_count: usize,
+    error_count: usize,
+    avg_tokens_per_second: Option<f64>,
+    avg_eval_count: Option<f64>,
+    // In seconds
+    avg_time_to_first_token: Option<f64>,
+    avg_total_duration: Option<f64>,
+    avg_rating: Option<f64>,
+    avg_score: Option<f64>,
+    avg_judge_score: Option<f64>,
+    thumbs_up: usize,
+    thumbs_down: usize,
+}
+
+/// The responses to one prompt
+struct PromptResponses<'a> {
+    system_prompt: &'a str,
+    prompt: &'a str,
+    rows: Vec<&'a ExportRow>,
+}
+
+struct Report<'a> {
+    name: &'a str,
+    log: &'a ExperimentLog,
+    rows: &'a [ExportRow],
+    // Parameter -> distinct values, in the order they appear
+    grid: Vec<(&'static str, Vec<String>)>,
+    models: Vec<ModelMetrics>,
+    // Error message -> count, most frequent first
+    failures: Vec<(&'a str, usize)>,
+    top_rated: Vec<&'a ExportRow>,
+    prompts: Vec<PromptResponses<'a>>,
+}
+
+/// Renders an experiment report.
+pub fn render_report(
+    log: &ExperimentLog,
+    experiment_name: &str,
+    ratings: &[InferenceRating],
+    scores: &[InferenceScore],
+    format: ReportFormat,
+) -> String {
+    let rows = export_rows(log, experiment_name, ratings, scores);
+    let report = Report::new(log, experiment_name, &rows);
+
+    match format {
+        ReportFormat::Html => report.html(),
+        ReportFormat::Markdown => report.markdown(),
+    }
+}
+
+impl<'a> Report<'a> {
+    fn new(log: &'a ExperimentLog, name: &'a str, rows: &'a [ExportRow]) -> Self {
+        let grid = vec![
+            ("model", distinct(rows, |r| r.model.clone())),
+            ("temperature", distinct(rows, |r| r.temperature.to_string())),
+            (
+                "repeat_penalty",
+                distinct(rows, |r| r.repeat_penalty.to_string()),
+            ),
+            ("top_k", distinct(rows, |r| r.top_k.to_string())),
+            ("top_p", distinct(rows, |r| r.top_p.to_string())),
+            (
+                "repeat_last_n",
+                distinct(rows, |r| r.repeat_last_n.to_string()),
+            ),
+            ("tfs_z", distinct(rows, |r| r.tfs_z.to_string())),
+            ("mirostat", distinct(rows, |r| r.mirostat.to_string())),
+            (
+                "mirostat_tau",
+                distinct(rows, |r| r.mirostat_tau.to_string()),
+            ),
+            (
+                "mirostat_eta",
+                distinct(rows, |r| r.mirostat_eta.to_string()),
+            ),
+            ("seed", distinct(rows, |r| r.seed.to_string())),
+            ("think", distinct(rows, think_value)),
+        ];
+
+        let models = distinct(rows, |r| r.model.clone())
+            .into_iter()
+            .map(|model| {
+                let model_rows: Vec<&ExportRow> =
+                    rows.iter().filter(|r| r.model == model).collect();
+                let thumbs = |thumb| model_rows.iter().filter(|r| r.thumb == Some(thumb)).count();
+                ModelMetrics {
+                    inference_count: model_rows.len(),
+                    error_count: model_rows.iter().filter(|r| r.error.is_some()).count(),
+                    avg_tokens_per_second: average(model_rows.iter().map(|r| r.tokens_per_second)),
+                    avg_eval_count: average(
+                        model_rows.iter().map(|r| r.eval_count.map(|c| c as f64)),
+                    ),
+                    avg_time_to_first_token: average(
+                        model_rows.iter().map(|r| r.time_to_first_token),
+                    ),
+                    avg_total_duration: average(
+                        model_rows
+                            .iter()
+                            .map(|r| r.total_duration.map(|d| d as f64 / 1e9)),
+                    ),
+                    avg_rating: average(model_rows.iter().map(|r| r.rating.map(|v| v as f64))),
+                    avg_score: average(model_rows.iter().map(|r| r.mean_score)),
+                    avg_judge_score: average(model_rows.iter().map(|r| r.judge_score)),
+                    thumbs_up: thumbs(1),
+                    thumbs_down: thumbs(-1),
+                    model,
+                }
+            })
+            .collect();
+
+        let mut failures: Vec<(&str, usize)> = vec![];
+        for error in rows.iter().filter_map(|r| r.error.as_deref()) {
+            match failures.iter_mut().find(|(message, _)| *message == error) {
+                Some((_, count)) => *count += 1,
+                None => failures.push((error, 1)),
+            }
+        }
+        failures.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        let mut top_rated: Vec<&ExportRow> = rows
+            .iter()
+            .filter(|r| r.response.is_some() && (r.rating.is_some() || r.thumb == Some(1)))
+            .collect();
+        top_rated.sort_by_key(|r| std::cmp::Reverse((r.rating.unwrap_or(0), r.thumb.unwrap_or(0))));
+        top_rated.truncate(TOP_RATED_COUNT);
+
+        let mut prompts: Vec<PromptResponses> = vec![];
+        let mut prompt_index: HashMap<(&str, &str), usize> = HashMap::new();
+        for row in rows {
+            let key = (row.system_prompt.as_str(), row.rendered_prompt.as_str());
+            let idx = *prompt_index.entry(key).or_insert_with(|| {
+                prompts.push(PromptResponses {
+                    system_prompt: key.0,
+                    prompt: key.1,
+                    rows: vec![],
+                });
+                prompts.len() - 1
+            });
+            prompts[idx].rows.push(row);
+        }
+
+        Self {
+            name,
+            log,
+            rows,
+            grid,
+            models,
+            failures,
+            top_rated,
+            prompts,
+        }
+    }
+
+    /// The model and the swept parameters of an inference
+    fn label(&self, row: &ExportRow) -> String {
+        let mut label = row.model.clone();
+        for (param, values) in self.grid.iter().skip(1) {
+            if values.len() > 1 {
+                let _ = write!(label, ", {}={}", param, param_value(row, param));
+            }
+        }
+        label
+    }
+
+    /// Throughput, failures and scores per model, and throughput over each
+    /// swept parameter
+    fn chart_specs(&self) -> Vec<ChartSpec> {
+        let spec = |kind, x: &str, y: &str, series: Option<&str>, title: &str| ChartSpec {
+            kind,
+            x: x.to_string(),
+            y: y.to_string(),
+            series: series.map(String::from),
+            value: None,
+            title: Some(title.to_string()),
+            width: 560,
+            height: 320,
+        };
+
+        let mut specs = vec![
+            spec(
+                ChartKind::Bar,
+                "model",
+                "tokens_per_second",
+                None,
+                "Tokens per second",
+            ),
+            spec(ChartKind::Bar, "model", "failed", None, "Failure rate"),
+        ];
+        if self.rows.iter().any(|r| r.mean_score.is_some()) {
+            specs.push(spec(
+                ChartKind::Bar,
+                "model",
+                "mean_score",
+                None,
+                "Mean score",
+            ));
+        }
+        if self.rows.iter().any(|r| r.reasoning_tokens > Some(0)) {
+            specs.push(spec(
+                ChartKind::Bar,
+                "model",
+                "reasoning_tokens",
+                None,
+                "Reasoning tokens",
+            ));
+        }
+        for (param, values) in self.grid.iter().skip(1) {
+            if values.len() > 1 {
+                specs.push(spec(
+                    ChartKind::Line,
+                    param,
+                    "tokens_per_second",
+                    Some("model"),
+                    &format!("Tokens per second vs {}", param),
+                ));
+            }
+        }
+        specs
+    }
+
+    fn error_count(&self) -> usize {
+        self.rows.iter().filter(|r| r.error.is_some()).count()
+    }
+
+    fn config(&self) -> String {
+        serde_json::to_string_pretty(&self.log.config).unwrap_or_default()
+    }
+
+    fn html(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n",
+            escape_html(self.name),
+            HTML_STYLE
+        );
+
+        let _ = writeln!(out, "<h1>{}</h1>", escape_html(self.name));
+        let _ = writeln!(
+            out,
+            "<p class=\"meta\">{} &middot; {} &middot; {} inferences, {} failed, {} prompts</p>",
+            escape_html(&self.log.experiment_uuid),
+            escape_html(&self.log.datetime),
+            self.rows.len(),
+            self.error_count(),
+            self.prompts.len()
+        );
+
+        out.push_str("<h2>Configuration</h2>\n");
+        let _ = writeln!(out, "<pre>{}</pre>", escape_html(&self.config()));
+
+        out.push_str(
+            "<h2>Parameter grid</h2>\n<table>\n<tr><th>Parameter</th><th>Values</th></tr>\n",
+        );
+        for (param, values) in &self.grid {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                param,
+                escape_html(&values.join(", "))
+            );
+        }
+        out.push_str("</table>\n");
+
+        out.push_str("<h2>Models</h2>\n<table>\n<tr><th>Model</th><th>Inferences</th><th>Failures</th><th>Tokens/s</th><th>Response tokens</th><th>First token (s)</th><th>Duration (s)</th><th>Rating</th><th>Score</th><th>Judge</th><th>&#128077;/&#128078;</th></tr>\n");
+        for m in &self.models {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}/{}</td></tr>",
+                escape_html(&m.model),
+                m.inference_count,
+                m.error_count,
+                number(m.avg_tokens_per_second, 1),
+                number(m.avg_eval_count, 0),
+                number(m.avg_time_to_first_token, 2),
+                number(m.avg_total_duration, 2),
+                number(m.avg_rating, 2),
+                number(m.avg_score, 2),
+                number(m.avg_judge_score, 2),
+                m.thumbs_up,
+                m.thumbs_down
+            );
+        }
+        out.push_str("</table>\n<div class=\"charts\">\n");
+        for spec in self.chart_specs() {
+            // Charts are extras: a chart that can't be drawn is left out
+            if let Ok(svg) = chart_svg(self.rows, &spec) {
+                out.push_str(&svg);
+            }
+        }
+        out.push_str("</div>\n");
+
+        if !self.failures.is_empty() {
+            out.push_str("<h2>Failures</h2>\n<table>\n<tr><th>Error</th><th>Count</th></tr>\n");
+            for (error, count) in &self.failures {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td></tr>",
+                    escape_html(error),
+                    count
+                );
+            }
+            out.push_str("</table>\n");
+        }
+
+        if !self.top_rated.is_empty() {
+            out.push_str("<h2>Top-rated outputs</h2>\n");
+            for row in &self.top_rated {
+                let _ = writeln!(
+                    out,
+                    "<div class=\"card\"><div class=\"label\">{} &middot; {}</div><div class=\"prompt\">{}</div><div class=\"response\">{}</div>{}</div>",
+                    escape_html(&self.label(row)),
+                    rating_text(row),
+                    escape_html(&row.rendered_prompt),
+                    escape_html(row.response.as_deref().unwrap_or_default()),
+                    row.note
+                        .as_deref()
+                        .map(|note| format!("<div class=\"note\">{}</div>", escape_html(note)))
+                        .unwrap_or_default()
+                );
+            }
+        }
+
+        out.push_str("<h2>Responses</h2>\n");
+        for prompt in &self.prompts {
+            out.push_str("<div class=\"prompt-group\">\n");
+            if !prompt.system_prompt.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "<div class=\"system\">{}</div>",
+                    escape_html(prompt.system_prompt)
+                );
+            }
+            let _ = writeln!(
+                out,
+                "<div class=\"prompt\">{}</div>",
+                escape_html(prompt.prompt)
+            );
+            out.push_str("<div class=\"side-by-side\">\n");
+            for row in &prompt.rows {
+                let body = match (&row.response, &row.error) {
+                    (_, Some(error)) => {
+                        format!("<div class=\"error\">{}</div>", escape_html(error))
+                    }
+                    (Some(response), None) => {
+                        format!("<div class=\"response\">{}</div>", escape_html(response))
+                    }
+                    (None, None) => String::new(),
+                };
+                let _ = writeln!(
+                    out,
+                    "<div class=\"card\"><div class=\"label\">{}</div>{}</div>",
+                    escape_html(&self.label(row)),
+                    body
+                );
+            }
+            out.push_str("</div>\n</div>\n");
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.name);
+        let _ = writeln!(
+            out,
+            "`{}` · {} · {} inferences, {} failed, {} prompts\n",
+            self.log.experiment_uuid,
+            self.log.datetime,
+            self.rows.len(),
+            self.error_count(),
+            self.prompts.len()
+        );
+
+        let _ = writeln!(out, "## Configuration\n\n```json\n{}\n```\n", self.config());
+
+        out.push_str("## Parameter grid\n\n| Parameter | Values |\n| --- | --- |\n");
+        for (param, values) in &self.grid {
+            let _ = writeln!(out, "| {} | {} |", param, table_cell(&values.join(", ")));
+        }
+
+        out.push_str("\n## Models\n\n| Model | Inferences | Failures | Tokens/s | Response tokens | First token (s) | Duration (s) | Rating | Score | Judge | 👍/👎 |\n| --- | ---: | ---: | ---: | ---: | ---: | ---: | ---: | ---: | ---: | ---: |\n");
+        for m in &self.models {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {}/{} |",
+                table_cell(&m.model),
+                m.inference_count,
+                m.error_count,
+                number(m.avg_tokens_per_second, 1),
+                number(m.avg_eval_count, 0),
+                number(m.avg_time_to_first_token, 2),
+                number(m.avg_total_duration, 2),
+                number(m.avg_rating, 2),
+                number(m.avg_score, 2),
+                number(m.avg_judge_score, 2),
+                m.thumbs_up,
+                m.thumbs_down
+            );
+        }
+
+        if !self.failures.is_empty() {
+            out.push_str("\n## Failures\n\n| Error | Count |\n| --- | ---: |\n");
+            for (error, count) in &self.failures {
+                let _ = writeln!(out, "| {} | {} |", table_cell(error), count);
+            }
+        }
+
+        if !self.top_rated.is_empty() {
+            out.push_str("\n## Top-rated outputs\n");
+            for row in &self.top_rated {
+                let _ = writeln!(
+                    out,
+                    "\n**{}** · {}\n\n> {}\n\n{}",
+                    self.label(row),
+                    rating_text(row),
+                    row.rendered_prompt.replace('\n', "\n> "),
+                    row.response.as_deref().unwrap_or_default()
+                );
+                if let Some(note) = &row.note {
+                    let _ = writeln!(out, "\n_Note: {}_", note);
+                }
+            }
+        }
+
+        out.push_str("\n## Responses\n");
+        for prompt in &self.prompts {
+            out.push('\n');
+            if !prompt.system_prompt.is_empty() {
+                let _ = writeln!(
+                    out,
+                    "_System:_ {}\n",
+                    prompt.system_prompt.replace('\n', " ")
+                );
+            }
+            let _ = writeln!(out, "> {}\n", prompt.prompt.replace('\n', "\n> "));
+
+            let labels: Vec<String> = prompt
+                .rows
+                .iter()
+                .map(|r| table_cell(&self.label(r)))
+                .collect();
+            let _ = writeln!(out, "| {} |", labels.join(" | "));
+            let _ = writeln!(out, "|{}", " --- |".repeat(labels.len()));
+            let cells: Vec<String> = prompt
+                .rows
+                .iter()
+                .map(|r| match (&r.response, &r.error) {
+                    (_, Some(error)) => format!("**Error:** {}", table_cell(error)),
+                    (Some(response), None) => table_cell(response),
+                    (None, None) => String::new(),
+                })
+                .collect();
+            let _ = writeln!(out, "| {} |", cells.join(" | "));
+        }
+
+        out
+    }
+}
+
+const HTML_STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; margin: 2rem auto; max-width: 1200px; padding: 0 1rem; color: #1f2937; }
+h1 { margin-bottom: 0.25rem; }
+h2 { margin-top: 2rem; border-bottom: 1px solid #e5e7eb; padding-bottom: 0.25rem; }
+.meta { color: #6b7280; font-size: 0.9rem; }
+table { border-collapse: collapse; font-size: 0.9rem; }
+th, td { border: 1px solid #e5e7eb; padding: 0.3rem 0.6rem; text-align: left; vertical-align: top; }
+th { background: #f9fafb; }
+pre { background: #f9fafb; padding: 0.75rem; overflow-x: auto; font-size: 0.8rem; }
+.charts { display: flex; flex-wrap: wrap; gap: 1.5rem; margin-top: 1rem; }
+.prompt-group { margin-bottom: 1.5rem; }
+.side-by-side { display: grid; grid-template-columns: repeat(auto-fit, minmax(280px, 1fr)); gap: 0.75rem; margin-top: 0.5rem; }
+.card { border: 1px solid #e5e7eb; border-radius: 6px; padding: 0.6rem; margin-bottom: 0.75rem; }
+.label { font-size: 0.75rem; color: #6b7280; margin-bottom: 0.4rem; }
+.system { white-space: pre-wrap; font-family: monospace; color: #d97706; }
+.prompt { white-space: pre-wrap; font-family: monospace; color: #16a34a; }
+.response { white-space: pre-wrap; font-size: 0.9rem; color: #0891b2; }
+.error { white-space: pre-wrap; font-size: 0.9rem; color: #dc2626; }
+.note { font-size: 0.8rem; font-style: italic; margin-top: 0.4rem; }
+"#;
+
+/// Distinct values, in the order they first appear
+fn distinct(rows: &[ExportRow], value: impl Fn(&ExportRow) -> String) -> Vec<String> {
+    let mut values: Vec<String> = vec![];
+    for row in rows {
+        let v = value(row);
+        if !values.contains(&v) {
+            values.push(v);
+        }
+    }
+    values
+}
+
+fn param_value(row: &ExportRow, param: &str) -> String {
+    match param {
+        "temperature" => row.temperature.to_string(),
+        "repeat_penalty" => row.repeat_penalty.to_string(),
+        "top_k" => row.top_k.to_string(),
+        "top_p" => row.top_p.to_string(),
+        "repeat_last_n" => row.repeat_last_n.to_string(),
+        "tfs_z" => row.tfs_z.to_string(),
+        "mirostat" => row.mirostat.to_string(),
+        "mirostat_tau" => row.mirostat_tau.to_string(),
+        "mirostat_eta" => row.mirostat_eta.to_string(),
+        "seed" => row.seed.to_string(),
+        "think" => think_value(row),
+        _ => row.model.clone(),
+    }
+}
+
+fn think_value(row: &ExportRow) -> String {
+    row.think
+        .map_or_else(|| "default".to_string(), |think| think.to_string())
+}
+
+fn average(values: impl Iterator<Item = Option<f64>>) -> Option<f64> {
+    let values: Vec<f64> = values.flatten().collect();
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn number(value: Option<f64>, decimals: usize) -> String {
+    value
+        .map(|v| format!("{:.*}", decimals, v))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn rating_text(row: &ExportRow) -> String {
+    let mut text = row
+        .rating
+        .map(|stars| format!("{}/5", stars))
+        .unwrap_or_default();
+    match row.thumb {
+        Some(1) => text.push_str(" 👍"),
+        Some(-1) => text.push_str(" 👎"),
+        _ => {}
+    }
+    text.trim().to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Markdown table cells are a single line, and `|` ends them
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('\r', "")
+        .replace('\n', "<br>")
+}
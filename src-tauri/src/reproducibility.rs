@@ -0,0 +1,243 @@
+/*
+Reproducibility checks.
+
+A check re-runs a logged inference with the same prompt, seed and options
+(the default options of the experiment's config), runs the post-hooks of the
+experiment's scripts on it and compares each output byte by byte with the
+logged one. Runs go one after another or, to expose
+nondeterminism from concurrent requests (e.g.: batching on the server), all
+at once. Reruns aren't added to the experiment.
+
+Checks are stored in `reproducibility_checks` with the Ollama version and
+the digest of the model (from /api/tags) at the time, so a change of results
+can be traced to a server upgrade or a new build of the model.
+*/
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+use sqlx::{Pool, Sqlite};
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+
+use crate::diff::{diff_lines, DiffLine};
+use crate::hooks::{experiment_scripts, run_post_hooks, Script};
+use crate::{generate, generation_request, Error, ExperimentLog, IDefaultConfigs, LoggedInference};
+
+pub const MAX_RUNS: usize = 20;
+
+/// The outcome of one rerun
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RunResult {
+    // Same response and reasoning as the logged inference
+    pub identical: bool,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    // Line diff from the logged response; empty when the responses are equal
+    pub diff: Vec<DiffLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct ReproducibilityCheck {
+    pub id: i64,
+    pub experiment_uuid: String,
+    pub inference_id: String,
+    pub model: String,
+    // None when the server didn't report them
+    pub model_digest: Option<String>,
+    pub ollama_version: Option<String>,
+    pub concurrent: bool,
+    pub run_count: i64,
+    pub identical_count: i64,
+    #[sqlx(json)]
+    pub runs: Vec<RunResult>,
+    pub date_created: i64, // Unix timestamp
+}
+
+fn server_url(config: &IDefaultConfigs, path: &str) -> String {
+    format!("{}{}", config.server_url.trim_end_matches('/'), path)
+}
+
+async fn get_json(config: &IDefaultConfigs, path: &str) -> Option<Value> {
+    Client::new()
+        .get(server_url(config, path))
+        .timeout(Duration::from_secs(config.request_timeout))
+        .send()
+        .await
+        .ok()?
+        .json()
+        .await
+        .ok()
+}
+
+/// The version of the Ollama server, if it reports it
+pub async fn server_version(config: &IDefaultConfigs) -> Option<String> {
+    let version = get_json(config, "/api/version").await?;
+    version["version"].as_str().map(String::from)
+}
+
+/// The digest of a local model, from /api/tags
+pub async fn model_digest(config: &IDefaultConfigs, model: &str) -> Option<String> {
+    let tags = get_json(config, "/api/tags").await?;
+    // Models pulled without a tag are listed as "<model>:latest"
+    let tagged = format!("{}:latest", model);
+    tags["models"]
+        .as_array()?
+        .iter()
+        .find(|m| m["name"] == model || m["name"] == tagged.as_str())
+        .and_then(|m| m["digest"].as_str())
+        .map(String::from)
+}
+
+/// The default options an experiment ran with (from its logged config), on
+/// the server of `config`.
+pub fn logged_run_config(config: &IDefaultConfigs, logged: &Value) -> IDefaultConfigs {
+    match serde_json::from_value::<IDefaultConfigs>(logged.clone()) {
+        Ok(logged) => IDefaultConfigs {
+            default_options: logged.default_options,
+            ..config.clone()
+        },
+        Err(_) => config.clone(),
+    }
+}
+
+/// Re-runs an inference `runs` times and compares each output with the
+/// logged one.
+pub async fn rerun_inference(
+    config: &IDefaultConfigs,
+    scripts: &[Script],
+    inference: &LoggedInference,
+    runs: usize,
+    concurrent: bool,
+) -> Result<Vec<RunResult>, Error> {
+    let Some(logged) = &inference.result else {
+        return Err(Error::StringError(format!(
+            "Inference {} failed, there's no response to reproduce",
+            inference.inference_id
+        )));
+    };
+    let params = &inference.parameters;
+    let prompt = inference
+        .rendered_prompt
+        .as_deref()
+        .unwrap_or(&params.prompt);
+
+    let mut outputs = Vec::with_capacity(runs);
+    if concurrent {
+        let mut tasks = JoinSet::new();
+        for idx in 0..runs {
+            let config = config.clone();
+            let req = generation_request(&config, params, prompt);
+            tasks.spawn(async move { (idx, generate(&config, req).await) });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            outputs.push(joined.map_err(|err| Error::StringError(err.to_string()))?);
+        }
+        outputs.sort_by_key(|(idx, _)| *idx);
+    } else {
+        for idx in 0..runs {
+            let req = generation_request(config, params, prompt);
+            outputs.push((idx, generate(config, req).await));
+        }
+    }
+
+    Ok(outputs
+        .into_iter()
+        .map(
+            |(_, output)| match output.and_then(|rerun| run_post_hooks(scripts, params, rerun)) {
+                Ok((rerun, _)) => RunResult {
+                    identical: rerun.response == logged.response
+                        && rerun.thinking == logged.thinking,
+                    diff: if rerun.response == logged.response {
+                        vec![]
+                    } else {
+                        diff_lines(&logged.response, &rerun.response)
+                    },
+                    response: Some(rerun.response),
+                    error: None,
+                },
+                Err(err_msg) => RunResult {
+                    identical: false,
+                    response: None,
+                    error: Some(err_msg),
+                    diff: vec![],
+                },
+            },
+        )
+        .collect())
+}
+
+/// Checks that each inference reproduces, storing and returning the checks.
+pub async fn verify_inferences(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    log: &ExperimentLog,
+    inference_ids: &[String],
+    runs: usize,
+    concurrent: bool,
+) -> Result<Vec<ReproducibilityCheck>, Error> {
+    if runs == 0 || runs > MAX_RUNS {
+        return Err(Error::StringError(format!(
+            "Invalid number of runs {}, use 1 to {}",
+            runs, MAX_RUNS
+        )));
+    }
+
+    let run_config = logged_run_config(config, &log.config);
+    let scripts = experiment_scripts(pool, &log.experiment_uuid).await?;
+    let ollama_version = server_version(config).await;
+
+    let mut checks = vec![];
+    for inference_id in inference_ids {
+        let inference = log
+            .inferences
+            .iter()
+            .find(|i| &i.inference_id == inference_id)
+            .ok_or_else(|| {
+                Error::StringError(format!(
+                    "Inference {} not found in experiment {}",
+                    inference_id, log.experiment_uuid
+                ))
+            })?;
+        let model = &inference.parameters.model;
+        let digest = model_digest(config, model).await;
+        let results = rerun_inference(&run_config, &scripts, inference, runs, concurrent).await?;
+        let identical_count = results.iter().filter(|r| r.identical).count();
+
+        let stmt = r#"
+            INSERT INTO reproducibility_checks (
+                experiment_uuid, inference_id, model, model_digest, ollama_version,
+                concurrent, run_count, identical_count, runs
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING *
+        "#;
+        let check = sqlx::query_as::<_, ReproducibilityCheck>(stmt)
+            .bind(&log.experiment_uuid)
+            .bind(inference_id)
+            .bind(model)
+            .bind(digest)
+            .bind(&ollama_version)
+            .bind(concurrent)
+            .bind(results.len() as i64)
+            .bind(identical_count as i64)
+            .bind(serde_json::to_string(&results)?)
+            .fetch_one(pool)
+            .await?;
+        checks.push(check);
+    }
+
+    Ok(checks)
+}
+
+/// The checks of an experiment, latest first.
+pub async fn get_checks(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+) -> Result<Vec<ReproducibilityCheck>, Error> {
+    Ok(sqlx::query_as::<_, ReproducibilityCheck>(
+        "SELECT * FROM reproducibility_checks WHERE experiment_uuid = $1 ORDER BY id DESC",
+    )
+    .bind(experiment_uuid)
+    .fetch_all(pool)
+    .await?)
+}
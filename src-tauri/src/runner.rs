@@ -0,0 +1,220 @@
+/*
+The inference pipeline.
+
+Each inference of a grid goes through the same steps, whether it's
+requested by the app (one `get_inference` command per iteration) or by the
+command-line runner:
+
+1. The prompt template is rendered with the variables of its dataset row.
+2. The pre-hooks of the experiment's scripts rewrite the prompt and options.
+3. The request is sent to the server.
+4. The post-hooks rewrite the response and may score it.
+5. The inference is logged, failed or not, and scored by the assertions,
+   the experiment's judge (when set to judge automatically) and the
+   evaluators.
+
+Problems scoring an inference are printed and don't fail it.
+*/
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::time::Instant;
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+
+use crate::evaluator::evaluate_inference;
+use crate::hooks::{experiment_scripts, run_post_hooks, run_pre_hooks, HOOK_SOURCE};
+use crate::judge::{get_judge, judge_inference};
+use crate::reasoning::token_split;
+use crate::scoring::{save_scores, score_inference};
+use crate::template::{has_template_syntax, prompt_includes, render_prompt};
+use crate::{
+    generate, generation_request, log_experiment, log_failed_inference, Error, IDefaultConfigs,
+    InferenceResponse, TParamIteration,
+};
+
+/// Runs, logs and scores a single inference of a grid.
+pub async fn run_inference(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    params: TParamIteration,
+) -> Result<InferenceResponse, Error> {
+    // Render the prompt template, filling variables and [placeholders]
+    // with the values of the dataset row, if any
+    let includes = if has_template_syntax(&params.prompt) {
+        prompt_includes(pool).await?
+    } else {
+        HashMap::new()
+    };
+    let mut prompt = render_prompt(&params.prompt, params.variables.as_ref(), &includes)?;
+
+    // Let the experiment's scripts rewrite the prompt and options. The template
+    // is still logged as the prompt, the rewritten prompt as the rendered one.
+    let scripts = experiment_scripts(pool, &params.experiment_uuid).await?;
+    let params = match run_pre_hooks(
+        &scripts,
+        &TParamIteration {
+            prompt: prompt.clone(),
+            ..params.clone()
+        },
+    ) {
+        Ok(hooked) => {
+            prompt = hooked.prompt;
+            TParamIteration {
+                prompt: params.prompt,
+                ..hooked
+            }
+        }
+        Err(err_msg) => {
+            log_failed_inference(pool, config, &params, &prompt, &err_msg).await?;
+            return Err(Error::StringError(err_msg));
+        }
+    };
+
+    let req = generation_request(config, &params, &prompt);
+    // .keep_alive(KeepAlive::Indefinitely);
+
+    // Process the inference; requests that time out fail like any other
+    let generation_response = match generate(config, req).await {
+        Ok(generation_response) => generation_response,
+        Err(err_msg) => {
+            log_failed_inference(pool, config, &params, &prompt, &err_msg).await?;
+            return Err(Error::StringError(err_msg));
+        }
+    };
+
+    // Log the inference; failures are kept too, so the experiment shows them
+    let (generation_response, hook_scores) =
+        match run_post_hooks(&scripts, &params, generation_response) {
+            Ok(hooked) => hooked,
+            Err(err_msg) => {
+                log_failed_inference(pool, config, &params, &prompt, &err_msg).await?;
+                return Err(Error::StringError(err_msg));
+            }
+        };
+    let inference_id = log_experiment(pool, config, &params, &prompt, &generation_response).await?;
+
+    // Scoring problems (e.g.: a malformed assertions column) don't fail the inference
+    if let Err(err) = score_inference(
+        pool,
+        &params.experiment_uuid,
+        &inference_id,
+        &params,
+        &generation_response.response,
+    )
+    .await
+    {
+        println!("Could not score inference {}: {}", inference_id, err);
+    }
+    if !hook_scores.is_empty() {
+        if let Err(err) = save_scores(
+            pool,
+            &params.experiment_uuid,
+            &inference_id,
+            HOOK_SOURCE,
+            &hook_scores,
+        )
+        .await
+        {
+            println!(
+                "Could not save the script scores of {}: {}",
+                inference_id, err
+            );
+        }
+    }
+    if let Ok(Some(judge)) = get_judge(pool, &params.experiment_uuid).await {
+        if judge.auto {
+            if let Err(err) = judge_inference(
+                pool,
+                config,
+                &judge,
+                &inference_id,
+                &params,
+                &prompt,
+                &generation_response.response,
+            )
+            .await
+            {
+                println!("Could not judge inference {}: {}", inference_id, err);
+            }
+        }
+    }
+    if let Err(err) = evaluate_inference(
+        pool,
+        &params.experiment_uuid,
+        &inference_id,
+        &params,
+        &prompt,
+        &generation_response.response,
+    )
+    .await
+    {
+        println!("Could not evaluate inference {}: {}", inference_id, err);
+    }
+
+    Ok(InferenceResponse {
+        inference_id,
+        reasoning_tokens: token_split(&generation_response).map(|(reasoning, _)| reasoning),
+        result: generation_response,
+    })
+}
+
+/// A finished inference of `run_grid`
+pub struct Progress {
+    // Position of the iteration in the grid, from 0
+    pub index: usize,
+    pub params: TParamIteration,
+    pub result: Result<InferenceResponse, Error>,
+    pub elapsed: Duration,
+}
+
+/// Runs every iteration of a grid, at most `concurrency` at a time, calling
+/// `on_done` as each one finishes. Returns the number of failed inferences.
+pub async fn run_grid(
+    pool: &Pool<Sqlite>,
+    config: &IDefaultConfigs,
+    iterations: Vec<TParamIteration>,
+    concurrency: usize,
+    mut on_done: impl FnMut(Progress),
+) -> usize {
+    let mut pending = iterations.into_iter().enumerate();
+    let mut tasks = JoinSet::new();
+    let mut failed = 0;
+
+    loop {
+        while tasks.len() < concurrency.max(1) {
+            let Some((index, params)) = pending.next() else {
+                break;
+            };
+            let (pool, config) = (pool.clone(), config.clone());
+            tasks.spawn(async move {
+                let started = Instant::now();
+                let result = run_inference(&pool, &config, params.clone()).await;
+                Progress {
+                    index,
+                    params,
+                    result,
+                    elapsed: started.elapsed(),
+                }
+            });
+        }
+
+        let Some(joined) = tasks.join_next().await else {
+            break;
+        };
+        match joined {
+            Ok(progress) => {
+                if progress.result.is_err() {
+                    failed += 1;
+                }
+                on_done(progress);
+            }
+            // A panicking inference is counted as failed
+            Err(err) => {
+                println!("Inference task failed: {}", err);
+                failed += 1;
+            }
+        }
+    }
+
+    failed
+}
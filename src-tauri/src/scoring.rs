@@ -0,0 +1,566 @@
+/*
+Reference-based scoring.
+
+Assertions check a response against an expected output:
+
+* exact_match: same text, ignoring surrounding whitespace.
+* contains: the response contains the expected text, ignoring case.
+* regex: the response matches a pattern.
+* numeric: the last number in the response is within `tolerance` of the
+  expected value. Commas are read as thousands separators ("1,234").
+* bleu, rouge_l, edit_distance: similarity to the expected text, from 0 to 1.
+
+Assertions are attached to prompts (stored with the prompt) or to dataset
+rows (an `assertions` column with a JSON list). Assertions without an
+expected value use the `expected` column of the dataset row, and a row
+with only an `expected` column gets every reference-based check.
+
+Every successful inference is scored after generation; scores range from
+0 to 1 and are stored in `inference_scores`, next to the judge and
+external evaluator scores.
+*/
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::prelude::FromRow;
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::{Error, TParamIteration};
+
+/// Dataset columns with the assertions and the expected output of a row
+pub const ASSERTIONS_COLUMN: &str = "assertions";
+pub const EXPECTED_COLUMN: &str = "expected";
+
+/// Where a stored score comes from
+pub const ASSERTION_SOURCE: &str = "assertion";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    ExactMatch {
+        #[serde(default)]
+        expected: Option<String>,
+    },
+    Contains {
+        #[serde(default)]
+        expected: Option<String>,
+    },
+    Regex {
+        pattern: String,
+    },
+    Numeric {
+        #[serde(default)]
+        expected: Option<f64>,
+        #[serde(default)]
+        tolerance: f64,
+    },
+    Bleu {
+        #[serde(default)]
+        expected: Option<String>,
+    },
+    RougeL {
+        #[serde(default)]
+        expected: Option<String>,
+    },
+    EditDistance {
+        #[serde(default)]
+        expected: Option<String>,
+    },
+}
+
+impl Assertion {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Assertion::ExactMatch { .. } => "exact_match",
+            Assertion::Contains { .. } => "contains",
+            Assertion::Regex { .. } => "regex",
+            Assertion::Numeric { .. } => "numeric",
+            Assertion::Bleu { .. } => "bleu",
+            Assertion::RougeL { .. } => "rouge_l",
+            Assertion::EditDistance { .. } => "edit_distance",
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        match self {
+            Assertion::Regex { pattern } => Regex::new(pattern)
+                .map(|_| ())
+                .map_err(|err| Error::StringError(format!("Invalid regex '{}': {}", pattern, err))),
+            Assertion::Numeric { tolerance, .. } if *tolerance < 0.0 => Err(Error::StringError(
+                format!("Invalid tolerance {}, it can't be negative", tolerance),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Scores a response; `None` if the assertion has no expected value to compare with.
+    fn score(&self, response: &str, row_expected: Option<&str>) -> Option<ScoreResult> {
+        let expected = |own: &Option<String>| own.clone().or(row_expected.map(String::from));
+        let check = |passed: bool, detail: String| ScoreResult {
+            name: self.name().to_string(),
+            score: Some(if passed { 1.0 } else { 0.0 }),
+            passed: Some(passed),
+            detail,
+        };
+        let similarity = |score: f64| ScoreResult {
+            name: self.name().to_string(),
+            score: Some(score),
+            passed: None,
+            detail: String::new(),
+        };
+
+        let result = match self {
+            Assertion::ExactMatch { expected: own } => {
+                let expected = expected(own)?;
+                check(response.trim() == expected.trim(), String::new())
+            }
+            Assertion::Contains { expected: own } => {
+                let expected = expected(own)?;
+                let found = response.to_lowercase().contains(&expected.to_lowercase());
+                check(found, String::new())
+            }
+            Assertion::Regex { pattern } => match Regex::new(pattern) {
+                Ok(re) => check(re.is_match(response), String::new()),
+                Err(err) => check(false, format!("Invalid regex: {}", err)),
+            },
+            Assertion::Numeric {
+                expected: own,
+                tolerance,
+            } => {
+                let expected = match own {
+                    Some(value) => *value,
+                    None => parse_number(row_expected?)?,
+                };
+                match last_number(response) {
+                    Some(found) => check(
+                        (found - expected).abs() <= *tolerance,
+                        format!("found {}", found),
+                    ),
+                    None => check(false, "no number in the response".to_string()),
+                }
+            }
+            Assertion::Bleu { expected: own } => {
+                similarity(bleu(&tokens(response), &tokens(&expected(own)?)))
+            }
+            Assertion::RougeL { expected: own } => {
+                similarity(rouge_l(&tokens(response), &tokens(&expected(own)?)))
+            }
+            Assertion::EditDistance { expected: own } => {
+                similarity(edit_similarity(response, &expected(own)?))
+            }
+        };
+        Some(result)
+    }
+}
+
+/// Every reference-based check, for rows that only have an expected output
+pub fn default_assertions() -> Vec<Assertion> {
+    vec![
+        Assertion::ExactMatch { expected: None },
+        Assertion::Contains { expected: None },
+        Assertion::Bleu { expected: None },
+        Assertion::RougeL { expected: None },
+        Assertion::EditDistance { expected: None },
+    ]
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScoreResult {
+    pub name: String,
+    // 0 to 1; None when the score couldn't be computed (see `detail`)
+    pub score: Option<f64>,
+    // Pass/fail checks only
+    pub passed: Option<bool>,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct InferenceScore {
+    pub experiment_uuid: String,
+    pub inference_id: String,
+    pub name: String,
+    // "assertion", "judge" or "evaluator"
+    pub source: String,
+    pub score: Option<f64>,
+    pub passed: Option<bool>,
+    pub detail: String,
+    pub date_created: i64, // Unix timestamp
+}
+
+/// The scores of an experiment's inferences, from every source.
+pub async fn get_scores(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+) -> Result<Vec<InferenceScore>, Error> {
+    let stmt = r#"
+        SELECT *
+        FROM inference_scores
+        WHERE experiment_uuid = $1
+        ORDER BY inference_id, source, name
+    "#;
+
+    Ok(sqlx::query_as::<_, InferenceScore>(stmt)
+        .bind(experiment_uuid)
+        .fetch_all(pool)
+        .await?)
+}
+
+/// Scores a response with every assertion that has an expected value.
+/// Repeated assertion types are numbered: "regex", "regex_2", ...
+pub fn score_response(
+    response: &str,
+    assertions: &[Assertion],
+    row_expected: Option<&str>,
+) -> Vec<ScoreResult> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    assertions
+        .iter()
+        .filter_map(|assertion| {
+            let mut result = assertion.score(response, row_expected)?;
+            let count = seen.entry(assertion.name()).or_insert(0);
+            *count += 1;
+            if *count > 1 {
+                result.name = format!("{}_{}", result.name, count);
+            }
+            Some(result)
+        })
+        .collect()
+}
+
+/// The assertions and expected output of a dataset row.
+/// In CSV datasets the assertions column holds the JSON list as text.
+pub fn row_assertions(
+    variables: Option<&Map<String, Value>>,
+) -> Result<(Vec<Assertion>, Option<String>), Error> {
+    let Some(row) = variables else {
+        return Ok((vec![], None));
+    };
+
+    let assertions = match row.get(ASSERTIONS_COLUMN) {
+        Some(Value::String(text)) if text.trim().is_empty() => vec![],
+        Some(Value::String(text)) => serde_json::from_str(text)?,
+        Some(Value::Null) | None => vec![],
+        Some(value) => serde_json::from_value(value.clone())?,
+    };
+    let expected = match row.get(EXPECTED_COLUMN) {
+        Some(Value::String(text)) => Some(text.clone()),
+        Some(Value::Null) | None => None,
+        Some(value) => Some(value.to_string()),
+    };
+
+    Ok((assertions, expected))
+}
+
+/// The assertions stored with the library prompt an inference ran, if any.
+pub async fn prompt_assertions(
+    pool: &Pool<Sqlite>,
+    prompt_uuid: Option<&str>,
+) -> Result<Vec<Assertion>, Error> {
+    let Some(prompt_uuid) = prompt_uuid else {
+        return Ok(vec![]);
+    };
+
+    let assertions: Option<(String,)> =
+        sqlx::query_as("SELECT assertions FROM prompts WHERE uuid = $1")
+            .bind(prompt_uuid)
+            .fetch_optional(pool)
+            .await?;
+
+    match assertions {
+        Some((assertions,)) => Ok(serde_json::from_str(&assertions)?),
+        None => Ok(vec![]),
+    }
+}
+
+/// Scores an inference with the assertions of its prompt and dataset row,
+/// replacing its previous assertion scores.
+pub async fn score_inference(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+    inference_id: &str,
+    params: &TParamIteration,
+    response: &str,
+) -> Result<Vec<ScoreResult>, Error> {
+    let (mut assertions, expected) = row_assertions(params.variables.as_ref())?;
+    assertions.extend(prompt_assertions(pool, params.prompt_uuid.as_deref()).await?);
+    if assertions.is_empty() && expected.is_some() {
+        assertions = default_assertions();
+    }
+
+    let results = score_response(response, &assertions, expected.as_deref());
+    save_scores(
+        pool,
+        experiment_uuid,
+        inference_id,
+        ASSERTION_SOURCE,
+        &results,
+    )
+    .await?;
+    Ok(results)
+}
+
+/// Replaces the scores of an inference from one source.
+pub async fn save_scores(
+    pool: &Pool<Sqlite>,
+    experiment_uuid: &str,
+    inference_id: &str,
+    source: &str,
+    results: &[ScoreResult],
+) -> Result<(), Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM inference_scores WHERE experiment_uuid = $1 AND inference_id = $2 AND source = $3",
+    )
+    .bind(experiment_uuid)
+    .bind(inference_id)
+    .bind(source)
+    .execute(&mut *tx)
+    .await?;
+
+    let stmt = r#"
+        INSERT INTO inference_scores (experiment_uuid, inference_id, name, source, score, passed, detail)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+    "#;
+    for result in results {
+        sqlx::query(stmt)
+            .bind(experiment_uuid)
+            .bind(inference_id)
+            .bind(&result.name)
+            .bind(source)
+            .bind(result.score)
+            .bind(result.passed)
+            .bind(&result.detail)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+// Commas only separate thousands ("1,234"), so a list like "1,2,3" is
+// read as three numbers
+fn number_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"-?\d{1,3}(?:,\d{3})+\b(?:\.\d+)?|-?\d+(?:\.\d+)?").unwrap())
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim().replace(',', "").parse().ok()
+}
+
+// The final answer usually comes last
+fn last_number(text: &str) -> Option<f64> {
+    number_regex()
+        .find_iter(text)
+        .filter_map(|m| parse_number(m.as_str()))
+        .last()
+}
+
+// Lowercase words, ignoring punctuation
+pub fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+fn ngram_counts(tokens: &[String], n: usize) -> HashMap<&[String], usize> {
+    let mut counts = HashMap::new();
+    for ngram in tokens.windows(n) {
+        *counts.entry(ngram).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Sentence BLEU-4, with add-one smoothing of the 2- to 4-gram precisions
+fn bleu(candidate: &[String], reference: &[String]) -> f64 {
+    if candidate.is_empty() || reference.is_empty() {
+        return 0.0;
+    }
+
+    let mut log_precision = 0.0;
+    for n in 1..=4 {
+        let candidate_ngrams = ngram_counts(candidate, n);
+        let reference_ngrams = ngram_counts(reference, n);
+        let matches: usize = candidate_ngrams
+            .iter()
+            .map(|(ngram, count)| (*count).min(*reference_ngrams.get(ngram).unwrap_or(&0)))
+            .sum();
+        let total = candidate.len().saturating_sub(n - 1);
+
+        let (matches, total) = if n == 1 {
+            (matches as f64, total as f64)
+        } else {
+            (matches as f64 + 1.0, total as f64 + 1.0)
+        };
+        if matches == 0.0 {
+            return 0.0;
+        }
+        log_precision += (matches / total).ln() / 4.0;
+    }
+
+    let (c, r) = (candidate.len() as f64, reference.len() as f64);
+    let brevity_penalty = if c > r { 1.0 } else { (1.0 - r / c).exp() };
+    brevity_penalty * log_precision.exp()
+}
+
+/// ROUGE-L F1, from the longest common subsequence of words
+fn rouge_l(candidate: &[String], reference: &[String]) -> f64 {
+    if candidate.is_empty() || reference.is_empty() {
+        return 0.0;
+    }
+
+    let mut previous = vec![0usize; reference.len() + 1];
+    for token in candidate {
+        let mut current = vec![0usize; reference.len() + 1];
+        for (j, reference_token) in reference.iter().enumerate() {
+            current[j + 1] = if token == reference_token {
+                previous[j] + 1
+            } else {
+                current[j].max(previous[j + 1])
+            };
+        }
+        previous = current;
+    }
+
+    let lcs = previous[reference.len()] as f64;
+    if lcs == 0.0 {
+        return 0.0;
+    }
+    let precision = lcs / candidate.len() as f64;
+    let recall = lcs / reference.len() as f64;
+    2.0 * precision * recall / (precision + recall)
+}
+
+/// 1 - Levenshtein distance / length of the longest text, over characters
+pub fn edit_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.trim().chars().collect();
+    let b: Vec<char> = b.trim().chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + (ca != cb) as usize;
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn last_number_reads_thousands_and_lists() {
+        assert_eq!(last_number("The answer is 42."), Some(42.0));
+        assert_eq!(last_number("It costs 1,234.50 dollars"), Some(1234.5));
+        assert_eq!(last_number("-3 degrees"), Some(-3.0));
+        assert_eq!(last_number("1,2,3"), Some(3.0));
+        assert_eq!(last_number("12,3456"), Some(3456.0));
+        assert_eq!(last_number("1,234,567 and 1, 2"), Some(2.0));
+        assert_eq!(last_number(""), None);
+        assert_eq!(last_number("no numbers"), None);
+    }
+
+    #[test]
+    fn bleu_known_values() {
+        let t = |text: &str| tokens(text);
+        assert!(close(bleu(&t("a b c d"), &t("a b c d")), 1.0));
+        // Same n-grams, one word short: only the brevity penalty applies
+        assert!(close(
+            bleu(&t("a b c d"), &t("a b c d e")),
+            (-0.25f64).exp()
+        ));
+        assert_eq!(bleu(&t("x y z"), &t("a b c")), 0.0);
+        assert_eq!(bleu(&[], &t("a b c")), 0.0);
+        assert_eq!(bleu(&t("a b c"), &[]), 0.0);
+    }
+
+    #[test]
+    fn rouge_l_known_values() {
+        let t = |text: &str| tokens(text);
+        assert!(close(rouge_l(&t("The cat sat."), &t("the cat sat")), 1.0));
+        // LCS "the cat": precision 2/3, recall 2/5
+        assert!(close(
+            rouge_l(&t("the cat sat"), &t("the cat on the mat")),
+            0.5
+        ));
+        assert_eq!(rouge_l(&t("x y"), &t("a b")), 0.0);
+        assert_eq!(rouge_l(&[], &t("a")), 0.0);
+    }
+
+    #[test]
+    fn edit_similarity_known_values() {
+        assert!(close(edit_similarity("kitten", "sitting"), 1.0 - 3.0 / 7.0));
+        assert!(close(edit_similarity(" same ", "same"), 1.0));
+        assert!(close(edit_similarity("", ""), 1.0));
+        assert!(close(edit_similarity("abc", ""), 0.0));
+    }
+
+    #[test]
+    fn assertions_check_responses() {
+        let assertions = vec![
+            Assertion::ExactMatch { expected: None },
+            Assertion::Contains {
+                expected: Some("PARIS".to_string()),
+            },
+            Assertion::Regex {
+                pattern: r"^\w+$".to_string(),
+            },
+            Assertion::Regex {
+                pattern: r"\d".to_string(),
+            },
+            Assertion::Numeric {
+                expected: Some(10.0),
+                tolerance: 0.5,
+            },
+        ];
+        let results = score_response(" Paris ", &assertions, Some("Paris"));
+        let passed: Vec<(&str, Option<bool>)> = results
+            .iter()
+            .map(|r| (r.name.as_str(), r.passed))
+            .collect();
+        assert_eq!(
+            passed,
+            vec![
+                ("exact_match", Some(true)),
+                ("contains", Some(true)),
+                ("regex", Some(false)),
+                ("regex_2", Some(false)),
+                ("numeric", Some(false)),
+            ]
+        );
+        assert_eq!(results[4].detail, "no number in the response");
+
+        let numeric = [Assertion::Numeric {
+            expected: None,
+            tolerance: 0.0,
+        }];
+        let results = score_response("about 1,000", &numeric, Some("1000"));
+        assert_eq!(results[0].passed, Some(true));
+        // Nothing to compare with
+        assert!(score_response("1,000", &numeric, None).is_empty());
+        assert!(Assertion::Numeric {
+            expected: None,
+            tolerance: -1.0
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,506 @@
+/*
+Experiment spec files.
+
+A spec describes a grid search the way the form of the app does, so it can
+be committed to git and run again later, from the app or without it (see
+the `grid-search-cli` binary). It's a versioned JSON, TOML or YAML file; the
+format is chosen from the file extension:
+
+    version = 1
+    models = ["llama3.2:3b", "qwen3:4b"]
+    # Inline, or by the slug of a prompt in the prompt archive
+    prompts = ["Summarize the text: [text]", { slug = "summarize" }]
+    system_prompts = ["You are a helpful AI assistant."]
+    # Fills the [placeholders] of the prompts, relative to the spec file
+    dataset = "articles.csv"
+    temperature = [0.2, 0.8]
+    think = ["default", "off"]
+    generations = 3
+    scripts = ["strip-thinking"]
+    evaluators = ["rouge"]
+    concurrency = 2
+
+    [server]
+    server_url = "http://gpu-box:11434"
+    request_timeout = 600
+    default_options = { num_ctx = 4096 }
+
+    [judge]
+    model = "llama3.1:8b"
+    rubric = "Is the summary faithful to the text?"
+
+Parameter lists that are left out default to the app's default options. The
+grid is expanded in the same order as in the app, with the generation
+number as the seed.
+*/
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{Pool, Sqlite};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::dataset::{load_dataset, validate_prompts, Dataset};
+use crate::evaluator::enabled_evaluators;
+use crate::judge::JudgeInput;
+use crate::{Error, IDefaultConfigs, TParamIteration};
+
+/// Bump when the spec format changes in a non backwards compatible way
+pub const SPEC_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExperimentSpec {
+    pub version: u32,
+    pub server: ServerProfile,
+    pub models: Vec<String>,
+    pub prompts: Vec<SpecPrompt>,
+    pub system_prompts: Vec<String>,
+    // Path of a CSV or JSONL file; the grid is expanded over its rows
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dataset: Option<String>,
+    // f64, so they're written as typed (TOML widens f32 values)
+    pub temperature: Vec<f64>,
+    pub repeat_penalty: Vec<f64>,
+    pub top_k: Vec<u32>,
+    pub top_p: Vec<f64>,
+    pub repeat_last_n: Vec<i32>,
+    pub tfs_z: Vec<f64>,
+    pub mirostat: Vec<u8>,
+    pub mirostat_tau: Vec<f64>,
+    pub mirostat_eta: Vec<f64>,
+    pub think: Vec<ThinkMode>,
+    pub generations: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub judge: Option<JudgeInput>,
+    // Names of the scripts run on each inference, in order
+    pub scripts: Vec<String>,
+    // Names of the evaluators that score the inferences
+    pub evaluators: Vec<String>,
+    // Inferences run at the same time
+    pub concurrency: usize,
+}
+
+/// The server settings of a spec; unset ones are left to the app's settings
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerProfile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_timeout: Option<u64>,
+    // Options sent with every request (e.g.: num_ctx)
+    pub default_options: HashMap<String, Value>,
+}
+
+/// A prompt template, inline or by the slug of an archived prompt
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SpecPrompt {
+    Text(String),
+    Slug { slug: String },
+}
+
+/// A prompt template of the spec, with the archived prompt it was read from
+#[derive(Debug, Serialize, Clone)]
+pub struct ResolvedPrompt {
+    pub prompt: String,
+    pub uuid: Option<String>,
+}
+
+/// TOML has no null, so "default" stands for leaving think mode to the model
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThinkMode {
+    Default,
+    On,
+    Off,
+}
+
+impl ThinkMode {
+    pub fn as_option(self) -> Option<bool> {
+        match self {
+            ThinkMode::Default => None,
+            ThinkMode::On => Some(true),
+            ThinkMode::Off => Some(false),
+        }
+    }
+}
+
+impl Default for ExperimentSpec {
+    fn default() -> Self {
+        Self {
+            // A spec without a version is rejected
+            version: 0,
+            server: ServerProfile::default(),
+            models: vec![],
+            prompts: vec![],
+            system_prompts: vec![String::new()],
+            dataset: None,
+            temperature: vec![0.7],
+            repeat_penalty: vec![1.1],
+            top_k: vec![40],
+            top_p: vec![0.9],
+            repeat_last_n: vec![64],
+            tfs_z: vec![1.0],
+            mirostat: vec![0],
+            mirostat_tau: vec![5.0],
+            mirostat_eta: vec![0.1],
+            think: vec![ThinkMode::Default],
+            generations: 1,
+            judge: None,
+            scripts: vec![],
+            evaluators: vec![],
+            concurrency: 1,
+        }
+    }
+}
+
+impl ServerProfile {
+    /// Overrides `config` with the settings of the profile. Default options
+    /// are merged, the profile's taking precedence.
+    pub fn apply(&self, mut config: IDefaultConfigs) -> IDefaultConfigs {
+        if let Some(server_url) = &self.server_url {
+            config.server_url = server_url.clone();
+        }
+        if let Some(request_timeout) = self.request_timeout {
+            config.request_timeout = request_timeout;
+        }
+        config.default_options.extend(
+            self.default_options
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        config
+    }
+
+    /// The integer options are parsed when each request is built, so a
+    /// value of the wrong type (e.g.: `num_ctx = "4096"`) is rejected here.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.request_timeout == Some(0) {
+            return Err(Error::StringError(
+                "The spec's request timeout must be at least 1 second".to_string(),
+            ));
+        }
+        for (name, min, max) in [
+            ("num_ctx", 0, u64::MAX as i128),
+            ("num_gqa", 0, u32::MAX as i128),
+            ("num_gpu", 0, u32::MAX as i128),
+            ("num_thread", 0, u32::MAX as i128),
+            ("num_predict", i32::MIN as i128, i32::MAX as i128),
+        ] {
+            let Some(value) = self.default_options.get(name) else {
+                continue;
+            };
+            let number = value
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| value.as_u64().map(i128::from));
+            if !number.is_some_and(|n| (min..=max).contains(&n)) {
+                return Err(Error::StringError(format!(
+                    "The spec's default option '{}' must be an integer from {} to {}, not {}",
+                    name, min, max, value
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpecFormat {
+    Json,
+    Toml,
+    Yaml,
+}
+
+fn spec_format(path: &Path) -> Result<SpecFormat, Error> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "json" => Ok(SpecFormat::Json),
+        "toml" => Ok(SpecFormat::Toml),
+        "yaml" | "yml" => Ok(SpecFormat::Yaml),
+        _ => Err(Error::StringError(format!(
+            "Unsupported spec format: '{}'. Use a .json, .toml, .yaml or .yml file.",
+            path.display()
+        ))),
+    }
+}
+
+impl ExperimentSpec {
+    /// Reads and validates a spec file. A relative dataset path is resolved
+    /// against the directory of the spec.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path)?;
+
+        let mut spec: Self = match spec_format(path)? {
+            SpecFormat::Json => serde_json::from_str(&contents)?,
+            SpecFormat::Toml => toml::from_str(&contents)?,
+            SpecFormat::Yaml => serde_yaml::from_str(&contents)?,
+        };
+        spec.validate()?;
+
+        if let (Some(dataset), Some(dir)) = (&mut spec.dataset, path.parent()) {
+            if Path::new(dataset.as_str()).is_relative() {
+                *dataset = dir.join(&*dataset).to_string_lossy().into_owned();
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Validates and writes a spec file. A dataset in the directory of the
+    /// spec (or below it) is written as a relative path, so both can be
+    /// moved together.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        self.validate()?;
+
+        let mut spec = self.clone();
+        if let (Some(dataset), Some(dir)) = (&mut spec.dataset, path.parent()) {
+            if let Ok(relative) = Path::new(dataset.as_str()).strip_prefix(dir) {
+                *dataset = relative.to_string_lossy().into_owned();
+            }
+        }
+
+        let contents = match spec_format(path)? {
+            SpecFormat::Json => serde_json::to_string_pretty(&spec)?,
+            SpecFormat::Toml => toml::to_string(&spec)?,
+            SpecFormat::Yaml => serde_yaml::to_string(&spec)?,
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Every list must have at least one value, so the grid isn't empty
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.version == 0 {
+            return Err(Error::StringError(format!(
+                "The spec must set its format version (version = {})",
+                SPEC_VERSION
+            )));
+        }
+        if self.version > SPEC_VERSION {
+            return Err(Error::StringError(format!(
+                "Spec version {} is newer than the supported version ({}). Please update the app.",
+                self.version, SPEC_VERSION
+            )));
+        }
+
+        let lists = [
+            ("models", self.models.len()),
+            ("prompts", self.prompts.len()),
+            ("system_prompts", self.system_prompts.len()),
+            ("temperature", self.temperature.len()),
+            ("repeat_penalty", self.repeat_penalty.len()),
+            ("top_k", self.top_k.len()),
+            ("top_p", self.top_p.len()),
+            ("repeat_last_n", self.repeat_last_n.len()),
+            ("tfs_z", self.tfs_z.len()),
+            ("mirostat", self.mirostat.len()),
+            ("mirostat_tau", self.mirostat_tau.len()),
+            ("mirostat_eta", self.mirostat_eta.len()),
+            ("think", self.think.len()),
+        ];
+        if let Some((name, _)) = lists.iter().find(|(_, len)| *len == 0) {
+            return Err(Error::StringError(format!(
+                "The spec must list at least one value in '{}'",
+                name
+            )));
+        }
+        if self.models.iter().any(|m| m.trim().is_empty()) {
+            return Err(Error::StringError(
+                "The spec lists an empty model name".to_string(),
+            ));
+        }
+        if self.prompts.iter().any(|prompt| match prompt {
+            SpecPrompt::Text(text) => text.trim().is_empty(),
+            SpecPrompt::Slug { slug } => slug.trim().is_empty(),
+        }) {
+            return Err(Error::StringError(
+                "The spec lists an empty prompt".to_string(),
+            ));
+        }
+        if self.generations == 0 {
+            return Err(Error::StringError(
+                "The spec must run at least one generation".to_string(),
+            ));
+        }
+        if self.concurrency == 0 {
+            return Err(Error::StringError(
+                "The spec must run at least one inference at a time".to_string(),
+            ));
+        }
+        self.server.validate()?;
+        if let Some(judge) = &self.judge {
+            judge.validate()?;
+        }
+        Ok(())
+    }
+
+    /// The prompt templates of the spec, with the ones given by slug read
+    /// from the prompt archive.
+    pub async fn resolve_prompts(&self, pool: &Pool<Sqlite>) -> Result<Vec<ResolvedPrompt>, Error> {
+        let mut prompts = Vec::with_capacity(self.prompts.len());
+        for prompt in &self.prompts {
+            match prompt {
+                SpecPrompt::Text(text) => prompts.push(ResolvedPrompt {
+                    prompt: text.clone(),
+                    uuid: None,
+                }),
+                SpecPrompt::Slug { slug } => {
+                    let archived: Option<(String, String)> =
+                        sqlx::query_as("SELECT uuid, prompt FROM prompts WHERE slug = $1")
+                            .bind(slug.trim())
+                            .fetch_optional(pool)
+                            .await?;
+                    let (uuid, prompt) = archived.ok_or_else(|| {
+                        Error::StringError(format!(
+                            "No prompt with the slug '{}' in the prompt archive",
+                            slug
+                        ))
+                    })?;
+                    prompts.push(ResolvedPrompt {
+                        prompt,
+                        uuid: Some(uuid),
+                    });
+                }
+            }
+        }
+        Ok(prompts)
+    }
+
+    /// Checks that the evaluators listed by the spec are set up and enabled.
+    /// Only the listed evaluators score the inferences of the experiment.
+    pub async fn check_evaluators(&self, pool: &Pool<Sqlite>) -> Result<(), Error> {
+        let enabled: Vec<String> = enabled_evaluators(pool)
+            .await?
+            .into_iter()
+            .map(|evaluator| evaluator.name)
+            .collect();
+        if let Some(missing) = self.evaluators.iter().find(|name| !enabled.contains(name)) {
+            return Err(Error::StringError(format!(
+                "The spec's evaluator '{}' isn't set up or enabled",
+                missing
+            )));
+        }
+        Ok(())
+    }
+
+    /// Loads the spec's dataset, if any, checking that its columns fill the
+    /// placeholders of every prompt.
+    pub fn load_dataset(&self, prompts: &[ResolvedPrompt]) -> Result<Option<Dataset>, Error> {
+        let Some(path) = self.dataset.as_deref().filter(|p| !p.is_empty()) else {
+            return Ok(None);
+        };
+        let dataset = load_dataset(path)?;
+        let texts: Vec<String> = prompts.iter().map(|p| p.prompt.clone()).collect();
+        validate_prompts(&texts, &dataset)?;
+        Ok(Some(dataset))
+    }
+
+    /// Expands the grid into the parameters of each inference, given the
+    /// resolved prompts.
+    pub fn iterations(
+        &self,
+        experiment_uuid: &str,
+        prompts: &[ResolvedPrompt],
+        dataset: Option<&Dataset>,
+    ) -> Vec<TParamIteration> {
+        let base = TParamIteration {
+            experiment_uuid: experiment_uuid.to_string(),
+            model: String::new(),
+            prompt: String::new(),
+            prompt_uuid: None,
+            system_prompt: String::new(),
+            temperature: 0.0,
+            repeat_penalty: 0.0,
+            top_k: 0,
+            top_p: 0.0,
+            repeat_last_n: 0,
+            tfs_z: 0.0,
+            mirostat: 0,
+            mirostat_tau: 0.0,
+            mirostat_eta: 0.0,
+            seed: 0,
+            think: None,
+            dataset_row: None,
+            variables: None,
+        };
+        // Without a dataset, each prompt runs once with no variables
+        let rows: Vec<Option<(usize, Map<String, Value>)>> = match dataset {
+            Some(dataset) => dataset.rows.iter().cloned().enumerate().map(Some).collect(),
+            None => vec![None],
+        };
+        let generations: Vec<u32> = (0..self.generations).collect();
+
+        // The first parameter crossed varies the slowest
+        let grid = cross(vec![base], &self.models, |p, v| p.model = v);
+        let grid = cross(grid, prompts, |p, v| {
+            p.prompt = v.prompt;
+            p.prompt_uuid = v.uuid;
+        });
+        let grid = cross(grid, &self.system_prompts, |p, v| p.system_prompt = v);
+        let grid = cross(grid, &rows, |p, row| {
+            if let Some((idx, values)) = row {
+                p.dataset_row = Some(idx);
+                p.variables = Some(values);
+            }
+        });
+        let grid = cross(grid, &self.temperature, |p, v| p.temperature = v as f32);
+        let grid = cross(grid, &self.repeat_penalty, |p, v| {
+            p.repeat_penalty = v as f32
+        });
+        let grid = cross(grid, &self.top_k, |p, v| p.top_k = v);
+        let grid = cross(grid, &self.top_p, |p, v| p.top_p = v as f32);
+        let grid = cross(grid, &self.repeat_last_n, |p, v| p.repeat_last_n = v);
+        let grid = cross(grid, &self.tfs_z, |p, v| p.tfs_z = v as f32);
+        let grid = cross(grid, &self.mirostat, |p, v| p.mirostat = v);
+        let grid = cross(grid, &self.mirostat_tau, |p, v| p.mirostat_tau = v as f32);
+        let grid = cross(grid, &self.mirostat_eta, |p, v| p.mirostat_eta = v as f32);
+        let grid = cross(grid, &self.think, |p, v| p.think = v.as_option());
+        // seed = generation, so results differ when temp > 0
+        cross(grid, &generations, |p, v| p.seed = v as i32)
+    }
+}
+
+/// Crosses every iteration of a grid with each value of a parameter.
+fn cross<T: Clone>(
+    grid: Vec<TParamIteration>,
+    values: &[T],
+    set: impl Fn(&mut TParamIteration, T),
+) -> Vec<TParamIteration> {
+    let mut crossed = Vec::with_capacity(grid.len() * values.len());
+    for params in grid {
+        for value in values {
+            let mut params = params.clone();
+            set(&mut params, value.clone());
+            crossed.push(params);
+        }
+    }
+    crossed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn profile(options: Value) -> ServerProfile {
+        serde_json::from_value(json!({ "default_options": options })).unwrap()
+    }
+
+    #[test]
+    fn integer_options_are_type_checked() {
+        assert!(profile(json!({ "num_ctx": 4096, "num_predict": -1 }))
+            .validate()
+            .is_ok());
+        assert!(profile(json!({ "num_ctx": "4096" })).validate().is_err());
+        assert!(profile(json!({ "num_gpu": 1.5 })).validate().is_err());
+        assert!(profile(json!({ "num_thread": -2 })).validate().is_err());
+        assert!(profile(json!({ "stop": "###" })).validate().is_ok());
+    }
+}
@@ -0,0 +1,515 @@
+/*
+Experiment summaries.
+
+Inferences are grouped by model, system prompt and parameter combination;
+the prompts and generations of a combination are pooled together.
+Human ratings are aggregated for the whole experiment and for each group.
+Automatic scores are averaged per name, and the groups ranked by their mean
+score.
+
+Performance metrics are derived from the durations and token counts Ollama
+reports for each inference (the tokens of reasoning models are split between
+reasoning and answer), and summarized with the mean, median, p95,
+standard deviation and a 95% confidence interval of the mean.
+*/
+use ollama_rs::generation::completion::GenerationResponse;
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::reasoning::token_split;
+use crate::scoring::InferenceScore;
+use crate::{ExperimentLog, TParamIteration};
+
+// Two-sided 95% critical values of Student's t, for 1 to 30 degrees of freedom
+const T_CRITICAL_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+// Normal approximation, beyond 30 degrees of freedom
+const Z_95: f64 = 1.96;
+
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct InferenceRating {
+    pub experiment_uuid: String,
+    pub inference_id: String,
+    // 1 = thumbs up, -1 = thumbs down
+    pub thumb: Option<i64>,
+    // 1 to 5
+    pub rating: Option<i64>,
+    // Custom rubric, criterion -> score
+    #[sqlx(json)]
+    pub rubric: BTreeMap<String, f64>,
+    pub note: String,
+    pub last_modified: i64, // Unix timestamp
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ParamCombination {
+    pub model: String,
+    #[serde(default)]
+    pub system_prompt: String,
+    pub temperature: f32,
+    pub repeat_penalty: f32,
+    pub top_k: u32,
+    pub top_p: f32,
+    pub repeat_last_n: i32,
+    pub tfs_z: f32,
+    pub mirostat: u8,
+    pub mirostat_tau: f32,
+    pub mirostat_eta: f32,
+    #[serde(default)]
+    pub think: Option<bool>,
+}
+
+impl ParamCombination {
+    /// Identifies the combination; f32 values can't be hashed, so the JSON text is used
+    pub fn key(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+impl From<&TParamIteration> for ParamCombination {
+    fn from(params: &TParamIteration) -> Self {
+        Self {
+            model: params.model.clone(),
+            system_prompt: params.system_prompt.clone(),
+            temperature: params.temperature,
+            repeat_penalty: params.repeat_penalty,
+            top_k: params.top_k,
+            top_p: params.top_p,
+            repeat_last_n: params.repeat_last_n,
+            tfs_z: params.tfs_z,
+            mirostat: params.mirostat,
+            mirostat_tau: params.mirostat_tau,
+            mirostat_eta: params.mirostat_eta,
+            think: params.think,
+        }
+    }
+}
+
+/// Performance of a single inference, derived from Ollama's durations
+/// (reported in nanoseconds) and token counts
+#[derive(Debug, Serialize, Default, Clone, Copy)]
+pub struct InferenceMetrics {
+    pub prompt_tokens_per_second: Option<f64>,
+    pub generation_tokens_per_second: Option<f64>,
+    // Seconds: loading the model plus evaluating the prompt
+    pub time_to_first_token: Option<f64>,
+    // Seconds
+    pub load_time: Option<f64>,
+    // Seconds
+    pub total_time: Option<f64>,
+    // Estimated, see `reasoning::token_split`
+    pub reasoning_tokens: Option<f64>,
+    pub answer_tokens: Option<f64>,
+}
+
+impl InferenceMetrics {
+    pub fn from_response(response: &GenerationResponse) -> Self {
+        let seconds = |nanos: Option<u64>| nanos.map(|n| n as f64 / 1e9);
+        let rate = |count: Option<u64>, nanos: Option<u64>| match (count, nanos) {
+            (Some(count), Some(nanos)) if nanos > 0 => Some(count as f64 / (nanos as f64 / 1e9)),
+            _ => None,
+        };
+        let tokens = token_split(response);
+
+        Self {
+            prompt_tokens_per_second: rate(
+                response.prompt_eval_count,
+                response.prompt_eval_duration,
+            ),
+            generation_tokens_per_second: rate(response.eval_count, response.eval_duration),
+            time_to_first_token: seconds(response.prompt_eval_duration)
+                .map(|prompt| prompt + seconds(response.load_duration).unwrap_or(0.0)),
+            load_time: seconds(response.load_duration),
+            total_time: seconds(response.total_duration),
+            reasoning_tokens: tokens.map(|(reasoning, _)| reasoning as f64),
+            answer_tokens: tokens.map(|(_, answer)| answer as f64),
+        }
+    }
+}
+
+/// Distribution of a metric over a set of inferences
+#[derive(Debug, Serialize, Clone)]
+pub struct MetricStats {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub p95: f64,
+    // Sample standard deviation and 95% confidence interval of the mean,
+    // only with two or more values
+    pub std_dev: Option<f64>,
+    pub ci95_low: Option<f64>,
+    pub ci95_high: Option<f64>,
+}
+
+impl MetricStats {
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let std_dev = (n > 1).then(|| {
+            let variance = sorted.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+            variance.sqrt()
+        });
+        let margin = std_dev.map(|sd| {
+            let t = T_CRITICAL_95.get(n - 2).copied().unwrap_or(Z_95);
+            t * sd / (n as f64).sqrt()
+        });
+
+        Some(Self {
+            count: n,
+            mean,
+            median: percentile(&sorted, 0.5),
+            p95: percentile(&sorted, 0.95),
+            std_dev,
+            ci95_low: margin.map(|m| mean - m),
+            ci95_high: margin.map(|m| mean + m),
+        })
+    }
+}
+
+// Linear interpolation between the closest ranks of sorted values
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct PerformanceStats {
+    pub prompt_tokens_per_second: Option<MetricStats>,
+    pub generation_tokens_per_second: Option<MetricStats>,
+    pub time_to_first_token: Option<MetricStats>,
+    pub load_time: Option<MetricStats>,
+    pub total_time: Option<MetricStats>,
+    pub reasoning_tokens: Option<MetricStats>,
+    pub answer_tokens: Option<MetricStats>,
+}
+
+impl PerformanceStats {
+    fn from_metrics(metrics: &[InferenceMetrics]) -> Self {
+        let stats = |metric: fn(&InferenceMetrics) -> Option<f64>| {
+            MetricStats::from_values(&metrics.iter().filter_map(metric).collect::<Vec<_>>())
+        };
+
+        Self {
+            prompt_tokens_per_second: stats(|m| m.prompt_tokens_per_second),
+            generation_tokens_per_second: stats(|m| m.generation_tokens_per_second),
+            time_to_first_token: stats(|m| m.time_to_first_token),
+            load_time: stats(|m| m.load_time),
+            total_time: stats(|m| m.total_time),
+            reasoning_tokens: stats(|m| m.reasoning_tokens),
+            answer_tokens: stats(|m| m.answer_tokens),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct RatingStats {
+    pub rated_count: usize,
+    pub thumbs_up: usize,
+    pub thumbs_down: usize,
+    pub avg_rating: Option<f64>,
+    // Average score of each rubric criterion
+    pub rubric: BTreeMap<String, f64>,
+    pub note_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupSummary {
+    pub combination: ParamCombination,
+    pub inference_count: usize,
+    pub error_count: usize,
+    pub ratings: RatingStats,
+    // Over the successful inferences of the group
+    pub performance: PerformanceStats,
+    pub scores: ScoreStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentStats {
+    pub experiment_uuid: String,
+    pub inference_count: usize,
+    pub error_count: usize,
+    pub ratings: RatingStats,
+    pub performance: PerformanceStats,
+    pub scores: ScoreStats,
+    // In the order each combination first appears in the experiment
+    pub groups: Vec<GroupSummary>,
+    // The score name the groups are ranked by, if anything was scored
+    pub ranked_by: Option<String>,
+    // Indices into `groups`, best mean `ranked_by` score first; groups without
+    // that score are left out
+    pub score_ranking: Vec<usize>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ScoreStats {
+    pub scored_count: usize,
+    // Average of each score name
+    pub means: BTreeMap<String, f64>,
+    // Average of every score, whatever its name
+    pub mean_score: Option<f64>,
+    // Share of the pass/fail checks that passed
+    pub pass_rate: Option<f64>,
+}
+
+#[derive(Default)]
+struct RatingAccumulator {
+    rated_count: usize,
+    thumbs_up: usize,
+    thumbs_down: usize,
+    rating_sum: f64,
+    rating_count: usize,
+    rubric: BTreeMap<String, (f64, usize)>,
+    note_count: usize,
+}
+
+impl RatingAccumulator {
+    fn add(&mut self, rating: &InferenceRating) {
+        self.rated_count += 1;
+        match rating.thumb {
+            Some(1) => self.thumbs_up += 1,
+            Some(-1) => self.thumbs_down += 1,
+            _ => {}
+        }
+        if let Some(value) = rating.rating {
+            self.rating_sum += value as f64;
+            self.rating_count += 1;
+        }
+        for (criterion, score) in &rating.rubric {
+            let entry = self.rubric.entry(criterion.clone()).or_default();
+            entry.0 += score;
+            entry.1 += 1;
+        }
+        if !rating.note.is_empty() {
+            self.note_count += 1;
+        }
+    }
+
+    fn finish(self) -> RatingStats {
+        RatingStats {
+            rated_count: self.rated_count,
+            thumbs_up: self.thumbs_up,
+            thumbs_down: self.thumbs_down,
+            avg_rating: (self.rating_count > 0).then(|| self.rating_sum / self.rating_count as f64),
+            rubric: self
+                .rubric
+                .into_iter()
+                .map(|(criterion, (sum, count))| (criterion, sum / count as f64))
+                .collect(),
+            note_count: self.note_count,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ScoreAccumulator {
+    scored_count: usize,
+    means: BTreeMap<String, (f64, usize)>,
+    score_sum: f64,
+    score_count: usize,
+    passed: usize,
+    checked: usize,
+}
+
+impl ScoreAccumulator {
+    fn add(&mut self, scores: &[&InferenceScore]) {
+        self.scored_count += 1;
+        for score in scores {
+            if let Some(value) = score.score {
+                let entry = self.means.entry(score.name.clone()).or_default();
+                entry.0 += value;
+                entry.1 += 1;
+                self.score_sum += value;
+                self.score_count += 1;
+            }
+            if let Some(passed) = score.passed {
+                self.checked += 1;
+                if passed {
+                    self.passed += 1;
+                }
+            }
+        }
+    }
+
+    fn finish(self) -> ScoreStats {
+        ScoreStats {
+            scored_count: self.scored_count,
+            means: self
+                .means
+                .into_iter()
+                .map(|(name, (sum, count))| (name, sum / count as f64))
+                .collect(),
+            mean_score: (self.score_count > 0).then(|| self.score_sum / self.score_count as f64),
+            pass_rate: (self.checked > 0).then(|| self.passed as f64 / self.checked as f64),
+        }
+    }
+}
+
+struct GroupAccumulator {
+    combination: ParamCombination,
+    inference_count: usize,
+    error_count: usize,
+    ratings: RatingAccumulator,
+    metrics: Vec<InferenceMetrics>,
+    scores: ScoreAccumulator,
+}
+
+/// Summarizes an experiment log, aggregating the ratings and scores of its
+/// inferences. Groups are ranked by the mean of the `rank_by` score, or of
+/// the first score name when it's not given (or nothing has that name).
+pub fn summarize_experiment(
+    log: &ExperimentLog,
+    ratings: &[InferenceRating],
+    scores: &[InferenceScore],
+    rank_by: Option<&str>,
+) -> ExperimentStats {
+    let ratings: HashMap<&str, &InferenceRating> = ratings
+        .iter()
+        .map(|r| (r.inference_id.as_str(), r))
+        .collect();
+    let mut scores_by_inference: HashMap<&str, Vec<&InferenceScore>> = HashMap::new();
+    for score in scores {
+        scores_by_inference
+            .entry(score.inference_id.as_str())
+            .or_default()
+            .push(score);
+    }
+
+    let mut overall = RatingAccumulator::default();
+    let mut overall_scores = ScoreAccumulator::default();
+    let mut metrics = vec![];
+    let mut error_count = 0;
+    let mut groups: Vec<GroupAccumulator> = vec![];
+    let mut group_index: HashMap<String, usize> = HashMap::new();
+
+    for inference in &log.inferences {
+        let combination = ParamCombination::from(&inference.parameters);
+        let idx = *group_index.entry(combination.key()).or_insert_with(|| {
+            groups.push(GroupAccumulator {
+                combination,
+                inference_count: 0,
+                error_count: 0,
+                ratings: RatingAccumulator::default(),
+                metrics: vec![],
+                scores: ScoreAccumulator::default(),
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[idx];
+
+        group.inference_count += 1;
+        if inference.error.is_some() {
+            group.error_count += 1;
+            error_count += 1;
+        }
+        if let Some(result) = &inference.result {
+            let inference_metrics = InferenceMetrics::from_response(result);
+            group.metrics.push(inference_metrics);
+            metrics.push(inference_metrics);
+        }
+        if let Some(rating) = ratings.get(inference.inference_id.as_str()) {
+            group.ratings.add(rating);
+            overall.add(rating);
+        }
+        if let Some(scores) = scores_by_inference.get(inference.inference_id.as_str()) {
+            group.scores.add(scores);
+            overall_scores.add(scores);
+        }
+    }
+
+    let groups: Vec<GroupSummary> = groups
+        .into_iter()
+        .map(|g| GroupSummary {
+            combination: g.combination,
+            inference_count: g.inference_count,
+            error_count: g.error_count,
+            ratings: g.ratings.finish(),
+            performance: PerformanceStats::from_metrics(&g.metrics),
+            scores: g.scores.finish(),
+        })
+        .collect();
+
+    // Scores of different names have different scales, so only one is ranked by
+    let overall_scores = overall_scores.finish();
+    let ranked_by = rank_by
+        .filter(|name| overall_scores.means.contains_key(*name))
+        .map(String::from)
+        .or_else(|| overall_scores.means.keys().next().cloned());
+    let mut score_ranking: Vec<(usize, f64)> = match &ranked_by {
+        Some(name) => groups
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, g)| g.scores.means.get(name).map(|mean| (idx, *mean)))
+            .collect(),
+        None => vec![],
+    };
+    score_ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    ExperimentStats {
+        experiment_uuid: log.experiment_uuid.clone(),
+        inference_count: log.inferences.len(),
+        error_count,
+        ratings: overall.finish(),
+        performance: PerformanceStats::from_metrics(&metrics),
+        scores: overall_scores,
+        groups,
+        ranked_by,
+        score_ranking: score_ranking.into_iter().map(|(idx, _)| idx).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn percentile_interpolates() {
+        let sorted = [1.0, 2.0, 3.0, 4.0];
+        assert!(close(percentile(&sorted, 0.0), 1.0));
+        assert!(close(percentile(&sorted, 0.5), 2.5));
+        assert!(close(percentile(&sorted, 0.95), 3.85));
+        assert!(close(percentile(&sorted, 1.0), 4.0));
+        assert!(close(percentile(&[7.0], 0.95), 7.0));
+    }
+
+    #[test]
+    fn stats_of_values() {
+        assert!(MetricStats::from_values(&[]).is_none());
+
+        let single = MetricStats::from_values(&[3.0]).unwrap();
+        assert_eq!((single.count, single.mean, single.median), (1, 3.0, 3.0));
+        assert!(single.std_dev.is_none() && single.ci95_low.is_none());
+
+        // Unsorted on purpose
+        let stats = MetricStats::from_values(&[9.0, 2.0, 4.0, 4.0, 5.0, 4.0, 7.0, 5.0]).unwrap();
+        assert_eq!(stats.count, 8);
+        assert!(close(stats.mean, 5.0));
+        assert!(close(stats.median, 4.5));
+        assert!(close(stats.p95, 8.3));
+        let sd = (32.0f64 / 7.0).sqrt();
+        assert!(close(stats.std_dev.unwrap(), sd));
+        // Student's t with 7 degrees of freedom
+        let margin = 2.365 * sd / 8f64.sqrt();
+        assert!(close(stats.ci95_low.unwrap(), 5.0 - margin));
+        assert!(close(stats.ci95_high.unwrap(), 5.0 + margin));
+
+        // Identical values have no spread
+        let ties = MetricStats::from_values(&[2.0, 2.0]).unwrap();
+        assert_eq!(ties.std_dev, Some(0.0));
+        assert_eq!((ties.ci95_low, ties.ci95_high), (Some(2.0), Some(2.0)));
+    }
+}
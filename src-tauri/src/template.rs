@@ -0,0 +1,279 @@
+/*
+Prompt templates.
+
+Prompt texts may use a minijinja (Jinja2-like) template language:
+
+    {% include "code-review-assistant" %}
+    {% for example in examples %}Q: {{ example.q }} A: {{ example.a }}{% endfor %}
+    {% if tone %}Use a {{ tone }} tone.{% endif %}
+
+Other prompts can be included by their slug. Variables left out of a
+dataset row can still be tested (`{% if tone %}`), but printing one is an
+error. Legacy [placeholders] of the prompt itself, outside of its tags, are
+printed like variables; brackets in included prompts and in the values
+are kept as-is.
+*/
+use minijinja::{Environment, ErrorKind, UndefinedBehavior};
+use serde_json::{Map, Value};
+use sqlx::{Pool, Sqlite};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::dataset::{fill_placeholders, find_placeholders, placeholder_regex, placeholder_text};
+use crate::Error;
+
+// Holds the values of the [placeholders] while the template is rendered
+const PLACEHOLDERS: &str = "__placeholders";
+
+/// Returns true if the text uses any template syntax.
+/// Prompts without it are sent as-is, so pasted code with brackets
+/// or braces is not mangled.
+pub fn has_template_syntax(text: &str) -> bool {
+    text.contains("{{") || text.contains("{%") || text.contains("{#")
+}
+
+/// Returns the text of every stored prompt, by slug,
+/// so they can be used in `{% include "slug" %}` tags.
+pub async fn prompt_includes(pool: &Pool<Sqlite>) -> Result<HashMap<String, String>, Error> {
+    let rows: Vec<(String, String)> = sqlx::query_as("SELECT slug, prompt FROM prompts")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.into_iter().collect())
+}
+
+/// Renders a prompt template.
+///
+/// * `includes` - prompt texts, by slug, that can be used in `{% include %}` tags
+/// * `variables` - values for template variables and [placeholders]
+pub fn render_prompt(
+    template: &str,
+    variables: Option<&Map<String, Value>>,
+    includes: &HashMap<String, String>,
+) -> Result<String, Error> {
+    if has_template_syntax(template) {
+        return render_template(template, variables, includes);
+    }
+    match variables {
+        Some(variables) => fill_placeholders(template, variables),
+        None => Ok(template.to_string()),
+    }
+}
+
+/// Splits a template into its text and its `{{ }}`, `{% %}` and `{# #}` tags.
+/// Each part comes with true if it's a tag.
+pub(crate) fn template_parts(source: &str) -> Vec<(bool, &str)> {
+    let mut parts = vec![];
+    let mut rest = source;
+    loop {
+        let tag = rest.match_indices('{').find_map(|(idx, _)| {
+            let close = match rest[idx + 1..].chars().next() {
+                Some('{') => "}}",
+                Some('%') => "%}",
+                Some('#') => "#}",
+                _ => return None,
+            };
+            Some((idx, close))
+        });
+        let Some((start, close)) = tag else {
+            if !rest.is_empty() {
+                parts.push((false, rest));
+            }
+            return parts;
+        };
+
+        if start > 0 {
+            parts.push((false, &rest[..start]));
+        }
+        let end = rest[start + 2..]
+            .find(close)
+            .map_or(rest.len(), |idx| start + 2 + idx + close.len());
+        parts.push((true, &rest[start..end]));
+        rest = &rest[end..];
+    }
+}
+
+/// Turns the [placeholders] outside of the tags into expressions, so their
+/// values are printed once, and never read as template syntax.
+fn print_placeholders(source: &str) -> String {
+    let expression = format!("{{{{ {}[\"${{1}}\"] }}}}", PLACEHOLDERS);
+    template_parts(source)
+        .into_iter()
+        .map(|(is_tag, text)| match is_tag {
+            true => Cow::Borrowed(text),
+            false => placeholder_regex().replace_all(text, expression.as_str()),
+        })
+        .collect()
+}
+
+fn render_template(
+    template: &str,
+    variables: Option<&Map<String, Value>>,
+    includes: &HashMap<String, String>,
+) -> Result<String, Error> {
+    let mut env = Environment::new();
+    // Undefined values can be tested, but not printed or iterated
+    env.set_undefined_behavior(UndefinedBehavior::SemiStrict);
+
+    let includes = includes.clone();
+    env.set_loader(move |name| Ok(includes.get(name).cloned()));
+
+    // Without variables, [placeholders] are left as they are
+    let empty = Map::new();
+    let (source, context) = match variables {
+        Some(variables) => {
+            let missing: Vec<String> = find_placeholders(template)
+                .into_iter()
+                .filter(|name| !variables.contains_key(name))
+                .collect();
+            if !missing.is_empty() {
+                return Err(Error::StringError(format!(
+                    "No value for placeholder(s) [{}] in prompt.",
+                    missing.join("], [")
+                )));
+            }
+            let values: Map<String, Value> = variables
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::String(placeholder_text(value))))
+                .collect();
+            let mut context = variables.clone();
+            context.insert(PLACEHOLDERS.to_string(), Value::Object(values));
+            (print_placeholders(template), context)
+        }
+        None => (template.to_string(), empty.clone()),
+    };
+    let variables = variables.unwrap_or(&empty);
+
+    let tmpl = env
+        .template_from_str(&source)
+        .map_err(|err| template_error(&err, variables, &[]))?;
+
+    tmpl.render(&context).map_err(|err| {
+        let err = innermost_error(&err);
+        if err.kind() != ErrorKind::UndefinedError {
+            return template_error(err, variables, &[]);
+        }
+
+        // Name every variable the row doesn't supply, not only the one that failed
+        let mut declared = tmpl.undeclared_variables(false);
+        if let Some(included) = err.name().and_then(|name| env.get_template(name).ok()) {
+            declared.extend(included.undeclared_variables(false));
+        }
+        let mut missing: Vec<String> = declared
+            .into_iter()
+            .filter(|name| !context.contains_key(name))
+            .collect();
+        missing.sort();
+        template_error(err, variables, &missing)
+    })
+}
+
+/// Errors in included prompts are wrapped in the error of the include tag.
+fn innermost_error(err: &minijinja::Error) -> &minijinja::Error {
+    match std::error::Error::source(err).and_then(|source| source.downcast_ref()) {
+        Some(inner) => innermost_error(inner),
+        None => err,
+    }
+}
+
+fn template_error(
+    err: &minijinja::Error,
+    variables: &Map<String, Value>,
+    missing: &[String],
+) -> Error {
+    let location = match (err.name(), err.line()) {
+        (Some(name), Some(line)) if name != "<string>" => {
+            format!(" (in '{}', line {})", name, line)
+        }
+        (_, Some(line)) => format!(" (line {})", line),
+        _ => String::new(),
+    };
+
+    let message = match err.kind() {
+        ErrorKind::UndefinedError => {
+            let mut known: Vec<&String> = variables.keys().collect();
+            known.sort();
+            let missing = if missing.is_empty() {
+                String::new()
+            } else {
+                format!(" Not supplied: {}.", missing.join(", "))
+            };
+            format!(
+                "Undefined value in template{}.{} Available variables: {:?}",
+                location, missing, known
+            )
+        }
+        ErrorKind::TemplateNotFound => format!(
+            "Included prompt not found{}: {}",
+            location,
+            err.detail().unwrap_or_default()
+        ),
+        _ => format!(
+            "Template error{}: {}",
+            location,
+            err.detail().unwrap_or(&err.kind().to_string())
+        ),
+    };
+
+    Error::StringError(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(json: &str) -> Map<String, Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn render(template: &str, variables: &Map<String, Value>) -> Result<String, Error> {
+        let includes = HashMap::from([("inc".to_string(), "Keep [brackets]".to_string())]);
+        render_prompt(template, Some(variables), &includes)
+    }
+
+    #[test]
+    fn bracketed_values_are_not_filled_again() {
+        let variables = row(r#"{"code": "a[index]", "topic": "[topic]"}"#);
+        assert_eq!(
+            render("{{ code }} on [topic]", &variables).unwrap(),
+            "a[index] on [topic]"
+        );
+        assert_eq!(
+            render("[code] [topic]", &variables).unwrap(),
+            "a[index] [topic]"
+        );
+    }
+
+    #[test]
+    fn values_are_not_read_as_template_syntax() {
+        let variables = row(r#"{"input": "{{ secret }}", "count": 2}"#);
+        assert_eq!(
+            render("{% if count %}[input] x[count]{% endif %}", &variables).unwrap(),
+            "{{ secret }} x2"
+        );
+    }
+
+    #[test]
+    fn included_brackets_and_subscripts_are_kept() {
+        let variables = row(r#"{"items": ["first"]}"#);
+        assert_eq!(
+            render("{{ items[0] }}: {% include \"inc\" %}", &variables).unwrap(),
+            "first: Keep [brackets]"
+        );
+    }
+
+    #[test]
+    fn optional_variables_can_be_tested() {
+        let variables = row(r#"{"topic": "rust"}"#);
+        assert_eq!(
+            render(
+                "[topic]{% if tone %} in a {{ tone }} tone{% endif %}",
+                &variables
+            )
+            .unwrap(),
+            "rust"
+        );
+        assert!(render("{{ tone }}", &variables).is_err());
+        assert!(render("{{ topic }} [level]", &variables).is_err());
+    }
+}
@@ -87,7 +87,8 @@ export const formValuesAtom = atom(
         experiment_uuid: "",
         models: [],
         prompts: [""],
-        system_prompt: config.system_prompt,
+        prompt_uuids: [null],
+        system_prompts: [config.system_prompt],
         temperatureList: [config.default_options.temperature],
         repeatPenaltyList: [config.default_options.repeat_penalty],
         topKList: [config.default_options.top_k],
@@ -97,7 +98,14 @@ export const formValuesAtom = atom(
         mirostatList: [config.default_options.mirostat],
         mirostatTauList: [config.default_options.mirostat_tau],
         mirostatEtaList: [config.default_options.mirostat_eta],
+        thinkList: [null],
         generations: 1,
+        dataset_path: "",
+        dataset_rows: [],
+        judge_model: "",
+        judge_rubric: "",
+        evaluators: [],
+        scripts: [],
       };
     }
 
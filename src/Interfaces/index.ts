@@ -6,6 +6,7 @@ export type TParamIteration = {
   model: string;
   system_prompt: string;
   prompt: string;
+  prompt_uuid?: string | null; // the library prompt it was taken from, if any
   temperature: number;
   repeat_penalty: number;
   top_k: number;
@@ -15,15 +16,20 @@ export type TParamIteration = {
   mirostat: number;
   mirostat_tau: number;
   mirostat_eta: number;
+  think?: boolean | null; // think mode of reasoning models, null for the model's default
   generation: number;
+  // set when the grid is expanded over the rows of a dataset
+  dataset_row?: number;
+  variables?: Record<string, unknown>;
 };
 
 // Represents the fields displayed in the inference form
 export type TFormValues = {
   experiment_uuid: string;
   models: string[];
-  system_prompt: string;
+  system_prompts: string[];
   prompts: string[];
+  prompt_uuids: (string | null)[]; // library prompt of each prompt, if any
   temperatureList: number[];
   repeatPenaltyList: number[];
   topKList: number[];
@@ -33,7 +39,14 @@ export type TFormValues = {
   mirostatList: number[];
   mirostatTauList: number[];
   mirostatEtaList: number[];
+  thinkList: (boolean | null)[];
   generations: number;
+  dataset_path: string;
+  dataset_rows: Record<string, unknown>[];
+  judge_model: string;
+  judge_rubric: string;
+  evaluators: string[]; // names of the evaluators that score each inference
+  scripts: string[]; // names of the scripts run on each inference, in order
 };
 
 // Interface for the default configuration options
@@ -59,9 +72,12 @@ export interface ErrorResponse {
 }
 
 export interface IResponsePayload {
+  inference_id: string; // id the inference was logged with
   model: string;
   created_at: string;
   response: string;
+  thinking?: string | null; // reasoning of reasoning models, apart from the response
+  reasoning_tokens: number | null; // estimated share of eval_count
   done: boolean;
   context: number[];
   total_duration: number;
@@ -81,11 +97,450 @@ export interface IExperimentFile {
   contents: string;
 }
 
+export type TExperimentStatus = "completed" | "partial" | "failed";
+
+// grid: a grid search; regression: the run of a golden suite
+export type TRunType = "grid" | "regression";
+
+export interface IExperimentSummary {
+  name: string;
+  experiment_uuid: string;
+  created: string;
+  date_created: number; // Unix timestamp
+  is_favorite: boolean;
+  description: string; // markdown
+  run_type: TRunType;
+  tags: string[];
+  inference_count: number;
+  error_count: number;
+  models: string[];
+  status: TExperimentStatus;
+}
+
+export interface IExperimentQuery {
+  page?: number; // zero based
+  page_size?: number;
+  sort_by?: "date_created" | "name" | "inference_count";
+  sort_dir?: "asc" | "desc";
+  date_from?: number; // Unix timestamp
+  date_to?: number; // Unix timestamp
+  model?: string;
+  favorite?: boolean;
+  run_type?: TRunType;
+  tags?: string[];
+  text?: string;
+}
+
+export interface IExperimentPage {
+  items: IExperimentSummary[];
+  total: number;
+  page: number;
+  page_size: number;
+}
+
+export interface IRatingInput {
+  thumb?: 1 | -1;
+  rating?: number; // 1 to 5
+  rubric?: Record<string, number>;
+  note?: string;
+}
+
+export interface IInferenceRating {
+  experiment_uuid: string;
+  inference_id: string;
+  thumb: 1 | -1 | null;
+  rating: number | null;
+  rubric: Record<string, number>;
+  note: string;
+  last_modified: number; // Unix timestamp
+}
+
+export interface IParamCombination {
+  model: string;
+  system_prompt: string;
+  temperature: number;
+  repeat_penalty: number;
+  top_k: number;
+  top_p: number;
+  repeat_last_n: number;
+  tfs_z: number;
+  mirostat: number;
+  mirostat_tau: number;
+  mirostat_eta: number;
+  think: boolean | null;
+}
+
+export interface IRatingStats {
+  rated_count: number;
+  thumbs_up: number;
+  thumbs_down: number;
+  avg_rating: number | null;
+  rubric: Record<string, number>; // average score per criterion
+  note_count: number;
+}
+
+export interface IMetricStats {
+  count: number;
+  mean: number;
+  median: number;
+  p95: number;
+  std_dev: number | null; // null with a single value
+  ci95_low: number | null;
+  ci95_high: number | null;
+}
+
+// Times are in seconds
+export interface IPerformanceStats {
+  prompt_tokens_per_second: IMetricStats | null;
+  generation_tokens_per_second: IMetricStats | null;
+  time_to_first_token: IMetricStats | null;
+  load_time: IMetricStats | null;
+  total_time: IMetricStats | null;
+  reasoning_tokens: IMetricStats | null;
+  answer_tokens: IMetricStats | null;
+}
+
+export interface ICellDiversity {
+  combination: IParamCombination;
+  prompt: string;
+  system_prompt: string;
+  dataset_row: number | null;
+  sample_count: number;
+  distinct_count: number; // different responses, ignoring surrounding whitespace
+  // means over every pair of responses, from 0 to 1
+  jaccard_similarity: number;
+  edit_similarity: number;
+  embedding_similarity: number | null; // with an embedding model only
+  diversity: number; // 0 = identical responses
+}
+
+export interface ICombinationDiversity {
+  combination: IParamCombination;
+  cell_count: number;
+  diversity: number; // mean of its cells
+}
+
+export interface IDiversityReport {
+  experiment_uuid: string;
+  embedding_model: string | null;
+  cells: ICellDiversity[]; // cells with at least two successful responses
+  combinations: ICombinationDiversity[];
+}
+
+export interface IScoreStats {
+  scored_count: number;
+  means: Record<string, number>; // average score per name
+  mean_score: number | null;
+  pass_rate: number | null;
+}
+
+export interface IGroupSummary {
+  combination: IParamCombination;
+  inference_count: number;
+  error_count: number;
+  ratings: IRatingStats;
+  performance: IPerformanceStats;
+  scores: IScoreStats;
+}
+
+export interface IExperimentStats {
+  experiment_uuid: string;
+  inference_count: number;
+  error_count: number;
+  ratings: IRatingStats;
+  performance: IPerformanceStats;
+  scores: IScoreStats;
+  groups: IGroupSummary[];
+  ranked_by: string | null; // score name the groups are ranked by
+  score_ranking: number[]; // indices into groups, best mean ranked_by first
+}
+
+// Without an expected value, assertions use the dataset row's "expected" column
+export type TAssertion =
+  | { type: "exact_match"; expected?: string }
+  | { type: "contains"; expected?: string }
+  | { type: "regex"; pattern: string }
+  | { type: "numeric"; expected?: number; tolerance?: number }
+  | { type: "bleu"; expected?: string }
+  | { type: "rouge_l"; expected?: string }
+  | { type: "edit_distance"; expected?: string };
+
+export interface IJudgeInput {
+  model: string;
+  rubric: string;
+  min_score?: number; // defaults to 1
+  max_score?: number; // defaults to 10
+  auto?: boolean; // judge each inference after it's generated, defaults to true
+}
+
+export interface IExperimentJudge extends Required<IJudgeInput> {
+  experiment_uuid: string;
+  last_modified: number; // Unix timestamp
+}
+
+export interface IEvaluatorInput {
+  name: string; // also the name of its scores
+  command: string;
+  args: string[];
+  working_dir: string; // empty for the app's working directory
+  timeout: number; // in seconds
+  enabled: boolean;
+}
+
+export interface IEvaluator extends IEvaluatorInput {
+  date_created: number; // Unix timestamp
+  last_modified: number; // Unix timestamp
+}
+
+export interface IScriptInput {
+  name: string;
+  description: string;
+  source: string; // Rhai, defining pre_inference and/or post_inference
+}
+
+export interface IScript extends IScriptInput {
+  date_created: number; // Unix timestamp
+  last_modified: number; // Unix timestamp
+}
+
+// Minimum similarities (0 to 1) of a rerun to its baseline; null ones
+// aren't checked
+export interface IThresholds {
+  min_edit_similarity: number | null;
+  min_jaccard_similarity: number | null;
+  embedding_model: string | null;
+  min_embedding_similarity: number | null;
+}
+
+export interface IGoldenSuiteInput {
+  name: string;
+  description: string;
+  thresholds: IThresholds;
+}
+
+export interface IGoldenSuite extends IGoldenSuiteInput {
+  baseline_count: number;
+  date_created: number; // Unix timestamp
+  last_modified: number; // Unix timestamp
+}
+
+export interface IGoldenBaseline {
+  id: number;
+  suite_name: string;
+  source_experiment_uuid: string;
+  source_inference_id: string;
+  parameters: TParamIteration;
+  config: IDefaultConfigs;
+  rendered_prompt: string;
+  response: string;
+  scripts: string[]; // names of the scripts whose post-hooks ran
+  date_created: number; // Unix timestamp
+}
+
+export interface IBaselineResult {
+  baseline_id: number;
+  model: string;
+  prompt: string;
+  baseline_response: string;
+  inference_id: string | null; // null when the rerun failed
+  response: string | null;
+  error: string | null;
+  edit_similarity: number | null;
+  jaccard_similarity: number | null;
+  embedding_similarity: number | null;
+  drift: number | null; // 0 when the responses are the same, up to 1
+  passed: boolean;
+  diff: IDiffLine[]; // from the baseline response, empty when equal
+}
+
+export interface IRegressionRun {
+  experiment_uuid: string; // the experiment with the reruns
+  suite_name: string;
+  model: string | null; // set when the baselines ran on another model
+  ollama_version: string | null;
+  thresholds: IThresholds;
+  baseline_count: number;
+  passed_count: number;
+  results: IBaselineResult[]; // failed and most drifted first
+  date_created: number; // Unix timestamp
+}
+
+export interface IRunResult {
+  identical: boolean; // same response and reasoning as the logged inference
+  response: string | null;
+  error: string | null;
+  diff: IDiffLine[]; // from the logged response, empty when equal
+}
+
+export interface IReproducibilityCheck {
+  id: number;
+  experiment_uuid: string;
+  inference_id: string;
+  model: string;
+  model_digest: string | null; // null when the server didn't report it
+  ollama_version: string | null;
+  concurrent: boolean;
+  run_count: number;
+  identical_count: number;
+  runs: IRunResult[];
+  date_created: number; // Unix timestamp
+}
+
+export type TThinkMode = "default" | "on" | "off";
+
+// Inline, or by the slug of a prompt in the prompt archive
+export type TSpecPrompt = string | { slug: string };
+
+export interface IServerProfile {
+  server_url?: string | null; // unset ones are left to the app's settings
+  request_timeout?: number | null;
+  default_options: { [key: string]: any };
+}
+
+export interface IExperimentSpec {
+  version: number;
+  server: IServerProfile;
+  models: string[];
+  prompts: TSpecPrompt[]; // loaded specs only have inline prompts
+  system_prompts: string[];
+  dataset?: string | null;
+  temperature: number[];
+  repeat_penalty: number[];
+  top_k: number[];
+  top_p: number[];
+  repeat_last_n: number[];
+  tfs_z: number[];
+  mirostat: number[];
+  mirostat_tau: number[];
+  mirostat_eta: number[];
+  think: TThinkMode[];
+  generations: number;
+  judge?: IJudgeInput | null;
+  scripts: string[];
+  evaluators: string[];
+  concurrency: number;
+}
+
+// A spec loaded into the form, with the library prompt of each prompt
+export interface ILoadedExperimentSpec extends IExperimentSpec {
+  prompt_uuids: (string | null)[];
+}
+
+export interface IInferenceScore {
+  experiment_uuid: string;
+  inference_id: string;
+  name: string;
+  source: "assertion" | "judge" | "evaluator" | "hook";
+  score: number | null; // 0 to 1
+  passed: boolean | null;
+  detail: string;
+  date_created: number; // Unix timestamp
+}
+
+export type TExportFormat = "csv" | "jsonl" | "parquet";
+
+export type TReportFormat = "html" | "markdown";
+
+export type TChartKind = "line" | "scatter" | "bar" | "heatmap";
+
+export type TChartFormat = "svg" | "png";
+
+export interface IChartSpec {
+  kind: TChartKind;
+  x: string; // Any column of the experiment export
+  y: string;
+  series?: string;
+  value?: string; // Heatmaps: the field shown as the cell color
+  title?: string;
+  width?: number;
+  height?: number;
+}
+
+export interface IExperimentImportReport {
+  experiment_uuid: string;
+  name: string;
+  status: "created" | "renamed" | "overwritten" | "skipped";
+  inference_count: number;
+  migrated_from: string; // log_version of the imported file
+}
+
+export type TVoteWinner = "left" | "right" | "tie";
+
+export interface IComparisonSession {
+  uuid: string;
+  experiment_uuid: string;
+  date_created: number; // Unix timestamp
+  vote_count: number;
+}
+
+// Model and parameters of each side are hidden by the backend
+export interface IBlindPair {
+  session_uuid: string;
+  system_prompt: string;
+  prompt: string;
+  left_id: string;
+  left_response: string;
+  right_id: string;
+  right_response: string;
+  remaining: number;
+}
+
+export interface ILeaderboardEntry {
+  combination: IParamCombination;
+  elo: number;
+  bradley_terry: number; // on the Elo scale
+  wins: number;
+  losses: number;
+  ties: number;
+}
+
 export interface IPrompt {
   uuid: string;
   name: string;
   slug: string;
   prompt: string;
+  folder?: string; // "/" separated path
+  tags?: string[];
   date_created?: number; // Unix timestamp
   last_modified?: number; // Unix timestamp
 }
+
+export interface IPromptSearch {
+  query?: string;
+  tags?: string[];
+  folder?: string;
+}
+
+export interface ITagCount {
+  tag: string;
+  count: number;
+}
+
+export interface IDataset {
+  path: string;
+  columns: string[];
+  rows: Record<string, unknown>[];
+}
+
+export interface IPromptVersion {
+  id: number;
+  prompt_uuid: string;
+  version: number;
+  prompt: string;
+  date_created: number; // Unix timestamp
+}
+
+export interface IDiffLine {
+  tag: "equal" | "insert" | "delete";
+  text: string;
+}
+
+export type TConflictPolicy = "skip" | "overwrite" | "rename";
+
+export interface IPromptImportReport {
+  created: string[];
+  updated: string[];
+  renamed: string[];
+  skipped: string[];
+  errors: string[];
+}
@@ -1,8 +1,11 @@
 import { PromptArchiveDialog } from "@/components/Prompt/prompt-archive-dialog";
 import { ExperimentSelector } from "@/components/Selectors/ExperimentSelector";
+import { EvaluatorsDialog } from "@/components/evaluators-dialog";
 import FormGridParams from "@/components/form-grid-params";
+import { GoldenSuitesDialog } from "@/components/golden-suites-dialog";
 import { ModeToggle } from "@/components/mode-toggle";
 import GridResultsPane from "@/components/results/grid-results-pane";
+import { ScriptsDialog } from "@/components/scripts-dialog";
 import { SettingsDialog } from "@/components/settings-dialog";
 
 function Layout() {
@@ -19,6 +22,9 @@ function Layout() {
           <PromptArchiveDialog />
           <ModeToggle />
           <ExperimentSelector />
+          <EvaluatorsDialog />
+          <ScriptsDialog />
+          <GoldenSuitesDialog />
           <SettingsDialog />
         </nav>
       </header>
@@ -25,6 +25,13 @@ export function PromptList(props: IProps) {
     setFormValues({
       ...formValues,
       prompts: [...formValues.prompts, prompt.prompt],
+      // prompts typed in the form have no library uuid
+      prompt_uuids: [
+        ...formValues.prompts.map(
+          (_, idx) => formValues.prompt_uuids?.[idx] ?? null,
+        ),
+        prompt.uuid,
+      ],
     });
 
     toast({
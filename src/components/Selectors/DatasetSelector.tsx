@@ -0,0 +1,93 @@
+import { load_dataset } from "@/components/queries";
+import { Button } from "@/components/ui/button";
+import {
+  FormDescription,
+  FormField,
+  FormItem,
+  FormLabel,
+  FormMessage,
+} from "@/components/ui/form";
+import { toast } from "@/components/ui/use-toast";
+import { CrossCircledIcon, FileIcon } from "@radix-ui/react-icons";
+import { open } from "@tauri-apps/api/dialog";
+
+interface IProps {
+  form: any;
+}
+
+function DatasetSelector({ form }: IProps) {
+  const datasetPath: string = form.watch("dataset_path");
+  const datasetRows: Record<string, unknown>[] = form.watch("dataset_rows");
+
+  const handleSelectDataset = async () => {
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: "Dataset", extensions: ["csv", "jsonl", "ndjson"] }],
+    });
+    if (!selected || Array.isArray(selected)) return;
+
+    try {
+      const dataset = await load_dataset(selected);
+      form.setValue("dataset_path", dataset.path);
+      form.setValue("dataset_rows", dataset.rows);
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not load dataset.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  const handleClearDataset = () => {
+    form.setValue("dataset_path", "");
+    form.setValue("dataset_rows", []);
+  };
+
+  return (
+    <FormField
+      control={form.control}
+      name="dataset_path"
+      render={() => (
+        <FormItem>
+          <FormLabel className="flex flex-row items-center justify-between font-bold">
+            Dataset
+            <div>
+              <Button
+                variant="ghost"
+                size="icon"
+                type="button"
+                onClick={handleSelectDataset}
+              >
+                <FileIcon className="h-4 w-4" />
+              </Button>
+              {datasetPath && (
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  type="button"
+                  onClick={handleClearDataset}
+                >
+                  <CrossCircledIcon className="h-4 w-4 text-red-500" />
+                </Button>
+              )}
+            </div>
+          </FormLabel>
+          {datasetPath && (
+            <div className="break-all font-mono text-xs">
+              {datasetPath} ({datasetRows.length} rows)
+            </div>
+          )}
+          <FormDescription>
+            Optional. A CSV or JSONL file where each row supplies values for
+            the [placeholders] in your prompts.
+          </FormDescription>
+          <FormMessage />
+        </FormItem>
+      )}
+    />
+  );
+}
+
+export default DatasetSelector;
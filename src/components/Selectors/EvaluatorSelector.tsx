@@ -0,0 +1,73 @@
+import { IEvaluator } from "@/Interfaces";
+import { Checkbox } from "@/components/ui/checkbox";
+import {
+  FormControl,
+  FormDescription,
+  FormField,
+  FormItem,
+  FormLabel,
+  FormMessage,
+} from "@/components/ui/form";
+import { useQuery } from "@tanstack/react-query";
+import { get_evaluators } from "../queries";
+
+interface IProps {
+  form: any;
+}
+
+function EvaluatorSelector({ form }: IProps) {
+  // Same query as the evaluators dialog, so saved evaluators show up here
+  const query = useQuery<IEvaluator[]>({
+    queryKey: ["get_evaluators"],
+    queryFn: get_evaluators,
+  });
+  // Disabled evaluators don't run, even when attached
+  const evaluators = query.data?.filter((evaluator) => evaluator.enabled);
+
+  // Hide the field until there's something to select
+  if (!evaluators?.length) {
+    return null;
+  }
+
+  return (
+    <FormField
+      control={form.control}
+      name="evaluators"
+      render={({ field }) => (
+        <FormItem>
+          <FormLabel className="font-bold">Evaluators</FormLabel>
+          {evaluators.map((evaluator) => (
+            <div
+              key={evaluator.name}
+              className="flex flex-row items-start space-x-3"
+            >
+              <FormControl>
+                <Checkbox
+                  checked={field.value?.includes(evaluator.name)}
+                  onCheckedChange={(checked: boolean) => {
+                    if (checked) {
+                      field.onChange([...field.value, evaluator.name]);
+                    } else {
+                      field.onChange(
+                        field.value?.filter(
+                          (value: string) => value !== evaluator.name,
+                        ),
+                      );
+                    }
+                  }}
+                />
+              </FormControl>
+              <div className="font-mono text-sm">{evaluator.name}</div>
+            </div>
+          ))}
+          <FormDescription>
+            Optional. Commands that score each response.
+          </FormDescription>
+          <FormMessage />
+        </FormItem>
+      )}
+    />
+  );
+}
+
+export default EvaluatorSelector;
@@ -1,9 +1,24 @@
-import { IExperimentFile, TFormValues } from "@/Interfaces";
+import {
+  IExperimentPage,
+  IExperimentQuery,
+  IExperimentSummary,
+  TFormValues,
+} from "@/Interfaces";
 import { Button } from "@/components/ui/button";
 
 import { formValuesAtom } from "@/Atoms";
+import { ChartDialog } from "@/components/chart-dialog";
+import { ComparisonDialog } from "@/components/comparison-dialog";
 import { ExperimentDataDialog } from "@/components/experiment-data-dialog";
-import { get_experiments } from "@/components/queries";
+import { ExperimentEditDialog } from "@/components/experiment-edit-dialog";
+import {
+  export_experiment,
+  generate_report,
+  get_experiment,
+  import_experiment,
+  list_experiments,
+  set_experiment_favorite,
+} from "@/components/queries";
 import { useConfirm } from "@/components/ui/alert-dialog-provider";
 import {
   Sheet,
@@ -15,15 +30,28 @@ import {
   SheetTitle,
   SheetTrigger,
 } from "@/components/ui/sheet";
+import { Input } from "@/components/ui/input";
 import { toast } from "@/components/ui/use-toast";
 import {
+  ChevronLeftIcon,
+  ChevronRightIcon,
   CrossCircledIcon,
   DownloadIcon,
   FileTextIcon,
+  ReaderIcon,
+  StarFilledIcon,
+  StarIcon,
+  TableIcon,
+  TargetIcon,
   UpdateIcon,
+  UploadIcon,
 } from "@radix-ui/react-icons";
-import { useQuery, useQueryClient } from "@tanstack/react-query";
-import { save } from "@tauri-apps/api/dialog";
+import {
+  keepPreviousData,
+  useQuery,
+  useQueryClient,
+} from "@tanstack/react-query";
+import { open, save } from "@tauri-apps/api/dialog";
 import { writeTextFile } from "@tauri-apps/api/fs";
 import { invoke } from "@tauri-apps/api/tauri";
 import { saveAs } from "file-saver";
@@ -36,8 +64,9 @@ function processExperimentData(logContent: string): TFormValues {
   const formValues: TFormValues = {
     experiment_uuid: "",
     models: [],
-    system_prompt: logData.config.system_prompt || "",
+    system_prompts: [],
     prompts: [],
+    prompt_uuids: [],
     temperatureList: [],
     repeatPenaltyList: [],
     topKList: [],
@@ -47,16 +76,30 @@ function processExperimentData(logContent: string): TFormValues {
     mirostatList: [],
     mirostatTauList: [],
     mirostatEtaList: [],
+    thinkList: [],
     generations: 0,
+    dataset_path: "",
+    dataset_rows: [],
+    judge_model: "",
+    judge_rubric: "",
+    evaluators: [],
+    scripts: [],
   };
 
   const uniquePrompts = new Set<string>();
+  // library uuid of each prompt, if it was taken from the library
+  const promptUuids = new Map<string, string | null>();
+  const uniqueSystemPrompts = new Set<string>();
   const uniqueModels = new Set<string>();
   const parameterSets = new Set<string>();
 
   logData.inferences.forEach((inference: any) => {
     const params = inference.parameters;
     uniquePrompts.add(params.prompt);
+    if (!promptUuids.has(params.prompt)) {
+      promptUuids.set(params.prompt, params.prompt_uuid ?? null);
+    }
+    uniqueSystemPrompts.add(params.system_prompt);
     uniqueModels.add(params.model);
 
     const roundedParams = {
@@ -69,6 +112,7 @@ function processExperimentData(logContent: string): TFormValues {
       mirostat: params.mirostat,
       mirostat_tau: Number(params.mirostat_tau.toFixed(2)),
       mirostat_eta: Number(params.mirostat_eta.toFixed(2)),
+      think: params.think ?? null,
     };
 
     const paramSet = JSON.stringify(roundedParams);
@@ -109,16 +153,27 @@ function processExperimentData(logContent: string): TFormValues {
         formValues.mirostatEtaList,
         roundedParams.mirostat_eta,
       );
+      if (!formValues.thinkList.includes(roundedParams.think)) {
+        formValues.thinkList.push(roundedParams.think);
+      }
     }
   });
 
   formValues.models = Array.from(uniqueModels);
   formValues.prompts = Array.from(uniquePrompts);
+  formValues.prompt_uuids = formValues.prompts.map(
+    (prompt) => promptUuids.get(prompt) ?? null,
+  );
+  formValues.system_prompts =
+    uniqueSystemPrompts.size > 0
+      ? Array.from(uniqueSystemPrompts)
+      : [logData.config.system_prompt || ""];
   formValues.generations = Math.floor(
     logData.inferences.length /
       parameterSets.size /
       uniqueModels.size /
-      uniquePrompts.size,
+      uniquePrompts.size /
+      formValues.system_prompts.length,
   );
 
   console.log(formValues);
@@ -151,15 +206,32 @@ export function ExperimentSelector() {
   const [sheetOpen, setSheetOpen] = useState(false);
   const confirm = useConfirm();
 
-  const query = useQuery<IExperimentFile[]>({
-    queryKey: ["get_experiments"],
-    queryFn: (): Promise<IExperimentFile[]> => get_experiments(),
+  const [filters, setFilters] = useState<IExperimentQuery>({
+    page: 0,
+    page_size: 25,
+    sort_by: "date_created",
+    sort_dir: "desc",
+  });
+
+  const query = useQuery<IExperimentPage>({
+    queryKey: ["list_experiments", filters],
+    queryFn: (): Promise<IExperimentPage> => list_experiments(filters),
+    placeholderData: keepPreviousData,
     staleTime: 0,
     // cacheTime: 0,
   });
 
-  function cloneExperiment(data: string) {
-    const experimentData = processExperimentData(data);
+  const pageCount = query.data
+    ? Math.max(1, Math.ceil(query.data.total / query.data.page_size))
+    : 1;
+
+  // Any filter change goes back to the first page
+  const updateFilters = (changes: IExperimentQuery) =>
+    setFilters((prev) => ({ ...prev, ...changes, page: 0 }));
+
+  async function cloneExperiment(uuid: string) {
+    const experiment = await get_experiment(uuid);
+    const experimentData = processExperimentData(experiment.contents);
     setFormValues(experimentData);
     toast({
       title:
@@ -190,12 +262,111 @@ export function ExperimentSelector() {
       });
 
       queryClient.refetchQueries({
-        queryKey: ["get_experiments"],
+        queryKey: ["list_experiments"],
       });
       // setSheetOpen(true);
     }
   }
 
+  async function toggleFavorite(exp: IExperimentSummary) {
+    await set_experiment_favorite(exp.experiment_uuid, !exp.is_favorite);
+    queryClient.refetchQueries({
+      queryKey: ["list_experiments"],
+    });
+  }
+
+  // Flat one-row-per-inference export, for pandas, DuckDB, etc.
+  async function exportExperiment(exp: IExperimentSummary) {
+    const path = await save({
+      defaultPath: exp.name + ".csv",
+      filters: [
+        { name: "CSV", extensions: ["csv"] },
+        { name: "JSON Lines", extensions: ["jsonl"] },
+        { name: "Parquet", extensions: ["parquet"] },
+      ],
+    });
+    if (!path) return;
+
+    try {
+      const rows = await export_experiment(exp.experiment_uuid, path);
+      toast({
+        variant: "success",
+        title: `Exported ${rows} inferences.`,
+        duration: 2500,
+      });
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not export the experiment.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  }
+
+  // Static HTML or Markdown summary, to paste into docs
+  async function reportExperiment(exp: IExperimentSummary) {
+    const path = await save({
+      defaultPath: exp.name + ".html",
+      filters: [
+        { name: "HTML", extensions: ["html"] },
+        { name: "Markdown", extensions: ["md"] },
+      ],
+    });
+    if (!path) return;
+
+    try {
+      await generate_report(exp.experiment_uuid, path);
+      toast({
+        variant: "success",
+        title: "Report saved.",
+        duration: 2500,
+      });
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not generate the report.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  }
+
+  // Downloaded logs (or logs from older versions) back into the history.
+  // An experiment that is already stored is imported again as a copy.
+  async function importExperiment() {
+    const path = await open({
+      multiple: false,
+      filters: [{ name: "Experiment", extensions: ["json"] }],
+    });
+    if (!path || Array.isArray(path)) return;
+
+    try {
+      const report = await import_experiment(path, "rename");
+      toast({
+        variant: "success",
+        title: `Imported ${report.name}.`,
+        description: `${report.inference_count} inferences, migrated from log version ${report.migrated_from}.`,
+        duration: 2500,
+      });
+      queryClient.refetchQueries({
+        queryKey: ["list_experiments"],
+      });
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not import the experiment.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  }
+
+  async function downloadExperiment(uuid: string) {
+    const experiment = await get_experiment(uuid);
+    await handleDownload(experiment.name + ".json", experiment.contents);
+  }
+
   return (
     <Sheet open={sheetOpen} onOpenChange={setSheetOpen}>
       <Tooltip>
@@ -206,7 +377,7 @@ export function ExperimentSelector() {
               size="icon"
               onClick={() =>
                 queryClient.refetchQueries({
-                  queryKey: ["get_experiments"],
+                  queryKey: ["list_experiments"],
                 })
               }
             >
@@ -221,7 +392,7 @@ export function ExperimentSelector() {
         <SheetHeader>
           <SheetTitle className="text-2xl">Experiments</SheetTitle>
           <SheetDescription>
-            Inspect, re-run, download or delete your experiments.
+            Inspect, re-run, download, import or delete your experiments.
           </SheetDescription>
         </SheetHeader>
         <div id="results" className="h-full w-full gap-8 py-6">
@@ -230,9 +401,118 @@ export function ExperimentSelector() {
               <div>Loading...</div>
             </div>
           )}
+          <div className="mb-2 flex items-center gap-2">
+            <Input
+              placeholder="Search by name, uuid or prompt..."
+              value={filters.text ?? ""}
+              onChange={(e) => updateFilters({ text: e.target.value })}
+            />
+            <Input
+              className="w-40"
+              placeholder="Model"
+              value={filters.model ?? ""}
+              onChange={(e) => updateFilters({ model: e.target.value })}
+            />
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  onClick={() =>
+                    updateFilters({
+                      favorite: filters.favorite ? undefined : true,
+                    })
+                  }
+                >
+                  {filters.favorite ? (
+                    <StarFilledIcon className="h-4 w-4 text-amber-500" />
+                  ) : (
+                    <StarIcon className="h-4 w-4" />
+                  )}
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent>Only favorites</TooltipContent>
+            </Tooltip>
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  onClick={() =>
+                    updateFilters({
+                      run_type: filters.run_type ? undefined : "regression",
+                    })
+                  }
+                >
+                  <TargetIcon
+                    className={`h-4 w-4 ${filters.run_type ? "text-cyan-500" : ""}`}
+                  />
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent>Only regression runs</TooltipContent>
+            </Tooltip>
+            <Tooltip>
+              <TooltipTrigger asChild>
+                <Button variant="ghost" size="icon" onClick={importExperiment}>
+                  <UploadIcon className="h-4 w-4" />
+                </Button>
+              </TooltipTrigger>
+              <TooltipContent>Import experiment</TooltipContent>
+            </Tooltip>
+          </div>
+          {filters.tags && filters.tags.length > 0 && (
+            <div className="mb-2 flex flex-wrap items-center gap-1 text-xs">
+              Tags:
+              {filters.tags.map((tag) => (
+                <Button
+                  key={tag}
+                  variant="secondary"
+                  size="sm"
+                  className="h-6 gap-1 px-2 text-xs"
+                  onClick={() =>
+                    updateFilters({
+                      tags: filters.tags?.filter((t) => t !== tag),
+                    })
+                  }
+                >
+                  {tag}
+                  <CrossCircledIcon className="h-3 w-3" />
+                </Button>
+              ))}
+            </div>
+          )}
           {query.data && (
-            <div className="mr-1 flex justify-end">
-              {" "}
+            <div className="mr-1 flex items-center justify-between">
+              <div className="flex items-center gap-1 text-xs text-gray-400">
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  disabled={!filters.page}
+                  onClick={() =>
+                    setFilters((prev) => ({
+                      ...prev,
+                      page: (prev.page ?? 0) - 1,
+                    }))
+                  }
+                >
+                  <ChevronLeftIcon className="h-4 w-4" />
+                </Button>
+                Page {(filters.page ?? 0) + 1} of {pageCount} (
+                {query.data.total} experiments)
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  disabled={(filters.page ?? 0) + 1 >= pageCount}
+                  onClick={() =>
+                    setFilters((prev) => ({
+                      ...prev,
+                      page: (prev.page ?? 0) + 1,
+                    }))
+                  }
+                >
+                  <ChevronRightIcon className="h-4 w-4" />
+                </Button>
+              </div>
               <Button
                 variant="ghost"
                 size="sm"
@@ -248,29 +528,73 @@ export function ExperimentSelector() {
           )}
           <div className="scrollv max-h-[calc(100vh-200px)] scroll-m-4 overflow-y-auto pr-1 scrollbar scrollbar-track-inherit scrollbar-thumb-gray-200 dark:scrollbar-track-inherit dark:scrollbar-thumb-gray-800">
             {query.data &&
-              query.data.map((exp: IExperimentFile) => (
+              query.data.items.map((exp: IExperimentSummary) => (
                 <div
                   className="my-1 flex items-center gap-2 justify-self-start rounded-sm p-2 transition-colors duration-200 hover:bg-gray-100 dark:hover:bg-gray-800"
-                  key={exp.name}
+                  key={exp.experiment_uuid}
                 >
                   <div className="flex-1 py-1">
-                    <div className="text-[14px] font-semibold">
+                    <div className="flex items-center gap-1 text-[14px] font-semibold">
+                      <button onClick={() => toggleFavorite(exp)}>
+                        {exp.is_favorite ? (
+                          <StarFilledIcon className="h-4 w-4 text-amber-500" />
+                        ) : (
+                          <StarIcon className="h-4 w-4 text-gray-400" />
+                        )}
+                      </button>
                       {exp.name}
+                      {exp.run_type === "regression" && (
+                        <span className="rounded bg-cyan-100 px-1.5 text-xs font-normal text-cyan-800 dark:bg-cyan-900 dark:text-cyan-200">
+                          regression
+                        </span>
+                      )}
                       {/* {convertEpochToDateTime(exp.created.secs_since_epoch)} */}
                     </div>
 
                     <div className="pb-1 text-xs text-gray-400">
                       {exp.created.toString()}
                     </div>
+                    <div className="text-xs text-gray-500">
+                      {exp.inference_count} inferences &middot;{" "}
+                      {exp.models.join(", ")}
+                      {exp.status !== "completed" && (
+                        <span className="text-red-500">
+                          {" "}
+                          &middot; {exp.status} ({exp.error_count} errors)
+                        </span>
+                      )}
+                    </div>
+                    {exp.tags.length > 0 && (
+                      <div className="flex flex-wrap gap-1 pt-1">
+                        {exp.tags.map((tag) => (
+                          <button
+                            key={tag}
+                            className="rounded bg-gray-200 px-1.5 text-xs dark:bg-gray-700"
+                            onClick={() =>
+                              updateFilters({
+                                tags: Array.from(
+                                  new Set([...(filters.tags ?? []), tag]),
+                                ),
+                              })
+                            }
+                          >
+                            {tag}
+                          </button>
+                        ))}
+                      </div>
+                    )}
                   </div>
 
                   {/* Buttons to inspect and download */}
                   <div>
                     <ExperimentDataDialog experiment={exp} />
+                    <ExperimentEditDialog experiment={exp} />
+                    <ComparisonDialog experiment={exp} />
+                    <ChartDialog experiment={exp} />
                     <Button
                       variant="ghost"
                       size="icon"
-                      onClick={() => cloneExperiment(exp.contents)}
+                      onClick={() => cloneExperiment(exp.experiment_uuid)}
                     >
                       <UpdateIcon className="h-4 w-4" />
                     </Button>
@@ -278,21 +602,33 @@ export function ExperimentSelector() {
                     <Button
                       variant="ghost"
                       size="icon"
-                      onClick={() =>
-                        handleDownload(exp.name + ".json", exp.contents)
-                      }
+                      onClick={() => downloadExperiment(exp.experiment_uuid)}
                     >
                       <DownloadIcon className="h-4 w-4" />
                     </Button>
 
+                    <Button
+                      variant="ghost"
+                      size="icon"
+                      onClick={() => exportExperiment(exp)}
+                    >
+                      <TableIcon className="h-4 w-4" />
+                    </Button>
+
+                    <Button
+                      variant="ghost"
+                      size="icon"
+                      onClick={() => reportExperiment(exp)}
+                    >
+                      <ReaderIcon className="h-4 w-4" />
+                    </Button>
+
                     {/* delete log file */}
                     <Button
                       size="icon"
                       variant="destructiveGhost"
                       onClick={async () => {
-                        await deleteExperiments(
-                          JSON.parse(exp.contents).experiment_uuid,
-                        );
+                        await deleteExperiments(exp.experiment_uuid);
                       }}
                     >
                       <CrossCircledIcon className="h-4 w-4 text-red-500" />
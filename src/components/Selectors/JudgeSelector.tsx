@@ -0,0 +1,79 @@
+import { configAtom } from "@/Atoms";
+import {
+  FormControl,
+  FormDescription,
+  FormField,
+  FormItem,
+  FormLabel,
+  FormMessage,
+} from "@/components/ui/form";
+import { Textarea } from "@/components/ui/textarea";
+import { useQuery } from "@tanstack/react-query";
+import { useAtom } from "jotai";
+import { get_models } from "../queries";
+
+interface IProps {
+  form: any;
+}
+
+function JudgeSelector({ form }: IProps) {
+  const [config, __] = useAtom(configAtom);
+  const judgeModel: string = form.watch("judge_model");
+
+  // Same query as the model selector, so the list is shared
+  const query = useQuery<string[]>({
+    queryKey: ["get_models", config],
+    queryFn: (): Promise<string[]> => get_models(config),
+  });
+
+  return (
+    <div className="flex flex-col gap-2">
+      <FormField
+        control={form.control}
+        name="judge_model"
+        render={({ field }) => (
+          <FormItem>
+            <FormLabel className="font-bold">Judge</FormLabel>
+            <FormControl>
+              <select
+                className="h-9 w-full rounded-md border border-input bg-transparent px-3 text-sm"
+                {...field}
+              >
+                <option value="">No judge</option>
+                {query.data?.map((model) => (
+                  <option key={model} value={model}>
+                    {model}
+                  </option>
+                ))}
+              </select>
+            </FormControl>
+            <FormDescription>
+              Optional. A model that grades each response against the rubric,
+              from 1 to 10.
+            </FormDescription>
+            <FormMessage />
+          </FormItem>
+        )}
+      />
+      {judgeModel && (
+        <FormField
+          control={form.control}
+          name="judge_rubric"
+          render={({ field }) => (
+            <FormItem>
+              <FormControl>
+                <Textarea
+                  placeholder="e.g.: Is the answer correct, complete and concise?"
+                  {...field}
+                />
+              </FormControl>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
+      )}
+    </div>
+  );
+}
+
+export default JudgeSelector;
@@ -33,6 +33,17 @@ function PromptSelector({ form }: IProps) {
   const handleAppendPrompt = () => {
     append("");
     form.setValue(`prompts.${fields.length}`, "");
+    form.setValue(`prompt_uuids.${fields.length}`, null);
+  };
+
+  // keeps the library uuids lined up with the prompts
+  const handleRemovePrompt = (index: number) => {
+    const uuids: (string | null)[] = form.getValues("prompt_uuids");
+    form.setValue(
+      "prompt_uuids",
+      uuids.filter((_, idx) => idx !== index),
+    );
+    remove(index);
   };
 
   const handlePromptChange = (value: string, index: number) => {
@@ -78,7 +89,7 @@ function PromptSelector({ form }: IProps) {
                           variant="destructiveGhost"
                           size="sm"
                           type="button"
-                          onClick={() => remove(index)}
+                          onClick={() => handleRemovePrompt(index)}
                         >
                           <Tooltip>
                             <TooltipTrigger asChild>
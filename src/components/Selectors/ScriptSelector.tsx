@@ -0,0 +1,87 @@
+import { IScript } from "@/Interfaces";
+import { Checkbox } from "@/components/ui/checkbox";
+import {
+  FormControl,
+  FormDescription,
+  FormField,
+  FormItem,
+  FormLabel,
+  FormMessage,
+} from "@/components/ui/form";
+import { useQuery } from "@tanstack/react-query";
+import { get_scripts } from "../queries";
+
+interface IProps {
+  form: any;
+}
+
+function ScriptSelector({ form }: IProps) {
+  // Same query as the scripts dialog, so saved scripts show up here
+  const query = useQuery<IScript[]>({
+    queryKey: ["get_scripts"],
+    queryFn: get_scripts,
+  });
+
+  // Hide the field until there's something to select
+  if (!query.data?.length) {
+    return null;
+  }
+
+  return (
+    <FormField
+      control={form.control}
+      name="scripts"
+      render={({ field }) => (
+        <FormItem>
+          <FormLabel className="font-bold">Scripts</FormLabel>
+          {query.data?.map((script) => {
+            // Scripts run in the order they're selected
+            const position = field.value?.indexOf(script.name) ?? -1;
+            return (
+              <div
+                key={script.name}
+                className="flex flex-row items-start space-x-3"
+              >
+                <FormControl>
+                  <Checkbox
+                    checked={position >= 0}
+                    onCheckedChange={(checked: boolean) => {
+                      if (checked) {
+                        field.onChange([...field.value, script.name]);
+                      } else {
+                        field.onChange(
+                          field.value?.filter(
+                            (value: string) => value !== script.name,
+                          ),
+                        );
+                      }
+                    }}
+                  />
+                </FormControl>
+                <div className="text-sm">
+                  {position >= 0 && (
+                    <span className="text-gray-500">{position + 1}. </span>
+                  )}
+                  {script.name}
+                  {script.description && (
+                    <span className="text-gray-500">
+                      {" "}
+                      - {script.description}
+                    </span>
+                  )}
+                </div>
+              </div>
+            );
+          })}
+          <FormDescription>
+            Optional. Scripts that rewrite each prompt before it's sent and
+            each response before it's logged, in the order they're selected.
+          </FormDescription>
+          <FormMessage />
+        </FormItem>
+      )}
+    />
+  );
+}
+
+export default ScriptSelector;
@@ -1,4 +1,5 @@
 import { PromptDialog } from "@/components/prompt-dialog";
+import { Button } from "@/components/ui/button";
 import {
   FormControl,
   FormDescription,
@@ -7,77 +8,110 @@ import {
   FormLabel,
   FormMessage,
 } from "@/components/ui/form";
-import { Textarea } from "@/components/ui/textarea";
-import { useEffect, useState } from "react";
-// import { Form } from "react-hook-form"
-import * as React from "react";
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipTrigger,
+} from "@/components/ui/tooltip";
+import { PlusIcon, TrashIcon } from "@radix-ui/react-icons";
+import { useFieldArray } from "react-hook-form";
+import { PromptTextArea } from "../prompt-textarea";
 
 interface IProps {
   form: any;
 }
 
-function SystemPromptSelector(props: IProps) {
-  const { form } = props;
-  const idx = 0;
-  const [systemPrompt, setSystemPrompt] = useState<string>(
-    form.getValues().system_prompt,
-  );
+function SystemPromptSelector({ form }: IProps) {
+  const { fields, append, remove } = useFieldArray({
+    control: form.control,
+    name: "system_prompts",
+  });
 
-  useEffect(() => {
-    // sync form state
-    form.setValue("system_prompt", systemPrompt);
-  }, [systemPrompt]);
+  const handleAppendSystemPrompt = () => {
+    append("");
+    form.setValue(`system_prompts.${fields.length}`, "");
+  };
 
-  const handleChange = (
-    e: React.ChangeEvent<HTMLTextAreaElement>,
-    _: number,
-  ) => {
-    setSystemPrompt(e.target.value);
+  const handleSystemPromptChange = (value: string, index: number) => {
+    form.setValue(`system_prompts.${index}`, value);
+    form.trigger();
   };
 
   return (
     <FormField
       control={form.control}
-      name="system_prompt"
+      name="system_prompts"
       render={() => (
         <FormItem>
-          <FormField
-            control={form.control}
-            name="prompt"
-            render={({ field }) => {
-              return (
+          {fields.map((field, index) => (
+            <FormField
+              key={field.id}
+              control={form.control}
+              name={`system_prompts.${index}`}
+              render={({ field: fieldProps }) => (
                 <FormItem>
                   <FormLabel className="flex flex-row items-center justify-between font-bold">
-                    System Prompt
+                    System Prompt {fields.length > 1 && (index + 1).toString()}
                     <div>
                       <PromptDialog
-                        content={systemPrompt}
-                        handleChange={handleChange}
-                        idx={idx}
-                        fieldName="system_prompt"
+                        content={fieldProps.value}
+                        handleChange={(e) =>
+                          handleSystemPromptChange(e.target.value, index)
+                        }
+                        idx={index}
+                        fieldName={`system_prompts.${index}`}
                         fieldLabel="system prompt"
                       />
+                      {fields.length > 1 && (
+                        <Button
+                          variant="destructiveGhost"
+                          size="sm"
+                          type="button"
+                          onClick={() => remove(index)}
+                        >
+                          <Tooltip>
+                            <TooltipTrigger asChild>
+                              <TrashIcon className="h-4 w-4" />
+                            </TooltipTrigger>
+                            <TooltipContent>
+                              Delete system prompt
+                            </TooltipContent>
+                          </Tooltip>
+                        </Button>
+                      )}
                     </div>
                   </FormLabel>
                   <FormControl>
-                    <Textarea
-                      {...field}
+                    <PromptTextArea
+                      value={fieldProps.value}
+                      onChange={(value) => {
+                        fieldProps.onChange(value);
+                        handleSystemPromptChange(value, index);
+                      }}
                       className="flex-1"
-                      value={systemPrompt}
-                      rows={4}
-                      onChange={(e: React.ChangeEvent<HTMLTextAreaElement>) =>
-                        handleChange(e, idx)
-                      }
+                      placeholder="Type '/' to search system prompts..."
+                      library="system_prompts"
                     />
                   </FormControl>
-                  <FormDescription>
-                    Optional. The system message used to specify custom
-                    behavior.
-                  </FormDescription>
                 </FormItem>
-              );
-            }}
-          />
+              )}
+            />
+          ))}
+
+          <FormDescription>
+            Optional. The system message used to specify custom behavior. Add
+            another system prompt to compare them in the same experiment.
+          </FormDescription>
+          <Button
+            variant="secondary"
+            size="sm"
+            type="button"
+            onClick={handleAppendSystemPrompt}
+            className="flex items-center gap-1"
+          >
+            <PlusIcon className="h-4 w-4" />
+            Add Another System Prompt
+          </Button>
           <FormMessage />
         </FormItem>
       )}
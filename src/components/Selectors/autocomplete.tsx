@@ -1,6 +1,6 @@
 import { useQuery } from "@tanstack/react-query";
 import * as React from "react";
-import { get_all_prompts } from "../queries";
+import { get_all_prompts, get_all_system_prompts } from "../queries";
 
 import { IPrompt } from "@/Interfaces";
 import { ScrollArea } from "../ui/scroll-area";
@@ -10,6 +10,8 @@ export interface IProps {
   index: number;
   inputText?: string;
   onSelect: (value: string) => void;
+  // which prompt library to search
+  library?: "prompts" | "system_prompts";
 }
 
 export function Autocomplete({
@@ -17,6 +19,7 @@ export function Autocomplete({
   index,
   inputText = "",
   onSelect,
+  library = "prompts",
 }: IProps) {
   const [filteredPrompts, setFilteredPrompts] = React.useState<IPrompt[]>([]);
   const [selectedIndex, setSelectedIndex] = React.useState<number>(0);
@@ -24,8 +27,11 @@ export function Autocomplete({
   const containerRef = React.useRef<HTMLDivElement>(null);
 
   const promptQuery = useQuery<IPrompt[]>({
-    queryKey: ["get_all_prompts"],
-    queryFn: (): Promise<IPrompt[]> => get_all_prompts(),
+    queryKey: [
+      library === "prompts" ? "get_all_prompts" : "get_all_system_prompts",
+    ],
+    queryFn: (): Promise<IPrompt[]> =>
+      library === "prompts" ? get_all_prompts() : get_all_system_prompts(),
     refetchInterval: 1000 * 30 * 1,
     refetchOnWindowFocus: "always",
     staleTime: 0,
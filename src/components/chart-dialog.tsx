@@ -0,0 +1,183 @@
+import { BarChartIcon } from "@radix-ui/react-icons";
+
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { toast } from "@/components/ui/use-toast";
+
+import { IChartSpec, IExperimentSummary, TChartKind } from "@/Interfaces";
+import { generate_chart } from "@/components/queries";
+import { save } from "@tauri-apps/api/dialog";
+import { useState } from "react";
+
+interface IProps {
+  experiment: IExperimentSummary;
+}
+
+// Suggestions only: any column of the experiment export can be charted
+const CHART_FIELDS = [
+  "model",
+  "temperature",
+  "repeat_penalty",
+  "top_k",
+  "top_p",
+  "repeat_last_n",
+  "tfs_z",
+  "mirostat",
+  "mirostat_tau",
+  "mirostat_eta",
+  "seed",
+  "think",
+  "tokens_per_second",
+  "eval_count",
+  "reasoning_tokens",
+  "answer_tokens",
+  "prompt_eval_count",
+  "total_duration",
+  "response_length",
+  "failed",
+  "rating",
+  "thumb",
+  "mean_score",
+  "judge_score",
+];
+
+const CHART_KINDS: TChartKind[] = ["line", "scatter", "bar", "heatmap"];
+
+export function ChartDialog(props: IProps) {
+  const { experiment } = props;
+  const [open, setOpen] = useState(false);
+  const [spec, setSpec] = useState<IChartSpec>({
+    kind: "line",
+    x: "temperature",
+    y: "tokens_per_second",
+    series: "model",
+  });
+
+  const update = (changes: Partial<IChartSpec>) =>
+    setSpec({ ...spec, ...changes });
+
+  const handleSave = async () => {
+    const path = await save({
+      defaultPath: `${experiment.name}-${spec.y}-${spec.x}.svg`,
+      filters: [
+        { name: "SVG", extensions: ["svg"] },
+        { name: "PNG", extensions: ["png"] },
+      ],
+    });
+    if (!path) return;
+
+    try {
+      await generate_chart(experiment.experiment_uuid, path, {
+        ...spec,
+        series: spec.series || undefined,
+        value: spec.kind === "heatmap" ? spec.value : undefined,
+      });
+      toast({
+        variant: "success",
+        title: "Chart saved.",
+        duration: 2500,
+      });
+      setOpen(false);
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not draw the chart.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={setOpen}>
+      <DialogTrigger asChild>
+        <Button variant="ghost" size="icon">
+          <BarChartIcon className="h-4 w-4" />
+        </Button>
+      </DialogTrigger>
+      <DialogContent className="sm:max-w-[500px]">
+        <DialogHeader>
+          <DialogTitle>Chart: {experiment.name}</DialogTitle>
+          <DialogDescription>
+            Plot inference fields across the parameter sweep, as SVG or PNG.
+          </DialogDescription>
+        </DialogHeader>
+
+        <datalist id="chart-fields">
+          {CHART_FIELDS.map((field) => (
+            <option key={field} value={field} />
+          ))}
+        </datalist>
+        <div className="grid grid-cols-[100px_1fr] items-center gap-3">
+          <Label htmlFor="chart-kind">Kind</Label>
+          <select
+            id="chart-kind"
+            className="h-9 rounded-md border border-input bg-transparent px-3 text-sm"
+            value={spec.kind}
+            onChange={(e) => update({ kind: e.target.value as TChartKind })}
+          >
+            {CHART_KINDS.map((kind) => (
+              <option key={kind} value={kind}>
+                {kind}
+              </option>
+            ))}
+          </select>
+          <Label htmlFor="chart-x">X axis</Label>
+          <Input
+            id="chart-x"
+            list="chart-fields"
+            value={spec.x}
+            onChange={(e) => update({ x: e.target.value })}
+          />
+          <Label htmlFor="chart-y">Y axis</Label>
+          <Input
+            id="chart-y"
+            list="chart-fields"
+            value={spec.y}
+            onChange={(e) => update({ y: e.target.value })}
+          />
+          {spec.kind === "heatmap" ? (
+            <>
+              <Label htmlFor="chart-value">Color</Label>
+              <Input
+                id="chart-value"
+                list="chart-fields"
+                placeholder="e.g.: tokens_per_second"
+                value={spec.value ?? ""}
+                onChange={(e) => update({ value: e.target.value })}
+              />
+            </>
+          ) : (
+            <>
+              <Label htmlFor="chart-series">Series</Label>
+              <Input
+                id="chart-series"
+                list="chart-fields"
+                placeholder="Optional, e.g.: model"
+                value={spec.series ?? ""}
+                onChange={(e) => update({ series: e.target.value })}
+              />
+            </>
+          )}
+        </div>
+
+        <DialogFooter>
+          <Button variant="secondary" onClick={() => setOpen(false)}>
+            Cancel
+          </Button>
+          <Button onClick={handleSave}>Save chart</Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
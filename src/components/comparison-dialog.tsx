@@ -0,0 +1,180 @@
+import { MixIcon } from "@radix-ui/react-icons";
+
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import { toast } from "@/components/ui/use-toast";
+
+import {
+  IBlindPair,
+  IComparisonSession,
+  IExperimentSummary,
+  ILeaderboardEntry,
+  TVoteWinner,
+} from "@/Interfaces";
+import {
+  comparison_leaderboard,
+  next_comparison_pair,
+  record_comparison_vote,
+  start_comparison_session,
+} from "@/components/queries";
+import { Fragment, useState } from "react";
+import { systemPromptLabel } from "../lib/index";
+import { Separator } from "./ui/separator";
+
+interface IProps {
+  experiment: IExperimentSummary;
+}
+
+// Blind A/B voting: the backend serves pairs of responses to the same
+// prompt without revealing which model or parameters produced them.
+export function ComparisonDialog(props: IProps) {
+  const { experiment } = props;
+  const [open, setOpen] = useState(false);
+  const [session, setSession] = useState<IComparisonSession | null>(null);
+  const [pair, setPair] = useState<IBlindPair | null>(null);
+  const [leaderboard, setLeaderboard] = useState<ILeaderboardEntry[]>([]);
+
+  const showError = (title: string, err: unknown) =>
+    toast({
+      variant: "destructive",
+      title,
+      description: String(err),
+      duration: 5000,
+    });
+
+  const loadNext = async (sessionUuid: string) => {
+    const next = await next_comparison_pair(sessionUuid);
+    setPair(next);
+    if (!next) {
+      setLeaderboard(await comparison_leaderboard(experiment.experiment_uuid));
+    }
+  };
+
+  const handleOpenChange = async (isOpen: boolean) => {
+    setOpen(isOpen);
+    if (!isOpen) {
+      setSession(null);
+      setPair(null);
+      return;
+    }
+    try {
+      const newSession = await start_comparison_session(
+        experiment.experiment_uuid,
+      );
+      setSession(newSession);
+      await loadNext(newSession.uuid);
+    } catch (err) {
+      setOpen(false);
+      showError("Could not start a comparison.", err);
+    }
+  };
+
+  const vote = async (winner: TVoteWinner) => {
+    if (!session || !pair) return;
+    try {
+      await record_comparison_vote(
+        session.uuid,
+        pair.left_id,
+        pair.right_id,
+        winner,
+      );
+      await loadNext(session.uuid);
+    } catch (err) {
+      showError("Could not record the vote.", err);
+    }
+  };
+
+  const showLeaderboard = async () => {
+    setPair(null);
+    setLeaderboard(await comparison_leaderboard(experiment.experiment_uuid));
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={handleOpenChange}>
+      <DialogTrigger asChild>
+        <Button variant="ghost" size="icon">
+          <MixIcon className="h-4 w-4" />
+        </Button>
+      </DialogTrigger>
+      <DialogContent className="sm:max-w-[1000px]">
+        <DialogHeader>
+          <DialogTitle>Blind comparison: {experiment.name}</DialogTitle>
+          <DialogDescription>
+            Pick the better response. Models and parameters are hidden until
+            you see the leaderboard.
+          </DialogDescription>
+        </DialogHeader>
+
+        {pair ? (
+          <div className="flex max-h-[600px] flex-col gap-4 overflow-y-auto text-sm">
+            <div className="whitespace-pre-wrap font-mono text-gray-700 dark:text-gray-400">
+              {pair.system_prompt && (
+                <div className="text-amber-600 dark:text-amber-500">
+                  {pair.system_prompt}
+                </div>
+              )}
+              <div className="text-green-600 dark:text-green-500">
+                {pair.prompt}
+              </div>
+            </div>
+            <div className="grid grid-cols-2 gap-4">
+              <div className="whitespace-pre-wrap rounded border p-3 text-cyan-600">
+                {pair.left_response}
+              </div>
+              <div className="whitespace-pre-wrap rounded border p-3 text-cyan-600">
+                {pair.right_response}
+              </div>
+            </div>
+            <div className="flex items-center justify-center gap-4">
+              <Button onClick={() => vote("left")}>Left is better</Button>
+              <Button variant="secondary" onClick={() => vote("tie")}>
+                Tie
+              </Button>
+              <Button onClick={() => vote("right")}>Right is better</Button>
+            </div>
+            <div className="flex items-center justify-between text-xs text-gray-400">
+              {pair.remaining} pairs left in this session
+              <Button variant="ghost" size="sm" onClick={showLeaderboard}>
+                Stop and show leaderboard
+              </Button>
+            </div>
+          </div>
+        ) : (
+          <div className="text-sm">
+            <div className="font-bold">Leaderboard</div>
+            <Separator className="my-2" />
+            <div className="grid grid-cols-[1fr_80px_80px_120px] gap-1 font-mono">
+              <div className="font-bold">Configuration</div>
+              <div className="font-bold">Elo</div>
+              <div className="font-bold">BT</div>
+              <div className="font-bold">W/L/T</div>
+              {leaderboard.map((entry, idx) => (
+                <Fragment key={idx}>
+                  <div>
+                    {entry.combination.model} (temp{" "}
+                    {entry.combination.temperature.toFixed(2)}, top_k{" "}
+                    {entry.combination.top_k}, top_p{" "}
+                    {entry.combination.top_p.toFixed(2)}
+                    {systemPromptLabel(entry.combination.system_prompt)})
+                  </div>
+                  <div>{entry.elo.toFixed(0)}</div>
+                  <div>{entry.bradley_terry.toFixed(0)}</div>
+                  <div>
+                    {entry.wins}/{entry.losses}/{entry.ties}
+                  </div>
+                </Fragment>
+              ))}
+            </div>
+          </div>
+        )}
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,198 @@
+import { CrossCircledIcon, MixerVerticalIcon } from "@radix-ui/react-icons";
+
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Switch } from "@/components/ui/switch";
+import { toast } from "@/components/ui/use-toast";
+
+import { IEvaluator, IEvaluatorInput } from "@/Interfaces";
+import {
+  delete_evaluator,
+  get_evaluators,
+  save_evaluator,
+} from "@/components/queries";
+import { useQuery } from "@tanstack/react-query";
+import { useState } from "react";
+import { Tooltip, TooltipContent, TooltipTrigger } from "./ui/tooltip";
+
+const EMPTY_EVALUATOR: IEvaluatorInput = {
+  name: "",
+  command: "",
+  args: [],
+  working_dir: "",
+  timeout: 30,
+  enabled: true,
+};
+
+export function EvaluatorsDialog() {
+  const [open, setOpen] = useState(false);
+  const [input, setInput] = useState<IEvaluatorInput>(EMPTY_EVALUATOR);
+  // Arguments are edited as a single line, split on spaces
+  const [args, setArgs] = useState("");
+
+  const query = useQuery<IEvaluator[]>({
+    queryKey: ["get_evaluators"],
+    queryFn: get_evaluators,
+    enabled: open,
+  });
+
+  const update = (changes: Partial<IEvaluatorInput>) =>
+    setInput({ ...input, ...changes });
+
+  const handleSave = async (evaluator: IEvaluatorInput) => {
+    try {
+      await save_evaluator(evaluator);
+      query.refetch();
+      return true;
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not save the evaluator.",
+        description: String(err),
+        duration: 5000,
+      });
+      return false;
+    }
+  };
+
+  const handleAdd = async () => {
+    const saved = await handleSave({
+      ...input,
+      args: args.split(" ").filter((arg) => arg !== ""),
+    });
+    if (saved) {
+      setInput(EMPTY_EVALUATOR);
+      setArgs("");
+    }
+  };
+
+  const handleDelete = async (name: string) => {
+    try {
+      await delete_evaluator(name);
+      query.refetch();
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not delete the evaluator.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={setOpen}>
+      <Tooltip>
+        <TooltipTrigger asChild>
+          <DialogTrigger asChild>
+            <Button variant="transparentDark" size="icon">
+              <MixerVerticalIcon className="h-5 w-5" />
+            </Button>
+          </DialogTrigger>
+        </TooltipTrigger>
+        <TooltipContent>Evaluators</TooltipContent>
+      </Tooltip>
+      <DialogContent className="sm:max-w-[600px]">
+        <DialogHeader>
+          <DialogTitle>Evaluators</DialogTitle>
+          <DialogDescription>
+            Commands run on the responses of the experiments they're
+            selected for. They get the prompt, parameters and response as
+            JSON on stdin, and print a JSON object with a
+            "score" from 0 to 1 (and optionally "passed", "detail" and
+            "metrics") on stdout.
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="flex flex-col gap-1 text-sm">
+          {query.data?.length === 0 && (
+            <div className="text-gray-500">No evaluators yet.</div>
+          )}
+          {query.data?.map((evaluator) => (
+            <div
+              key={evaluator.name}
+              className="flex items-center justify-between gap-2"
+            >
+              <div className="truncate font-mono">
+                <span className="font-bold">{evaluator.name}</span>:{" "}
+                {[evaluator.command, ...evaluator.args].join(" ")} (
+                {evaluator.timeout}s)
+              </div>
+              <div className="flex items-center gap-2">
+                <Switch
+                  checked={evaluator.enabled}
+                  onCheckedChange={(enabled) =>
+                    handleSave({ ...evaluator, enabled })
+                  }
+                />
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  onClick={() => handleDelete(evaluator.name)}
+                >
+                  <CrossCircledIcon className="h-4 w-4 text-red-500" />
+                </Button>
+              </div>
+            </div>
+          ))}
+        </div>
+
+        <div className="grid grid-cols-[120px_1fr] items-center gap-3">
+          <Label htmlFor="evaluator-name">Name</Label>
+          <Input
+            id="evaluator-name"
+            placeholder="e.g.: factuality"
+            value={input.name}
+            onChange={(e) => update({ name: e.target.value })}
+          />
+          <Label htmlFor="evaluator-command">Command</Label>
+          <Input
+            id="evaluator-command"
+            placeholder="e.g.: python3"
+            value={input.command}
+            onChange={(e) => update({ command: e.target.value })}
+          />
+          <Label htmlFor="evaluator-args">Arguments</Label>
+          <Input
+            id="evaluator-args"
+            placeholder="e.g.: score.py --strict"
+            value={args}
+            onChange={(e) => setArgs(e.target.value)}
+          />
+          <Label htmlFor="evaluator-dir">Working directory</Label>
+          <Input
+            id="evaluator-dir"
+            placeholder="Optional"
+            value={input.working_dir}
+            onChange={(e) => update({ working_dir: e.target.value })}
+          />
+          <Label htmlFor="evaluator-timeout">Timeout (s)</Label>
+          <Input
+            id="evaluator-timeout"
+            type="number"
+            min={1}
+            value={input.timeout}
+            onChange={(e) => update({ timeout: Number(e.target.value) })}
+          />
+        </div>
+
+        <DialogFooter>
+          <Button variant="secondary" onClick={() => setOpen(false)}>
+            Close
+          </Button>
+          <Button onClick={handleAdd}>Save evaluator</Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
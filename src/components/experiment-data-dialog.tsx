@@ -9,24 +9,188 @@ import {
   DialogTrigger,
 } from "@/components/ui/dialog";
 
-import { IExperimentFile } from "@/Interfaces";
+import { configAtom } from "@/Atoms";
+import { toast } from "@/components/ui/use-toast";
+import {
+  IDiversityReport,
+  IExperimentFile,
+  IExperimentJudge,
+  IExperimentSummary,
+  IGoldenSuite,
+  IReproducibilityCheck,
+} from "@/Interfaces";
+import { IExperimentStats } from "@/Interfaces";
+import {
+  add_golden_baselines,
+  experiment_diversity,
+  experiment_summary,
+  get_experiment,
+  evaluate_experiment,
+  get_experiment_judge,
+  get_golden_suites,
+  get_models,
+  get_reproducibility_checks,
+  judge_experiment,
+} from "@/components/queries";
 import {
   convertNanosecondsToTime,
   formatInterval,
   tokensPerSecond,
 } from "@/lib";
-import { useState } from "react";
-import { convertToUTCString } from "../lib/index";
+import { useQuery } from "@tanstack/react-query";
+import { useAtom } from "jotai";
+import { Fragment, useState } from "react";
+import { convertToUTCString, systemPromptLabel } from "../lib/index";
 import { Separator } from "./ui/separator";
 
 interface IProps {
-  experiment: IExperimentFile;
+  experiment: IExperimentSummary;
 }
 
 export function ExperimentDataDialog(props: IProps) {
   const { experiment } = props;
   const [open, setOpen] = useState(false);
-  const data = JSON.parse(experiment.contents);
+
+  // The contents are only fetched when the dialog is opened
+  const query = useQuery<IExperimentFile>({
+    queryKey: ["get_experiment", experiment.experiment_uuid],
+    queryFn: () => get_experiment(experiment.experiment_uuid),
+    enabled: open,
+  });
+  const data = query.data ? JSON.parse(query.data.contents) : null;
+
+  // Scores of different names aren't comparable, so one is ranked by
+  const [rankBy, setRankBy] = useState("");
+  const summary = useQuery<IExperimentStats>({
+    queryKey: ["experiment_summary", experiment.experiment_uuid, rankBy],
+    queryFn: () => experiment_summary(experiment.experiment_uuid, rankBy),
+    enabled: open,
+  });
+
+  const [config, __] = useAtom(configAtom);
+  const [isJudging, setIsJudging] = useState(false);
+  const [isEvaluating, setIsEvaluating] = useState(false);
+  const judge = useQuery<IExperimentJudge | null>({
+    queryKey: ["get_experiment_judge", experiment.experiment_uuid],
+    queryFn: () => get_experiment_judge(experiment.experiment_uuid),
+    enabled: open,
+  });
+
+  const checks = useQuery<IReproducibilityCheck[]>({
+    queryKey: ["get_reproducibility_checks", experiment.experiment_uuid],
+    queryFn: () => get_reproducibility_checks(experiment.experiment_uuid),
+    enabled: open,
+  });
+
+  const [suiteName, setSuiteName] = useState("");
+  const suites = useQuery<IGoldenSuite[]>({
+    queryKey: ["get_golden_suites"],
+    queryFn: get_golden_suites,
+    enabled: open,
+  });
+
+  const handleAddBaselines = async () => {
+    // Failed inferences can't be baselines
+    const inferenceIds: string[] = (data?.inferences ?? [])
+      .filter((inference: any) => inference.result && inference.inference_id)
+      .map((inference: any) => inference.inference_id);
+    try {
+      const added = await add_golden_baselines(
+        suiteName,
+        experiment.experiment_uuid,
+        inferenceIds,
+      );
+      toast({
+        variant: "success",
+        title: `Added ${added} baselines to ${suiteName}.`,
+        duration: 2500,
+      });
+      suites.refetch();
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not add the baselines.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  const [embeddingModel, setEmbeddingModel] = useState("");
+  const [diversity, setDiversity] = useState<IDiversityReport | null>(null);
+  const [isAnalyzing, setIsAnalyzing] = useState(false);
+  // Same query as the model selector, so the list is shared
+  const models = useQuery<string[]>({
+    queryKey: ["get_models", config],
+    queryFn: (): Promise<string[]> => get_models(config),
+    enabled: open,
+  });
+
+  const handleDiversity = async () => {
+    setIsAnalyzing(true);
+    try {
+      setDiversity(
+        await experiment_diversity(
+          config,
+          experiment.experiment_uuid,
+          embeddingModel,
+        ),
+      );
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not analyze the diversity of the responses.",
+        description: String(err),
+        duration: 5000,
+      });
+    } finally {
+      setIsAnalyzing(false);
+    }
+  };
+
+  const handleJudge = async () => {
+    setIsJudging(true);
+    try {
+      const count = await judge_experiment(config, experiment.experiment_uuid);
+      toast({
+        variant: "success",
+        title: `The judge scored ${count} inferences.`,
+        duration: 2500,
+      });
+      summary.refetch();
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not judge the experiment.",
+        description: String(err),
+        duration: 5000,
+      });
+    } finally {
+      setIsJudging(false);
+    }
+  };
+
+  const handleEvaluate = async () => {
+    setIsEvaluating(true);
+    try {
+      const count = await evaluate_experiment(experiment.experiment_uuid);
+      toast({
+        variant: "success",
+        title: `The evaluators gave ${count} scores.`,
+        duration: 2500,
+      });
+      summary.refetch();
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not run the evaluators.",
+        description: String(err),
+        duration: 5000,
+      });
+    } finally {
+      setIsEvaluating(false);
+    }
+  };
 
   return (
     <Dialog open={open} onOpenChange={setOpen}>
@@ -43,195 +207,528 @@ export function ExperimentDataDialog(props: IProps) {
           </DialogDescription> */}
         </DialogHeader>
 
+        {query.isLoading && <div className="text-sm">Loading...</div>}
+        {query.isError && (
+          <div className="text-sm text-red-500">{String(query.error)}</div>
+        )}
+
         {/* we need to set a max-height on this div for overflow to work */}
-        <div className="flex max-h-[500px] w-full flex-col gap-4 overflow-y-auto">
-          <div className="text-sm">
-            <div className="font-bold">Metadata</div>
-            <div className="flex flex-col gap-2 px-2">
-              <div>ID: {JSON.parse(experiment.contents).experiment_uuid}</div>
-              <div>Date: {experiment.created.toString()}</div>
+        {data && (
+          <div className="flex max-h-[500px] w-full flex-col gap-4 overflow-y-auto">
+            <div className="text-sm">
+              <div className="font-bold">Metadata</div>
+              <div className="flex flex-col gap-2 px-2">
+                <div>ID: {experiment.experiment_uuid}</div>
+                <div>Date: {experiment.created.toString()}</div>
+                {experiment.tags.length > 0 && (
+                  <div>Tags: {experiment.tags.join(", ")}</div>
+                )}
+              </div>
             </div>
-          </div>
 
-          {/* CONFIG */}
-          <div className="text-sm">
-            <div className="font-bold">Settings</div>
-
-            {/* main settings table */}
-            <div className="grid grid-cols-[180px_minmax(180px,_auto)] gap-0">
-              <div className="flex flex-col">
-                <div className="p-2">Server URL</div>
-                <div className="p-2">Timeout</div>
-                <div className="p-2">Default System Prompt</div>
-              </div>
-              <div className="flex flex-col">
-                <div className="p-2">{data.config.server_url}</div>
-                <div className="p-2">{data.config.request_timeout}</div>
-                <div className="p-2 text-amber-600 dark:text-amber-500">
-                  {data.config.system_prompt}
+            {experiment.description && (
+              <div className="text-sm">
+                <div className="font-bold">Description</div>
+                <div className="whitespace-pre-wrap px-2">
+                  {experiment.description}
                 </div>
               </div>
-            </div>
+            )}
 
-            {/* options table */}
-            <div className="grid  grid-cols-[180px_minmax(180px,_auto)] gap-0">
-              <div className="flex flex-col">
-                <div className="p-2">Default Options</div>
-              </div>
-              <div className="flex flex-col">
-                <div className="p-2 font-mono  text-gray-700 dark:text-gray-400">
-                  <pre>
-                    {Object.entries(data.config.default_options || {}).map(
-                      ([key, value]) => (
-                        <div key={key}>
-                          {key}: {JSON.stringify(value)}
-                        </div>
-                      ),
-                    )}
-                  </pre>
+            {/* RATINGS */}
+            {summary.data && summary.data.ratings.rated_count > 0 && (
+              <div className="text-sm">
+                <div className="font-bold">Ratings</div>
+                <div className="flex flex-col gap-1 px-2 font-mono text-gray-700 dark:text-gray-400">
+                  {summary.data.groups.map((group, idx) => (
+                    <div key={idx}>
+                      {group.combination.model} (temp{" "}
+                      {group.combination.temperature.toFixed(2)}, top_k{" "}
+                      {group.combination.top_k}, top_p{" "}
+                      {group.combination.top_p.toFixed(2)}
+                      {systemPromptLabel(group.combination.system_prompt)}):{" "}
+                      {group.ratings.rated_count}/{group.inference_count}{" "}
+                      rated, {group.ratings.thumbs_up} up /{" "}
+                      {group.ratings.thumbs_down} down
+                      {group.ratings.avg_rating !== null &&
+                        `, avg ${group.ratings.avg_rating.toFixed(2)}/5`}
+                    </div>
+                  ))}
                 </div>
               </div>
-            </div>
-          </div>
-
-          <Separator className="my-3" />
-
-          {/* inferences */}
-          <div className="text-sm">
-            <div className="font-bold">Inferences</div>
+            )}
 
-            {/* inferences table */}
-            <div>
-              {data.inferences.map((inf: any, index: number) => (
-                <div key={index} className="my-8">
-                  <div className="font-bold">
-                    [{index + 1}/{data.inferences.length}]{" "}
-                    {inf.parameters.model}
+            {/* JUDGE */}
+            {judge.data && (
+              <div className="text-sm">
+                <div className="flex items-center justify-between font-bold">
+                  Judge
+                  <Button
+                    variant="secondary"
+                    size="sm"
+                    disabled={isJudging}
+                    onClick={handleJudge}
+                  >
+                    {isJudging ? "Judging..." : "Judge again"}
+                  </Button>
+                </div>
+                <div className="flex flex-col gap-1 px-2">
+                  <div>
+                    {judge.data.model}, scores from {judge.data.min_score} to{" "}
+                    {judge.data.max_score}
                   </div>
-                  <div className="m-4">
-                    <div>Inference Prompts</div>
-                    <div className="whitespace-pre-wrap font-mono text-gray-700 dark:text-gray-400">
-                      System prompt:{" "}
-                      <span className="text-amber-600 dark:text-amber-500">
-                        {inf.parameters.system_prompt}
-                      </span>
-                    </div>
-                    <div className="whitespace-pre-wrap font-mono text-gray-700 dark:text-gray-400">
-                      Prompt:{" "}
-                      <span className="text-green-600 dark:text-green-500">
-                        {inf.parameters.prompt}
-                      </span>
-                    </div>
+                  <div className="whitespace-pre-wrap text-gray-700 dark:text-gray-400">
+                    {judge.data.rubric}
                   </div>
-                  <div className="m-4">
-                    <div>Response</div>
-                    <div className="whitespace-pre-wrap text-cyan-600 dark:text-cyan-600">
-                      {inf.result.response}
-                    </div>
+                </div>
+              </div>
+            )}
+
+            {/* SCORES */}
+            {summary.data && (
+              <div className="text-sm">
+                <div className="flex items-center justify-between font-bold">
+                  Scores
+                  <Button
+                    variant="secondary"
+                    size="sm"
+                    disabled={isEvaluating}
+                    onClick={handleEvaluate}
+                  >
+                    {isEvaluating ? "Evaluating..." : "Run evaluators"}
+                  </Button>
+                </div>
+                {summary.data.ranked_by && (
+                  <div className="flex items-center gap-2 px-2 py-1">
+                    <span>Rank by</span>
+                    <select
+                      className="h-8 rounded-md border border-input bg-transparent px-2 text-sm"
+                      value={summary.data.ranked_by}
+                      onChange={(e) => setRankBy(e.target.value)}
+                    >
+                      {Object.keys(summary.data.scores.means).map((name) => (
+                        <option key={name} value={name}>
+                          {name}
+                        </option>
+                      ))}
+                    </select>
                   </div>
-                  {/* parameters and metadata */}
-                  <div>
-                    <div className="ml-4 flex gap-8">
-                      <div>
-                        <div>Inference Parameters</div>
+                )}
+                <div className="flex flex-col gap-1 px-2 font-mono text-gray-700 dark:text-gray-400">
+                  {summary.data.score_ranking.length === 0 && (
+                    <div>No scores yet.</div>
+                  )}
+                  {summary.data.score_ranking.map((idx, rank) => {
+                    const group = summary.data.groups[idx];
+                    return (
+                      <div key={idx}>
+                        {rank + 1}. {group.combination.model} (temp{" "}
+                        {group.combination.temperature.toFixed(2)}, top_k{" "}
+                        {group.combination.top_k}, top_p{" "}
+                        {group.combination.top_p.toFixed(2)}
+                        {systemPromptLabel(group.combination.system_prompt)}):{" "}
+                        {summary.data.ranked_by}{" "}
+                        {group.scores.means[
+                          summary.data.ranked_by ?? ""
+                        ]?.toFixed(3)}
+                        {group.scores.pass_rate !== null &&
+                          `, ${(group.scores.pass_rate * 100).toFixed(0)}% passed`}
+                        {" - "}
+                        {Object.entries(group.scores.means)
+                          .map(([name, mean]) => `${name} ${mean.toFixed(2)}`)
+                          .join(", ")}
+                      </div>
+                    );
+                  })}
+                </div>
+              </div>
+            )}
 
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          temperature:{" "}
-                          {Number(inf.parameters.temperature).toFixed(2)}
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          repeat_penalty:{" "}
-                          {Number(inf.parameters.repeat_penalty).toFixed(2)}
+            {/* PERFORMANCE */}
+            {summary.data && summary.data.groups.length > 0 && (
+              <div className="text-sm">
+                <div className="font-bold">Performance</div>
+                <div className="grid grid-cols-[1fr_auto_auto_auto_auto] gap-x-4 gap-y-1 px-2 font-mono text-gray-700 dark:text-gray-400">
+                  <div>Configuration</div>
+                  <div>Generation t/s (95% CI)</div>
+                  <div>Prompt t/s</div>
+                  <div>First token s (p95)</div>
+                  <div>Reasoning / answer tokens</div>
+                  {summary.data.groups.map((group, idx) => {
+                    const gen = group.performance.generation_tokens_per_second;
+                    const prompt = group.performance.prompt_tokens_per_second;
+                    const ttft = group.performance.time_to_first_token;
+                    const reasoning = group.performance.reasoning_tokens;
+                    const answer = group.performance.answer_tokens;
+                    const ci =
+                      gen && gen.ci95_low !== null && gen.ci95_high !== null
+                        ? ` (${gen.ci95_low.toFixed(1)}-${gen.ci95_high.toFixed(1)})`
+                        : "";
+                    return (
+                      <Fragment key={idx}>
+                        <div>
+                          {group.combination.model} (temp{" "}
+                          {group.combination.temperature.toFixed(2)}, top_k{" "}
+                          {group.combination.top_k}, top_p{" "}
+                          {group.combination.top_p.toFixed(2)}
+                          {systemPromptLabel(group.combination.system_prompt)}
+                          {group.combination.think !== null &&
+                            `, think ${group.combination.think ? "on" : "off"}`}
+                          )
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          top_k: {Number(inf.parameters.top_k).toFixed(2)}
+                        <div>{gen ? gen.mean.toFixed(1) + ci : "-"}</div>
+                        <div>{prompt ? prompt.median.toFixed(1) : "-"}</div>
+                        <div>
+                          {ttft
+                            ? `${ttft.median.toFixed(2)} (${ttft.p95.toFixed(2)})`
+                            : "-"}
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          top_p: {Number(inf.parameters.top_p).toFixed(2)}
+                        <div>
+                          {reasoning && answer
+                            ? `${reasoning.mean.toFixed(0)} / ${answer.mean.toFixed(0)}`
+                            : "-"}
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          repeat_last_n: {Number(inf.parameters.repeat_last_n)}
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          tfs_z: {Number(inf.parameters.tfs_z).toFixed(2)}
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          mirostat: {Number(inf.parameters.mirostat)}
+                      </Fragment>
+                    );
+                  })}
+                </div>
+              </div>
+            )}
+
+            {/* DIVERSITY */}
+            <div className="text-sm">
+              <div className="flex items-center gap-2">
+                <span className="font-bold">Diversity</span>
+                <select
+                  className="h-8 rounded-md border border-input bg-transparent px-2 text-sm"
+                  value={embeddingModel}
+                  onChange={(e) => setEmbeddingModel(e.target.value)}
+                >
+                  <option value="">Lexical only</option>
+                  {models.data?.map((model) => (
+                    <option key={model} value={model}>
+                      {model}
+                    </option>
+                  ))}
+                </select>
+                <Button
+                  variant="outline"
+                  size="sm"
+                  disabled={isAnalyzing}
+                  onClick={handleDiversity}
+                >
+                  {isAnalyzing ? "Analyzing..." : "Analyze"}
+                </Button>
+              </div>
+              {diversity && diversity.cells.length === 0 && (
+                <div className="px-2 text-gray-500">
+                  No cell has more than one response; run more than one
+                  generation.
+                </div>
+              )}
+              {diversity && diversity.cells.length > 0 && (
+                <div className="grid grid-cols-[1fr_auto_auto_auto_auto] gap-x-4 gap-y-1 px-2 font-mono text-gray-700 dark:text-gray-400">
+                  <div>Configuration</div>
+                  <div>Diversity</div>
+                  <div>Jaccard</div>
+                  <div>Edit</div>
+                  <div>Embedding</div>
+                  {diversity.combinations.map((entry, idx) => {
+                    const cells = diversity.cells.filter(
+                      (cell) =>
+                        JSON.stringify(cell.combination) ===
+                        JSON.stringify(entry.combination),
+                    );
+                    const mean = (values: number[]) =>
+                      values.reduce((sum, v) => sum + v, 0) / values.length;
+                    const embedding = cells
+                      .map((cell) => cell.embedding_similarity)
+                      .filter((v): v is number => v !== null);
+                    return (
+                      <Fragment key={idx}>
+                        <div>
+                          {entry.combination.model} (temp{" "}
+                          {entry.combination.temperature.toFixed(2)}, top_p{" "}
+                          {entry.combination.top_p.toFixed(2)}
+                          {systemPromptLabel(entry.combination.system_prompt)})
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          mirostat_tau:{" "}
-                          {Number(inf.parameters.mirostat_tau).toFixed(2)}
+                        <div>{entry.diversity.toFixed(3)}</div>
+                        <div>
+                          {mean(
+                            cells.map((cell) => cell.jaccard_similarity),
+                          ).toFixed(2)}
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          mirostat_eta:{" "}
-                          {Number(inf.parameters.mirostat_eta).toFixed(2)}
+                        <div>
+                          {mean(
+                            cells.map((cell) => cell.edit_similarity),
+                          ).toFixed(2)}
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          seed: {Number(inf.parameters.seed)}
+                        <div>
+                          {embedding.length > 0
+                            ? mean(embedding).toFixed(2)
+                            : "-"}
                         </div>
-                      </div>
+                      </Fragment>
+                    );
+                  })}
+                </div>
+              )}
+            </div>
+
+            {/* GOLDEN SUITES */}
+            {suites.data && suites.data.length > 0 && (
+              <div className="flex items-center gap-2 text-sm">
+                <span className="font-bold">Golden suite</span>
+                <select
+                  className="h-8 rounded-md border border-input bg-transparent px-2 text-sm"
+                  value={suiteName}
+                  onChange={(e) => setSuiteName(e.target.value)}
+                >
+                  <option value="">Select a suite</option>
+                  {suites.data.map((suite) => (
+                    <option key={suite.name} value={suite.name}>
+                      {suite.name}
+                    </option>
+                  ))}
+                </select>
+                <Button
+                  variant="outline"
+                  size="sm"
+                  disabled={!suiteName || !data}
+                  onClick={handleAddBaselines}
+                >
+                  Add inferences as baselines
+                </Button>
+              </div>
+            )}
 
-                      {/* Vertical line separator */}
-                      <div className="w-px bg-gray-200 dark:bg-gray-700"></div>
-                      {/* metadata */}
+            {/* REPRODUCIBILITY */}
+            {checks.data && checks.data.length > 0 && (
+              <div className="text-sm">
+                <div className="font-bold">Reproducibility</div>
+                <div className="grid grid-cols-[1fr_auto_auto_auto] gap-x-4 gap-y-1 px-2 font-mono text-gray-700 dark:text-gray-400">
+                  <div>Model (digest)</div>
+                  <div>Ollama</div>
+                  <div>Identical runs</div>
+                  <div>Date</div>
+                  {checks.data.map((check) => (
+                    <Fragment key={check.id}>
                       <div>
-                        <div>Result Metadata</div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Created at:{" "}
-                          {convertToUTCString(inf.result.created_at)}
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Prompt Eval Count:{" "}
-                          {Number(inf.result.prompt_eval_count)} tokens
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Prompt Eval Duration:{" "}
-                          {formatInterval(
-                            convertNanosecondsToTime(
-                              inf.result.prompt_eval_duration,
-                            ),
-                          )}
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Eval Count: {inf.result.eval_count} tokens
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Eval Duration:{" "}
-                          {formatInterval(
-                            convertNanosecondsToTime(inf.result.eval_duration),
-                          )}
-                        </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Inference Duration (prompt + eval):{" "}
-                          {formatInterval(
-                            convertNanosecondsToTime(
-                              inf.result.eval_duration +
-                                inf.result.prompt_eval_duration,
-                            ),
-                          )}
+                        {check.model} ({check.model_digest?.slice(0, 19) ?? "-"}
+                        )
+                      </div>
+                      <div>{check.ollama_version ?? "-"}</div>
+                      <div
+                        className={
+                          check.identical_count === check.run_count
+                            ? "text-green-600"
+                            : "text-red-500"
+                        }
+                      >
+                        {check.identical_count}/{check.run_count}
+                        {check.concurrent && " (concurrent)"}
+                      </div>
+                      <div>
+                        {new Date(check.date_created * 1000).toLocaleString()}
+                      </div>
+                    </Fragment>
+                  ))}
+                </div>
+              </div>
+            )}
+
+            {/* CONFIG */}
+            <div className="text-sm">
+              <div className="font-bold">Settings</div>
+
+              {/* main settings table */}
+              <div className="grid grid-cols-[180px_minmax(180px,_auto)] gap-0">
+                <div className="flex flex-col">
+                  <div className="p-2">Server URL</div>
+                  <div className="p-2">Timeout</div>
+                  <div className="p-2">Default System Prompt</div>
+                </div>
+                <div className="flex flex-col">
+                  <div className="p-2">{data.config.server_url}</div>
+                  <div className="p-2">{data.config.request_timeout}</div>
+                  <div className="p-2 text-amber-600 dark:text-amber-500">
+                    {data.config.system_prompt}
+                  </div>
+                </div>
+              </div>
+
+              {/* options table */}
+              <div className="grid  grid-cols-[180px_minmax(180px,_auto)] gap-0">
+                <div className="flex flex-col">
+                  <div className="p-2">Default Options</div>
+                </div>
+                <div className="flex flex-col">
+                  <div className="p-2 font-mono  text-gray-700 dark:text-gray-400">
+                    <pre>
+                      {Object.entries(data.config.default_options || {}).map(
+                        ([key, value]) => (
+                          <div key={key}>
+                            {key}: {JSON.stringify(value)}
+                          </div>
+                        ),
+                      )}
+                    </pre>
+                  </div>
+                </div>
+              </div>
+            </div>
+
+            <Separator className="my-3" />
+
+            {/* inferences */}
+            <div className="text-sm">
+              <div className="font-bold">Inferences</div>
+
+              {/* inferences table */}
+              <div>
+                {data.inferences.map((inf: any, index: number) => (
+                  <div key={index} className="my-8">
+                    <div className="font-bold">
+                      [{index + 1}/{data.inferences.length}]{" "}
+                      {inf.parameters.model}
+                    </div>
+                    <div className="m-4">
+                      <div>Inference Prompts</div>
+                      <div className="whitespace-pre-wrap font-mono text-gray-700 dark:text-gray-400">
+                        System prompt:{" "}
+                        <span className="text-amber-600 dark:text-amber-500">
+                          {inf.parameters.system_prompt}
+                        </span>
+                      </div>
+                      <div className="whitespace-pre-wrap font-mono text-gray-700 dark:text-gray-400">
+                        Prompt:{" "}
+                        <span className="text-green-600 dark:text-green-500">
+                          {inf.parameters.prompt}
+                        </span>
+                      </div>
+                    </div>
+                    {inf.error && (
+                      <div className="m-4">
+                        <div>Error</div>
+                        <div className="whitespace-pre-wrap text-red-500">
+                          {inf.error}
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Total Duration:{" "}
-                          {formatInterval(
-                            convertNanosecondsToTime(inf.result.total_duration),
-                          )}
+                      </div>
+                    )}
+                    {inf.result && (
+                      <div className="m-4">
+                        <div>Response</div>
+                        <div className="whitespace-pre-wrap text-cyan-600 dark:text-cyan-600">
+                          {inf.result.response}
                         </div>
-                        <div className="font-mono text-gray-700 dark:text-gray-400">
-                          Throughput (tokens/total_duration):{" "}
-                          {tokensPerSecond(
-                            inf.result.total_duration,
-                            inf.result.eval_count,
-                          )}{" "}
-                          tokens/s
+                      </div>
+                    )}
+                    {/* parameters and metadata */}
+                    <div>
+                      <div className="ml-4 flex gap-8">
+                        <div>
+                          <div>Inference Parameters</div>
+
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            temperature:{" "}
+                            {Number(inf.parameters.temperature).toFixed(2)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            repeat_penalty:{" "}
+                            {Number(inf.parameters.repeat_penalty).toFixed(2)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            top_k: {Number(inf.parameters.top_k).toFixed(2)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            top_p: {Number(inf.parameters.top_p).toFixed(2)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            repeat_last_n: {Number(inf.parameters.repeat_last_n)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            tfs_z: {Number(inf.parameters.tfs_z).toFixed(2)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            mirostat: {Number(inf.parameters.mirostat)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            mirostat_tau:{" "}
+                            {Number(inf.parameters.mirostat_tau).toFixed(2)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            mirostat_eta:{" "}
+                            {Number(inf.parameters.mirostat_eta).toFixed(2)}
+                          </div>
+                          <div className="font-mono text-gray-700 dark:text-gray-400">
+                            seed: {Number(inf.parameters.seed)}
+                          </div>
                         </div>
+
+                        {/* Vertical line separator */}
+                        <div className="w-px bg-gray-200 dark:bg-gray-700"></div>
+                        {/* metadata */}
+                        {inf.result && (
+                          <div>
+                            <div>Result Metadata</div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Created at:{" "}
+                              {convertToUTCString(inf.result.created_at)}
+                            </div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Prompt Eval Count:{" "}
+                              {Number(inf.result.prompt_eval_count)} tokens
+                            </div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Prompt Eval Duration:{" "}
+                              {formatInterval(
+                                convertNanosecondsToTime(
+                                  inf.result.prompt_eval_duration,
+                                ),
+                              )}
+                            </div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Eval Count: {inf.result.eval_count} tokens
+                            </div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Eval Duration:{" "}
+                              {formatInterval(
+                                convertNanosecondsToTime(inf.result.eval_duration),
+                              )}
+                            </div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Inference Duration (prompt + eval):{" "}
+                              {formatInterval(
+                                convertNanosecondsToTime(
+                                  inf.result.eval_duration +
+                                    inf.result.prompt_eval_duration,
+                                ),
+                              )}
+                            </div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Total Duration:{" "}
+                              {formatInterval(
+                                convertNanosecondsToTime(inf.result.total_duration),
+                              )}
+                            </div>
+                            <div className="font-mono text-gray-700 dark:text-gray-400">
+                              Throughput (tokens/total_duration):{" "}
+                              {tokensPerSecond(
+                                inf.result.total_duration,
+                                inf.result.eval_count,
+                              )}{" "}
+                              tokens/s
+                            </div>
+                          </div>
+                        )}
                       </div>
                     </div>
                   </div>
-                </div>
-              ))}
+                ))}
+              </div>
             </div>
           </div>
-        </div>
+        )}
         {/* <DialogFooter>
           <div className="flex w-full items-center justify-around"></div>
         </DialogFooter> */}
@@ -0,0 +1,119 @@
+import { Pencil1Icon } from "@radix-ui/react-icons";
+
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Textarea } from "@/components/ui/textarea";
+import { toast } from "@/components/ui/use-toast";
+
+import { IExperimentSummary } from "@/Interfaces";
+import {
+  rename_experiment,
+  set_experiment_description,
+  set_experiment_tags,
+} from "@/components/queries";
+import { useQueryClient } from "@tanstack/react-query";
+import { useEffect, useState } from "react";
+
+interface IProps {
+  experiment: IExperimentSummary;
+}
+
+export function ExperimentEditDialog(props: IProps) {
+  const { experiment } = props;
+  const queryClient = useQueryClient();
+  const [open, setOpen] = useState(false);
+  const [name, setName] = useState(experiment.name);
+  const [tags, setTags] = useState(experiment.tags.join(", "));
+  const [description, setDescription] = useState(experiment.description);
+
+  useEffect(() => {
+    if (open) {
+      setName(experiment.name);
+      setTags(experiment.tags.join(", "));
+      setDescription(experiment.description);
+    }
+  }, [open, experiment]);
+
+  const handleSave = async () => {
+    try {
+      if (name !== experiment.name) {
+        await rename_experiment(experiment.experiment_uuid, name);
+      }
+      await set_experiment_tags(experiment.experiment_uuid, tags.split(","));
+      await set_experiment_description(experiment.experiment_uuid, description);
+
+      queryClient.refetchQueries({ queryKey: ["list_experiments"] });
+      setOpen(false);
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not update the experiment.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={setOpen}>
+      <DialogTrigger asChild>
+        <Button variant="ghost" size="icon">
+          <Pencil1Icon className="h-4 w-4" />
+        </Button>
+      </DialogTrigger>
+      <DialogContent className="sm:max-w-[600px]">
+        <DialogHeader>
+          <DialogTitle>Edit experiment</DialogTitle>
+          <DialogDescription>{experiment.experiment_uuid}</DialogDescription>
+        </DialogHeader>
+
+        <div className="flex flex-col gap-4">
+          <div className="flex flex-col gap-2">
+            <Label htmlFor="experiment-name">Name</Label>
+            <Input
+              id="experiment-name"
+              value={name}
+              onChange={(e) => setName(e.target.value)}
+            />
+          </div>
+          <div className="flex flex-col gap-2">
+            <Label htmlFor="experiment-tags">Tags</Label>
+            <Input
+              id="experiment-tags"
+              placeholder="Comma separated, e.g.: baseline, summarization"
+              value={tags}
+              onChange={(e) => setTags(e.target.value)}
+            />
+          </div>
+          <div className="flex flex-col gap-2">
+            <Label htmlFor="experiment-description">Description</Label>
+            <Textarea
+              id="experiment-description"
+              className="min-h-[160px] font-mono"
+              placeholder="Notes about this experiment (markdown)"
+              value={description}
+              onChange={(e) => setDescription(e.target.value)}
+            />
+          </div>
+        </div>
+
+        <DialogFooter>
+          <Button variant="secondary" onClick={() => setOpen(false)}>
+            Cancel
+          </Button>
+          <Button onClick={handleSave}>Save</Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
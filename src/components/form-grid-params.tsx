@@ -1,9 +1,15 @@
-import { formValuesAtom } from "@/Atoms";
+import { configAtom, formValuesAtom } from "@/Atoms";
+import { IExperimentSpec, TThinkMode } from "@/Interfaces";
+import DatasetSelector from "@/components/Selectors/DatasetSelector";
+import EvaluatorSelector from "@/components/Selectors/EvaluatorSelector";
+import JudgeSelector from "@/components/Selectors/JudgeSelector";
 import ModelSelector from "@/components/Selectors/ModelSelector";
 import PromptSelector from "@/components/Selectors/PromptSelector";
+import ScriptSelector from "@/components/Selectors/ScriptSelector";
 import SystemPromptSelector from "@/components/Selectors/SystemPromptSelector";
 import { useConfirm } from "@/components/ui/alert-dialog-provider";
 import { Button } from "@/components/ui/button";
+import { Checkbox } from "@/components/ui/checkbox";
 import {
   Form,
   FormControl,
@@ -21,10 +27,25 @@ import {
   TooltipTrigger,
 } from "@/components/ui/tooltip";
 import { useToast } from "@/components/ui/use-toast";
+import {
+  get_evaluators,
+  load_dataset,
+  load_experiment_spec,
+  save_experiment_spec,
+  set_experiment_evaluators,
+  set_experiment_judge,
+  set_experiment_scripts,
+  validate_dataset_prompts,
+} from "@/components/queries";
 import { isCommaDelimitedList } from "@/lib";
 import { zodResolver } from "@hookform/resolvers/zod";
-import { InfoCircledIcon } from "@radix-ui/react-icons";
+import {
+  DownloadIcon,
+  InfoCircledIcon,
+  UploadIcon,
+} from "@radix-ui/react-icons";
 import { useIsFetching, useQueryClient } from "@tanstack/react-query";
+import { open, save } from "@tauri-apps/api/dialog";
 import { useAtom } from "jotai";
 import { useEffect } from "react";
 import { useForm } from "react-hook-form";
@@ -74,8 +95,18 @@ export const ParamsFormSchema = z.object({
     .refine((arr) => !arr.some((val) => val.trim() === ""), {
       message: "All prompts must have content.",
     }),
-  system_prompt: z.string(),
+  prompt_uuids: z.string().nullable().array(),
+  system_prompts: z.string().array().nonempty(),
+  dataset_path: z.string(),
+  dataset_rows: z.record(z.unknown()).array(),
+  judge_model: z.string(),
+  judge_rubric: z.string(),
+  evaluators: z.array(z.string()),
+  scripts: z.array(z.string()),
   generations: z.coerce.number().int().min(1),
+  thinkList: z
+    .array(z.boolean().nullable())
+    .min(1, "Select at least one think mode"),
   temperatureList: z.custom(
     (value) => validateNumberOrArray("float")(value as string | number),
     {
@@ -132,6 +163,33 @@ export const ParamsFormSchema = z.object({
   ),
 });
 
+// "Default" sends no think option, leaving it to the model
+const THINK_MODES: { label: string; value: boolean | null }[] = [
+  { label: "Default", value: null },
+  { label: "On", value: true },
+  { label: "Off", value: false },
+];
+
+// Set by every iteration of the grid, so specs don't repeat them as options
+const GRID_OPTIONS = [
+  "temperature",
+  "repeat_penalty",
+  "top_k",
+  "top_p",
+  "repeat_last_n",
+  "tfs_z",
+  "mirostat",
+  "mirostat_tau",
+  "mirostat_eta",
+];
+
+// Spec files use "default" for null, as TOML has no null
+const toThinkMode = (value: boolean | null): TThinkMode =>
+  value === null ? "default" : value ? "on" : "off";
+
+const fromThinkMode = (mode: TThinkMode): boolean | null =>
+  mode === "default" ? null : mode === "on";
+
 /**
  * Takes a list of values which can be a scalar number, an array of numbers and/or strings,
  * or a comma-separated string of numbers and/or strings, and returns a flattened array
@@ -188,6 +246,7 @@ export default function FormGridParams() {
   const isFetching = useIsFetching({ queryKey: ["get_inference"] });
   const { toast } = useToast();
   const [formValues, setFormValues] = useAtom(formValuesAtom);
+  const [config, setConfig] = useAtom(configAtom);
   const confirm = useConfirm();
 
   // Initiates for fields with value set in Settings > default options
@@ -196,7 +255,8 @@ export default function FormGridParams() {
     defaultValues: {
       experiment_uuid: uuidv4(),
       prompts: formValues.prompts,
-      system_prompt: formValues.system_prompt,
+      prompt_uuids: formValues.prompt_uuids,
+      system_prompts: formValues.system_prompts,
       models: [],
       temperatureList: arrayToFormValue(formValues.temperatureList),
       repeatPenaltyList: arrayToFormValue(formValues.repeatPenaltyList),
@@ -207,7 +267,14 @@ export default function FormGridParams() {
       mirostatList: arrayToFormValue(formValues.mirostatList),
       mirostatTauList: arrayToFormValue(formValues.mirostatTauList),
       mirostatEtaList: arrayToFormValue(formValues.mirostatEtaList),
+      thinkList: formValues.thinkList,
       generations: formValues.generations,
+      dataset_path: formValues.dataset_path,
+      dataset_rows: formValues.dataset_rows,
+      judge_model: formValues.judge_model,
+      judge_rubric: formValues.judge_rubric,
+      evaluators: formValues.evaluators,
+      scripts: formValues.scripts,
     },
   });
 
@@ -216,7 +283,8 @@ export default function FormGridParams() {
     form.reset({
       // experiment_uuid: formValues.experiment_uuid,
       prompts: formValues.prompts,
-      system_prompt: formValues.system_prompt,
+      prompt_uuids: formValues.prompt_uuids,
+      system_prompts: formValues.system_prompts,
       models: formValues.models,
       temperatureList: arrayToFormValue(formValues.temperatureList),
       repeatPenaltyList: arrayToFormValue(formValues.repeatPenaltyList),
@@ -227,18 +295,90 @@ export default function FormGridParams() {
       mirostatList: arrayToFormValue(formValues.mirostatList),
       mirostatTauList: arrayToFormValue(formValues.mirostatTauList),
       mirostatEtaList: arrayToFormValue(formValues.mirostatEtaList),
+      thinkList: formValues.thinkList,
       generations: formValues.generations,
+      dataset_path: formValues.dataset_path,
+      dataset_rows: formValues.dataset_rows,
+      judge_model: formValues.judge_model,
+      judge_rubric: formValues.judge_rubric,
+      evaluators: formValues.evaluators,
+      scripts: formValues.scripts,
     });
   }, [formValues, form]);
 
-  function onSubmit(data: z.infer<typeof ParamsFormSchema>) {
+  async function onSubmit(data: z.infer<typeof ParamsFormSchema>) {
+    // every [placeholder] in the prompts must be filled by a dataset column
+    if (data.dataset_path) {
+      try {
+        await validate_dataset_prompts(data.dataset_path, data.prompts);
+      } catch (err) {
+        toast({
+          variant: "destructive",
+          title: "Prompts don't match the dataset.",
+          description: String(err),
+          duration: 5000,
+        });
+        return;
+      }
+    }
+
+    // regenerate uuid for this experiment so all results are refreshed
+    const experiment_uuid = uuidv4();
+
+    // the judge is set before the first inference, so it grades every one
+    if (data.judge_model) {
+      try {
+        await set_experiment_judge(experiment_uuid, {
+          model: data.judge_model,
+          rubric: data.judge_rubric,
+        });
+      } catch (err) {
+        toast({
+          variant: "destructive",
+          title: "Could not set the judge.",
+          description: String(err),
+          duration: 5000,
+        });
+        return;
+      }
+    }
+
+    // same for the evaluators, which only score the experiments they're set on
+    if (data.evaluators.length > 0) {
+      try {
+        await set_experiment_evaluators(experiment_uuid, data.evaluators);
+      } catch (err) {
+        toast({
+          variant: "destructive",
+          title: "Could not set the evaluators.",
+          description: String(err),
+          duration: 5000,
+        });
+        return;
+      }
+    }
+
+    // and for the scripts, which rewrite prompts and responses
+    if (data.scripts.length > 0) {
+      try {
+        await set_experiment_scripts(experiment_uuid, data.scripts);
+      } catch (err) {
+        toast({
+          variant: "destructive",
+          title: "Could not set the scripts.",
+          description: String(err),
+          duration: 5000,
+        });
+        return;
+      }
+    }
+
     // ! clear previous results (keep queries sequential)
     queryClient.removeQueries({ queryKey: ["get_inference"] });
 
-    // regenerate uuid for this experiment so all results are refreshed
     setFormValues({
       ...data,
-      experiment_uuid: uuidv4(),
+      experiment_uuid,
       temperatureList: formValueToArray(data.temperatureList),
       repeatPenaltyList: formValueToArray(data.repeatPenaltyList),
       topKList: formValueToArray(data.topKList),
@@ -257,6 +397,152 @@ export default function FormGridParams() {
     });
   }
 
+  // Saves the grid in the form, with the server settings, as a spec file
+  async function handleSaveSpec() {
+    const data = form.getValues();
+    const path = await save({
+      defaultPath: "experiment.toml",
+      filters: [
+        { name: "TOML", extensions: ["toml"] },
+        { name: "YAML", extensions: ["yaml", "yml"] },
+        { name: "JSON", extensions: ["json"] },
+      ],
+    });
+    if (!path) return;
+
+    try {
+      const spec: IExperimentSpec = {
+        version: 1,
+        server: {
+          server_url: config.server_url,
+          request_timeout: config.request_timeout,
+          default_options: Object.fromEntries(
+            Object.entries(config.default_options).filter(
+              ([key]) => !GRID_OPTIONS.includes(key),
+            ),
+          ),
+        },
+        models: data.models,
+        prompts: data.prompts,
+        system_prompts: data.system_prompts,
+        dataset: data.dataset_path || null,
+        temperature: formValueToArray(data.temperatureList),
+        repeat_penalty: formValueToArray(data.repeatPenaltyList),
+        top_k: formValueToArray(data.topKList),
+        top_p: formValueToArray(data.topPList),
+        repeat_last_n: formValueToArray(data.repeatLastNList),
+        tfs_z: formValueToArray(data.tfsZList),
+        mirostat: formValueToArray(data.mirostatList),
+        mirostat_tau: formValueToArray(data.mirostatTauList),
+        mirostat_eta: formValueToArray(data.mirostatEtaList),
+        think: data.thinkList.map(toThinkMode),
+        generations: Number(data.generations),
+        judge: data.judge_model
+          ? { model: data.judge_model, rubric: data.judge_rubric }
+          : null,
+        scripts: data.scripts,
+        evaluators: data.evaluators,
+        concurrency: config.concurrent_inferences,
+      };
+      await save_experiment_spec(path, spec);
+      toast({
+        variant: "success",
+        title: "Experiment spec saved.",
+        description: path,
+        duration: 2500,
+      });
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not save the experiment spec.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  }
+
+  // Fills the form from a spec file, and the settings from its server profile
+  async function handleLoadSpec() {
+    const path = await open({
+      multiple: false,
+      filters: [
+        {
+          name: "Experiment spec",
+          extensions: ["toml", "yaml", "yml", "json"],
+        },
+      ],
+    });
+    if (!path || Array.isArray(path)) return;
+
+    try {
+      const spec = await load_experiment_spec(path);
+      const dataset = spec.dataset ? await load_dataset(spec.dataset) : null;
+      const enabled = (await get_evaluators())
+        .filter((e) => e.enabled)
+        .map((e) => e.name);
+      const missing = spec.evaluators.filter(
+        (name) => !enabled.includes(name),
+      );
+
+      setConfig({
+        ...config,
+        server_url: spec.server.server_url ?? config.server_url,
+        request_timeout: spec.server.request_timeout ?? config.request_timeout,
+        concurrent_inferences: spec.concurrency,
+        default_options: {
+          ...config.default_options,
+          ...spec.server.default_options,
+        },
+      });
+      setFormValues({
+        // like a cloned experiment, it only runs when the form is submitted
+        experiment_uuid: "",
+        models: spec.models,
+        // load_experiment_spec replaces prompts given by slug with their text
+        prompts: spec.prompts.map((p) =>
+          typeof p === "string" ? p : p.slug,
+        ),
+        prompt_uuids: spec.prompt_uuids,
+        system_prompts: spec.system_prompts,
+        temperatureList: spec.temperature,
+        repeatPenaltyList: spec.repeat_penalty,
+        topKList: spec.top_k,
+        topPList: spec.top_p,
+        repeatLastNList: spec.repeat_last_n,
+        tfsZList: spec.tfs_z,
+        mirostatList: spec.mirostat,
+        mirostatTauList: spec.mirostat_tau,
+        mirostatEtaList: spec.mirostat_eta,
+        thinkList: spec.think.map(fromThinkMode),
+        generations: spec.generations,
+        dataset_path: dataset?.path ?? "",
+        dataset_rows: dataset?.rows ?? [],
+        judge_model: spec.judge?.model ?? "",
+        judge_rubric: spec.judge?.rubric ?? "",
+        // only the enabled ones can be selected in the form
+        evaluators: spec.evaluators.filter((name) =>
+          enabled.includes(name),
+        ),
+        scripts: spec.scripts,
+      });
+      toast({
+        title: "The Form has been updated with the experiment spec.",
+        description:
+          missing.length > 0
+            ? `These evaluators of the spec aren't enabled: ${missing.join(", ")}`
+            : undefined,
+        duration: missing.length > 0 ? 5000 : 2500,
+      });
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not load the experiment spec.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  }
+
   useEffect(() => {
     form.trigger();
   }, [form]);
@@ -272,6 +558,10 @@ export default function FormGridParams() {
             <ModelSelector form={form} />
             <PromptSelector form={form} />
             <SystemPromptSelector form={form} />
+            <DatasetSelector form={form} />
+            <JudgeSelector form={form} />
+            <EvaluatorSelector form={form} />
+            <ScriptSelector form={form} />
 
             {/* generations */}
             <div className="flex flex-col gap-2">
@@ -601,6 +891,49 @@ export default function FormGridParams() {
                 )}
               />
             </div>
+            {/* think */}
+            <div className="flex flex-col gap-2">
+              <FormField
+                control={form.control}
+                name="thinkList"
+                render={({ field }) => (
+                  <FormItem>
+                    <FormLabel className="font-bold">Think Mode</FormLabel>
+                    <div className="flex gap-4">
+                      {THINK_MODES.map(({ label, value }) => (
+                        <div
+                          key={label}
+                          className="flex flex-row items-center space-x-2"
+                        >
+                          <FormControl>
+                            <Checkbox
+                              checked={field.value.includes(value)}
+                              onCheckedChange={(checked: boolean) =>
+                                field.onChange(
+                                  checked
+                                    ? [...field.value, value]
+                                    : field.value.filter(
+                                        (v: boolean | null) => v !== value,
+                                      ),
+                                )
+                              }
+                            />
+                          </FormControl>
+                          <FormLabel className="text-sm font-normal">
+                            {label}
+                          </FormLabel>
+                        </div>
+                      ))}
+                    </div>
+                    <FormDescription>
+                      For reasoning models. Their reasoning is kept apart from
+                      the answer.
+                    </FormDescription>
+                    <FormMessage />
+                  </FormItem>
+                )}
+              />
+            </div>
             {/* ===================================== */}
             {/* Buttons */}
             <div
@@ -640,6 +973,35 @@ export default function FormGridParams() {
                 >
                   Stop Experiment
                 </Button>
+
+                <Tooltip>
+                  <TooltipTrigger asChild>
+                    <Button
+                      type="button"
+                      variant="outline"
+                      size="icon"
+                      disabled={!!isFetching}
+                      onClick={handleLoadSpec}
+                    >
+                      <UploadIcon className="h-4 w-4" />
+                    </Button>
+                  </TooltipTrigger>
+                  <TooltipContent>Load experiment spec</TooltipContent>
+                </Tooltip>
+
+                <Tooltip>
+                  <TooltipTrigger asChild>
+                    <Button
+                      type="button"
+                      variant="outline"
+                      size="icon"
+                      onClick={handleSaveSpec}
+                    >
+                      <DownloadIcon className="h-4 w-4" />
+                    </Button>
+                  </TooltipTrigger>
+                  <TooltipContent>Save experiment spec</TooltipContent>
+                </Tooltip>
               </div>
             </div>
           </form>
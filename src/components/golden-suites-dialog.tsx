@@ -0,0 +1,400 @@
+import { CrossCircledIcon, TargetIcon } from "@radix-ui/react-icons";
+
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { toast } from "@/components/ui/use-toast";
+
+import { configAtom } from "@/Atoms";
+import {
+  IGoldenBaseline,
+  IGoldenSuite,
+  IGoldenSuiteInput,
+  IRegressionRun,
+  IThresholds,
+} from "@/Interfaces";
+import {
+  delete_golden_baseline,
+  delete_golden_suite,
+  get_golden_baselines,
+  get_golden_suites,
+  get_models,
+  get_regression_runs,
+  run_golden_suite,
+  save_golden_suite,
+} from "@/components/queries";
+import { useQuery, useQueryClient } from "@tanstack/react-query";
+import { useAtom } from "jotai";
+import { Fragment, useState } from "react";
+import { Tooltip, TooltipContent, TooltipTrigger } from "./ui/tooltip";
+
+const EMPTY_SUITE: IGoldenSuiteInput = {
+  name: "",
+  description: "",
+  thresholds: {
+    min_edit_similarity: 0.8,
+    min_jaccard_similarity: null,
+    embedding_model: null,
+    min_embedding_similarity: null,
+  },
+};
+
+const THRESHOLDS: {
+  key: Exclude<keyof IThresholds, "embedding_model">;
+  label: string;
+}[] = [
+  { key: "min_edit_similarity", label: "Min. edit similarity" },
+  { key: "min_jaccard_similarity", label: "Min. Jaccard similarity" },
+  { key: "min_embedding_similarity", label: "Min. embedding similarity" },
+];
+
+const formatSimilarity = (value: number | null) =>
+  value === null ? "-" : value.toFixed(2);
+
+function RegressionReport({ run }: { run: IRegressionRun }) {
+  return (
+    <div className="grid grid-cols-[auto_1fr_auto_auto_auto_auto_auto] gap-x-3 gap-y-1 font-mono text-xs text-gray-700 dark:text-gray-400">
+      <div>Result</div>
+      <div>Prompt</div>
+      <div>Model</div>
+      <div>Drift</div>
+      <div>Edit</div>
+      <div>Jaccard</div>
+      <div>Embedding</div>
+      {run.results.map((result) => (
+        <Fragment key={result.baseline_id}>
+          <div className={result.passed ? "text-green-600" : "text-red-500"}>
+            {result.passed ? "pass" : result.error ? "error" : "drift"}
+          </div>
+          <div className="truncate" title={result.error ?? result.prompt}>
+            {result.prompt}
+          </div>
+          <div>{result.model}</div>
+          <div>{formatSimilarity(result.drift)}</div>
+          <div>{formatSimilarity(result.edit_similarity)}</div>
+          <div>{formatSimilarity(result.jaccard_similarity)}</div>
+          <div>{formatSimilarity(result.embedding_similarity)}</div>
+        </Fragment>
+      ))}
+    </div>
+  );
+}
+
+export function GoldenSuitesDialog() {
+  const [open, setOpen] = useState(false);
+  const [input, setInput] = useState<IGoldenSuiteInput>(EMPTY_SUITE);
+  const [selected, setSelected] = useState<string | null>(null);
+  const [model, setModel] = useState("");
+  const [isRunning, setIsRunning] = useState(false);
+  const [shownRun, setShownRun] = useState<IRegressionRun | null>(null);
+  const [config, __] = useAtom(configAtom);
+  const queryClient = useQueryClient();
+
+  const suites = useQuery<IGoldenSuite[]>({
+    queryKey: ["get_golden_suites"],
+    queryFn: get_golden_suites,
+    enabled: open,
+  });
+
+  // Same query as the model selector, so the list is shared
+  const models = useQuery<string[]>({
+    queryKey: ["get_models", config],
+    queryFn: (): Promise<string[]> => get_models(config),
+    enabled: open,
+  });
+
+  const baselines = useQuery<IGoldenBaseline[]>({
+    queryKey: ["get_golden_baselines", selected],
+    queryFn: () => get_golden_baselines(selected!),
+    enabled: open && selected !== null,
+  });
+
+  const runs = useQuery<IRegressionRun[]>({
+    queryKey: ["get_regression_runs", selected],
+    queryFn: () => get_regression_runs(selected!),
+    enabled: open && selected !== null,
+  });
+
+  const updateThresholds = (changes: Partial<IThresholds>) =>
+    setInput({ ...input, thresholds: { ...input.thresholds, ...changes } });
+
+  const selectSuite = (suite: IGoldenSuite) => {
+    setSelected(suite.name);
+    setShownRun(null);
+    setInput({
+      name: suite.name,
+      description: suite.description,
+      thresholds: suite.thresholds,
+    });
+  };
+
+  const showError = (title: string, err: unknown) =>
+    toast({
+      variant: "destructive",
+      title,
+      description: String(err),
+      duration: 5000,
+    });
+
+  const handleSave = async () => {
+    try {
+      await save_golden_suite(input);
+      suites.refetch();
+      setSelected(input.name.trim());
+    } catch (err) {
+      showError("Could not save the golden suite.", err);
+    }
+  };
+
+  const handleDelete = async (name: string) => {
+    try {
+      await delete_golden_suite(name);
+      suites.refetch();
+      if (selected === name) {
+        setSelected(null);
+        setInput(EMPTY_SUITE);
+      }
+    } catch (err) {
+      showError("Could not delete the golden suite.", err);
+    }
+  };
+
+  const handleDeleteBaseline = async (id: number) => {
+    try {
+      await delete_golden_baseline(id);
+      baselines.refetch();
+      suites.refetch();
+    } catch (err) {
+      showError("Could not remove the baseline.", err);
+    }
+  };
+
+  const handleRun = async () => {
+    if (!selected) return;
+    setIsRunning(true);
+    try {
+      const run = await run_golden_suite(config, selected, model || undefined);
+      setShownRun(run);
+      runs.refetch();
+      // The reruns are a new experiment
+      queryClient.refetchQueries({ queryKey: ["list_experiments"] });
+      toast({
+        variant:
+          run.passed_count === run.baseline_count ? "success" : "destructive",
+        title: `${run.passed_count} of ${run.baseline_count} baselines passed.`,
+        duration: 5000,
+      });
+    } catch (err) {
+      showError("Could not run the golden suite.", err);
+    } finally {
+      setIsRunning(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={setOpen}>
+      <Tooltip>
+        <TooltipTrigger asChild>
+          <DialogTrigger asChild>
+            <Button variant="transparentDark" size="icon">
+              <TargetIcon className="h-5 w-5" />
+            </Button>
+          </DialogTrigger>
+        </TooltipTrigger>
+        <TooltipContent>Golden suites</TooltipContent>
+      </Tooltip>
+      <DialogContent className="max-h-[90vh] overflow-y-auto sm:max-w-[900px]">
+        <DialogHeader>
+          <DialogTitle>Golden suites</DialogTitle>
+          <DialogDescription>
+            Baselines are inferences known to be good; add them from the data
+            of an experiment. Running a suite re-runs every baseline with the
+            same prompt and parameters and compares the new responses with
+            them. Reruns below any threshold drifted.
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="flex flex-col gap-1 text-sm">
+          {suites.data?.length === 0 && (
+            <div className="text-gray-500">No golden suites yet.</div>
+          )}
+          {suites.data?.map((suite) => (
+            <div
+              key={suite.name}
+              className="flex items-center justify-between gap-2"
+            >
+              <button
+                className={`truncate text-left ${selected === suite.name ? "text-cyan-600" : ""}`}
+                onClick={() => selectSuite(suite)}
+              >
+                <span className="font-bold">{suite.name}</span> (
+                {suite.baseline_count} baselines)
+                {suite.description && `: ${suite.description}`}
+              </button>
+              <Button
+                variant="ghost"
+                size="icon"
+                onClick={() => handleDelete(suite.name)}
+              >
+                <CrossCircledIcon className="h-4 w-4 text-red-500" />
+              </Button>
+            </div>
+          ))}
+        </div>
+
+        <div className="grid grid-cols-[180px_1fr] items-center gap-3 text-sm">
+          <Label htmlFor="suite-name">Name</Label>
+          <Input
+            id="suite-name"
+            placeholder="e.g.: summaries"
+            value={input.name}
+            onChange={(e) => setInput({ ...input, name: e.target.value })}
+          />
+          <Label htmlFor="suite-description">Description</Label>
+          <Input
+            id="suite-description"
+            placeholder="Optional"
+            value={input.description}
+            onChange={(e) =>
+              setInput({ ...input, description: e.target.value })
+            }
+          />
+          {THRESHOLDS.map(({ key, label }) => (
+            <Fragment key={key}>
+              <Label htmlFor={key}>{label}</Label>
+              <Input
+                id={key}
+                type="number"
+                step="0.05"
+                min={0}
+                max={1}
+                placeholder="Not checked"
+                value={input.thresholds[key] ?? ""}
+                onChange={(e) =>
+                  setInput({
+                    ...input,
+                    thresholds: {
+                      ...input.thresholds,
+                      [key]:
+                        e.target.value === "" ? null : Number(e.target.value),
+                    },
+                  })
+                }
+              />
+            </Fragment>
+          ))}
+          <Label htmlFor="suite-embedding-model">Embedding model</Label>
+          <select
+            id="suite-embedding-model"
+            className="h-9 rounded-md border border-input bg-transparent px-2 text-sm"
+            value={input.thresholds.embedding_model ?? ""}
+            onChange={(e) =>
+              updateThresholds({ embedding_model: e.target.value || null })
+            }
+          >
+            <option value="">None</option>
+            {models.data?.map((m) => (
+              <option key={m} value={m}>
+                {m}
+              </option>
+            ))}
+          </select>
+        </div>
+
+        {selected && (
+          <div className="flex flex-col gap-2 text-sm">
+            <div className="font-bold">Baselines of {selected}</div>
+            {baselines.data?.length === 0 && (
+              <div className="text-gray-500">
+                No baselines yet; add them from the data of an experiment.
+              </div>
+            )}
+            {baselines.data?.map((baseline) => (
+              <div
+                key={baseline.id}
+                className="flex items-center justify-between gap-2 font-mono text-xs"
+              >
+                <span className="truncate" title={baseline.response}>
+                  {baseline.parameters.model}: {baseline.rendered_prompt}
+                </span>
+                <Button
+                  variant="ghost"
+                  size="icon"
+                  onClick={() => handleDeleteBaseline(baseline.id)}
+                >
+                  <CrossCircledIcon className="h-4 w-4 text-red-500" />
+                </Button>
+              </div>
+            ))}
+
+            <div className="flex items-center gap-2">
+              <select
+                className="h-8 rounded-md border border-input bg-transparent px-2 text-sm"
+                value={model}
+                onChange={(e) => setModel(e.target.value)}
+              >
+                <option value="">Baseline models</option>
+                {models.data?.map((m) => (
+                  <option key={m} value={m}>
+                    {m}
+                  </option>
+                ))}
+              </select>
+              <Button
+                variant="outline"
+                size="sm"
+                disabled={isRunning || !baselines.data?.length}
+                onClick={handleRun}
+              >
+                {isRunning ? "Running..." : "Run suite"}
+              </Button>
+            </div>
+
+            {runs.data && runs.data.length > 0 && (
+              <div className="flex flex-col gap-1">
+                <div className="font-bold">Runs</div>
+                {runs.data.map((run) => (
+                  <button
+                    key={run.experiment_uuid}
+                    className="text-left font-mono text-xs"
+                    onClick={() => setShownRun(run)}
+                  >
+                    {new Date(run.date_created * 1000).toLocaleString()}{" "}
+                    &middot; {run.model ?? "baseline models"} &middot; Ollama{" "}
+                    {run.ollama_version ?? "?"} &middot;{" "}
+                    <span
+                      className={
+                        run.passed_count === run.baseline_count
+                          ? "text-green-600"
+                          : "text-red-500"
+                      }
+                    >
+                      {run.passed_count}/{run.baseline_count} passed
+                    </span>
+                  </button>
+                ))}
+              </div>
+            )}
+            {shownRun && <RegressionReport run={shownRun} />}
+          </div>
+        )}
+
+        <DialogFooter>
+          <Button variant="secondary" onClick={() => setOpen(false)}>
+            Close
+          </Button>
+          <Button onClick={handleSave}>Save suite</Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -9,6 +9,7 @@ interface IProps {
   className?: string;
   rows?: number;
   placeholder?: string;
+  library?: "prompts" | "system_prompts";
 }
 
 export function PromptTextArea(props: IProps) {
@@ -18,6 +19,7 @@ export function PromptTextArea(props: IProps) {
     className = "",
     rows = 4,
     placeholder = "Type '/' to search prompts...",
+    library = "prompts",
   } = props;
   const [showAutocomplete, setShowAutocomplete] = useState(false);
   const [selectedVariable, setSelectedVariable] = useState<{
@@ -29,7 +31,7 @@ export function PromptTextArea(props: IProps) {
 
   // Find all variables in the text
   const findVariables = (text: string) => {
-    const regex = /\[(\w+)\]/g;
+    const regex = /\[([A-Za-z_]\w+)\]/g;
     const variables: Array<{
       start: number;
       end: number;
@@ -136,6 +138,7 @@ export function PromptTextArea(props: IProps) {
         trigger={showAutocomplete}
         index={0}
         inputText={value}
+        library={library}
         onSelect={(newValue) => {
           onChange(newValue);
           setShowAutocomplete(false);
@@ -1,9 +1,46 @@
 import {
+  IBlindPair,
+  IChartSpec,
+  IComparisonSession,
+  IDataset,
   IDefaultConfigs,
+  IDiffLine,
+  IDiversityReport,
+  IEvaluator,
+  IEvaluatorInput,
   IExperimentFile,
+  IExperimentJudge,
+  IExperimentImportReport,
+  IExperimentPage,
+  IExperimentQuery,
+  IExperimentSpec,
+  IExperimentStats,
+  IGoldenBaseline,
+  IGoldenSuite,
+  IGoldenSuiteInput,
+  IInferenceRating,
+  IInferenceScore,
+  IJudgeInput,
+  ILeaderboardEntry,
+  ILoadedExperimentSpec,
   IPrompt,
+  IPromptImportReport,
+  IPromptSearch,
+  IPromptVersion,
+  IRatingInput,
+  IRegressionRun,
+  IReproducibilityCheck,
   IResponsePayload,
+  IScript,
+  IScriptInput,
+  ITagCount,
+  TAssertion,
+  TChartFormat,
+  TConflictPolicy,
+  TExportFormat,
+  TReportFormat,
   TParamIteration,
+  TVoteWinner,
 } from "@/Interfaces";
 import { invoke } from "@tauri-apps/api/tauri";
 
@@ -91,6 +128,61 @@ export async function get_all_prompts(): Promise<IPrompt[]> {
   return prompts;
 }
 
+/**
+ * Retrieves all system prompts.
+ *
+ * @return {Promise<IPrompt[]>} a promise that resolves to an array of system prompts
+ */
+export async function get_all_system_prompts(): Promise<IPrompt[]> {
+  const prompts = await invoke<IPrompt[]>("get_all_system_prompts");
+  return prompts;
+}
+
+/**
+ * Creates a system prompt in the database.
+ *
+ * @param {IPrompt} prompt - The system prompt to create.
+ * @return {Promise<void | Error>} The result of the operation.
+ */
+export async function create_system_prompt(
+  prompt: IPrompt,
+): Promise<void | Error> {
+  const createOp = await invoke<void | Error>("create_system_prompt", {
+    input: prompt,
+  });
+  return createOp;
+}
+
+/**
+ * Updates a system prompt in the database.
+ *
+ * @param {IPrompt} prompt - The system prompt to update.
+ * @return {Promise<void | Error>} The result of the operation.
+ */
+export async function update_system_prompt(
+  prompt: IPrompt,
+): Promise<void | Error> {
+  const updateOp = await invoke<void | Error>("update_system_prompt", {
+    input: prompt,
+  });
+  return updateOp;
+}
+
+/**
+ * Deletes a system prompt in the database.
+ *
+ * @param {string} uuid - The UUID of the system prompt to delete.
+ * @return {Promise<void | Error>} The result of the operation.
+ */
+export async function delete_system_prompt(
+  uuid: string,
+): Promise<void | Error> {
+  const deleteOp = await invoke<void | Error>("delete_system_prompt", {
+    uuid,
+  });
+  return deleteOp;
+}
+
 /**
  * Retrieves ollama version from the server.
  *
@@ -107,11 +199,951 @@ export async function get_ollama_version(
 }
 
 /**
- * Retrieves a list of experiments from the server.
+ * Retrieves one page of experiment summaries, without their contents.
+ *
+ * @param {IExperimentQuery} query - Pagination, sorting and filters.
+ * @return {Promise<IExperimentPage>} The matching experiments and their total count.
+ */
+export async function list_experiments(
+  query: IExperimentQuery,
+): Promise<IExperimentPage> {
+  const page = await invoke<IExperimentPage>("list_experiments", { query });
+  return page;
+}
+
+/**
+ * Retrieves the full contents of a single experiment.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @return {Promise<IExperimentFile>} The experiment file.
+ */
+export async function get_experiment(uuid: string): Promise<IExperimentFile> {
+  const experiment = await invoke<IExperimentFile>("get_experiment", { uuid });
+  return experiment;
+}
+
+/**
+ * Renames an experiment. Rejects if another experiment has the same name.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {string} name - The new name.
+ * @return {Promise<void>}
+ */
+export async function rename_experiment(
+  uuid: string,
+  name: string,
+): Promise<void> {
+  await invoke<void>("rename_experiment", { uuid, name });
+}
+
+/**
+ * Marks or unmarks an experiment as favorite.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {boolean} isFavorite - The new favorite flag.
+ * @return {Promise<void>}
+ */
+export async function set_experiment_favorite(
+  uuid: string,
+  isFavorite: boolean,
+): Promise<void> {
+  await invoke<void>("set_experiment_favorite", { uuid, isFavorite });
+}
+
+/**
+ * Sets the markdown description of an experiment.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {string} description - The markdown description.
+ * @return {Promise<void>}
+ */
+export async function set_experiment_description(
+  uuid: string,
+  description: string,
+): Promise<void> {
+  await invoke<void>("set_experiment_description", { uuid, description });
+}
+
+/**
+ * Replaces the tags of an experiment.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {string[]} tags - The new tags.
+ * @return {Promise<void>}
+ */
+export async function set_experiment_tags(
+  uuid: string,
+  tags: string[],
+): Promise<void> {
+  await invoke<void>("set_experiment_tags", { uuid, tags });
+}
+
+/**
+ * Retrieves every tag used by experiments, with usage counts.
+ *
+ * @return {Promise<ITagCount[]>} The tags.
+ */
+export async function get_experiment_tags(): Promise<ITagCount[]> {
+  const tags = await invoke<ITagCount[]>("get_experiment_tags");
+  return tags;
+}
+
+/**
+ * Summarizes an experiment per model and parameter combination,
+ * including aggregated ratings and scores.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {string} [rankBy] - The score name to rank by; the first one if unset.
+ * @return {Promise<IExperimentStats>} The experiment summary.
+ */
+export async function experiment_summary(
+  uuid: string,
+  rankBy?: string,
+): Promise<IExperimentStats> {
+  const stats = await invoke<IExperimentStats>("experiment_summary", {
+    uuid,
+    rankBy: rankBy || null,
+  });
+  return stats;
+}
+
+/**
+ * Measures how different the responses of each grid cell are across
+ * generations, lexically and, optionally, with an embedding model.
+ *
+ * @param {IDefaultConfigs} config - The server to request embeddings from.
+ * @param {string} uuid - The experiment uuid.
+ * @param {string} [embeddingModel] - The embedding model, if any.
+ * @return {Promise<IDiversityReport>} The diversity per cell and combination.
+ */
+export async function experiment_diversity(
+  config: IDefaultConfigs,
+  uuid: string,
+  embeddingModel?: string,
+): Promise<IDiversityReport> {
+  const report = await invoke<IDiversityReport>("experiment_diversity", {
+    config,
+    uuid,
+    embeddingModel: embeddingModel || null,
+  });
+  return report;
+}
+
+/**
+ * Sets (replaces) the rating and note of an inference.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @param {string} inferenceId - The inference id.
+ * @param {IRatingInput} input - Thumb, 1-5 rating, rubric scores and note.
+ * @return {Promise<void>}
+ */
+export async function set_inference_rating(
+  experimentUuid: string,
+  inferenceId: string,
+  input: IRatingInput,
+): Promise<void> {
+  await invoke<void>("set_inference_rating", {
+    experimentUuid,
+    inferenceId,
+    input,
+  });
+}
+
+/**
+ * Removes the rating and note of an inference.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @param {string} inferenceId - The inference id.
+ * @return {Promise<void>}
+ */
+export async function clear_inference_rating(
+  experimentUuid: string,
+  inferenceId: string,
+): Promise<void> {
+  await invoke<void>("clear_inference_rating", { experimentUuid, inferenceId });
+}
+
+/**
+ * Retrieves the ratings of an experiment's inferences.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<IInferenceRating[]>} The ratings.
+ */
+export async function get_inference_ratings(
+  experimentUuid: string,
+): Promise<IInferenceRating[]> {
+  const ratings = await invoke<IInferenceRating[]>("get_inference_ratings", {
+    experimentUuid,
+  });
+  return ratings;
+}
+
+/**
+ * Sets (replaces) the assertions checked against every response to a prompt.
+ *
+ * @param {string} uuid - The prompt uuid.
+ * @param {TAssertion[]} assertions - The assertions.
+ * @return {Promise<void>}
+ */
+export async function set_prompt_assertions(
+  uuid: string,
+  assertions: TAssertion[],
+): Promise<void> {
+  await invoke("set_prompt_assertions", { uuid, assertions });
+}
+
+/**
+ * Retrieves the assertions of a prompt.
+ *
+ * @param {string} uuid - The prompt uuid.
+ * @return {Promise<TAssertion[]>} The assertions.
+ */
+export async function get_prompt_assertions(
+  uuid: string,
+): Promise<TAssertion[]> {
+  const assertions = await invoke<TAssertion[]>("get_prompt_assertions", {
+    uuid,
+  });
+  return assertions;
+}
+
+/**
+ * Scores every successful inference of an experiment again.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<number>} The number of scores.
+ */
+export async function score_experiment(
+  experimentUuid: string,
+): Promise<number> {
+  const count = await invoke<number>("score_experiment", { experimentUuid });
+  return count;
+}
+
+/**
+ * Retrieves the scores of an experiment's inferences, from every source.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<IInferenceScore[]>} The scores.
+ */
+export async function get_inference_scores(
+  experimentUuid: string,
+): Promise<IInferenceScore[]> {
+  const scores = await invoke<IInferenceScore[]>("get_inference_scores", {
+    experimentUuid,
+  });
+  return scores;
+}
+
+/**
+ * Sets (replaces) the judge model and rubric of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @param {IJudgeInput} judge - The judge model, rubric and scale.
+ * @return {Promise<void>}
+ */
+export async function set_experiment_judge(
+  experimentUuid: string,
+  judge: IJudgeInput,
+): Promise<void> {
+  await invoke("set_experiment_judge", { experimentUuid, judge });
+}
+
+/**
+ * Retrieves the judge of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<IExperimentJudge | null>} The judge, if any.
+ */
+export async function get_experiment_judge(
+  experimentUuid: string,
+): Promise<IExperimentJudge | null> {
+  const judge = await invoke<IExperimentJudge | null>(
+    "get_experiment_judge",
+    { experimentUuid },
+  );
+  return judge;
+}
+
+/**
+ * Removes the judge of an experiment; the scores it gave are kept.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<void>}
+ */
+export async function delete_experiment_judge(
+  experimentUuid: string,
+): Promise<void> {
+  await invoke("delete_experiment_judge", { experimentUuid });
+}
+
+/**
+ * Judges every successful inference of an experiment again.
+ *
+ * @param {IDefaultConfigs} config - The server config.
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<number>} The number of inferences the judge scored.
+ */
+export async function judge_experiment(
+  config: IDefaultConfigs,
+  experimentUuid: string,
+): Promise<number> {
+  const count = await invoke<number>("judge_experiment", {
+    config,
+    experimentUuid,
+  });
+  return count;
+}
+
+/**
+ * Registers an evaluator, or replaces the one with the same name.
+ *
+ * @param {IEvaluatorInput} input - The evaluator.
+ * @return {Promise<void>}
+ */
+export async function save_evaluator(input: IEvaluatorInput): Promise<void> {
+  await invoke("save_evaluator", { input });
+}
+
+/**
+ * Retrieves every registered evaluator.
+ *
+ * @return {Promise<IEvaluator[]>} The evaluators, by name.
+ */
+export async function get_evaluators(): Promise<IEvaluator[]> {
+  const evaluators = await invoke<IEvaluator[]>("get_evaluators");
+  return evaluators;
+}
+
+/**
+ * Removes an evaluator; the scores it gave are kept.
+ *
+ * @param {string} name - The evaluator name.
+ * @return {Promise<void>}
+ */
+export async function delete_evaluator(name: string): Promise<void> {
+  await invoke("delete_evaluator", { name });
+}
+
+/**
+ * Sets (replaces) the evaluators that score each inference of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @param {string[]} names - The evaluator names.
+ * @return {Promise<void>}
+ */
+export async function set_experiment_evaluators(
+  experimentUuid: string,
+  names: string[],
+): Promise<void> {
+  await invoke("set_experiment_evaluators", { experimentUuid, names });
+}
+
+/**
+ * Retrieves the enabled evaluators of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<string[]>} The evaluator names.
+ */
+export async function get_experiment_evaluators(
+  experimentUuid: string,
+): Promise<string[]> {
+  const names = await invoke<string[]>("get_experiment_evaluators", {
+    experimentUuid,
+  });
+  return names;
+}
+
+/**
+ * Runs the enabled evaluators of an experiment on every successful inference
+ * of the experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<number>} The number of scores with a value.
+ */
+export async function evaluate_experiment(
+  experimentUuid: string,
+): Promise<number> {
+  const count = await invoke<number>("evaluate_experiment", {
+    experimentUuid,
+  });
+  return count;
+}
+
+/**
+ * Saves a script, or replaces the one with the same name. The script must
+ * compile and define a hook.
+ *
+ * @param {IScriptInput} input - The script.
+ * @return {Promise<void>}
+ */
+export async function save_script(input: IScriptInput): Promise<void> {
+  await invoke("save_script", { input });
+}
+
+/**
+ * Retrieves every saved script.
+ *
+ * @return {Promise<IScript[]>} The scripts, by name.
+ */
+export async function get_scripts(): Promise<IScript[]> {
+  const scripts = await invoke<IScript[]>("get_scripts");
+  return scripts;
+}
+
+/**
+ * Removes a script, detaching it from every experiment.
+ *
+ * @param {string} name - The script name.
+ * @return {Promise<void>}
+ */
+export async function delete_script(name: string): Promise<void> {
+  await invoke("delete_script", { name });
+}
+
+/**
+ * Sets (replaces) the scripts run on each inference of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @param {string[]} names - The script names, in the order they run.
+ * @return {Promise<void>}
+ */
+export async function set_experiment_scripts(
+  experimentUuid: string,
+  names: string[],
+): Promise<void> {
+  await invoke("set_experiment_scripts", { experimentUuid, names });
+}
+
+/**
+ * Retrieves the scripts of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<string[]>} The script names, in the order they run.
+ */
+export async function get_experiment_scripts(
+  experimentUuid: string,
+): Promise<string[]> {
+  const names = await invoke<string[]>("get_experiment_scripts", {
+    experimentUuid,
+  });
+  return names;
+}
+
+/**
+ * Creates a golden suite, or updates the one with the same name.
+ *
+ * @param {IGoldenSuiteInput} input - The suite and its thresholds.
+ * @return {Promise<void>}
+ */
+export async function save_golden_suite(
+  input: IGoldenSuiteInput,
+): Promise<void> {
+  await invoke("save_golden_suite", { input });
+}
+
+/**
+ * Retrieves every golden suite.
+ *
+ * @return {Promise<IGoldenSuite[]>} The suites, by name.
+ */
+export async function get_golden_suites(): Promise<IGoldenSuite[]> {
+  const suites = await invoke<IGoldenSuite[]>("get_golden_suites");
+  return suites;
+}
+
+/**
+ * Removes a golden suite and its baselines. Its runs are kept.
+ *
+ * @param {string} name - The suite name.
+ * @return {Promise<void>}
+ */
+export async function delete_golden_suite(name: string): Promise<void> {
+  await invoke("delete_golden_suite", { name });
+}
+
+/**
+ * Adds inferences of an experiment to a golden suite as baselines.
+ *
+ * @param {string} suiteName - The suite name.
+ * @param {string} experimentUuid - The experiment uuid.
+ * @param {string[]} inferenceIds - Successful inferences of the experiment.
+ * @return {Promise<number>} The number added, skipping the ones already in it.
+ */
+export async function add_golden_baselines(
+  suiteName: string,
+  experimentUuid: string,
+  inferenceIds: string[],
+): Promise<number> {
+  const added = await invoke<number>("add_golden_baselines", {
+    suiteName,
+    experimentUuid,
+    inferenceIds,
+  });
+  return added;
+}
+
+/**
+ * Retrieves the baselines of a golden suite.
+ *
+ * @param {string} suiteName - The suite name.
+ * @return {Promise<IGoldenBaseline[]>} The baselines, in the order added.
+ */
+export async function get_golden_baselines(
+  suiteName: string,
+): Promise<IGoldenBaseline[]> {
+  const baselines = await invoke<IGoldenBaseline[]>("get_golden_baselines", {
+    suiteName,
+  });
+  return baselines;
+}
+
+/**
+ * Removes a baseline from its golden suite.
+ *
+ * @param {number} id - The baseline id.
+ * @return {Promise<void>}
+ */
+export async function delete_golden_baseline(id: number): Promise<void> {
+  await invoke("delete_golden_baseline", { id });
+}
+
+/**
+ * Re-runs the baselines of a golden suite and compares the responses with
+ * them. The reruns are logged as a regression experiment.
+ *
+ * @param {IDefaultConfigs} config - The server to run on.
+ * @param {string} suiteName - The suite name.
+ * @param {string} [model] - Run every baseline on this model instead.
+ * @return {Promise<IRegressionRun>} The regression report.
+ */
+export async function run_golden_suite(
+  config: IDefaultConfigs,
+  suiteName: string,
+  model?: string,
+): Promise<IRegressionRun> {
+  const run = await invoke<IRegressionRun>("run_golden_suite", {
+    config,
+    suiteName,
+    model,
+  });
+  return run;
+}
+
+/**
+ * Retrieves the regression runs of a golden suite, or of every suite.
+ *
+ * @param {string} [suiteName] - The suite name.
+ * @return {Promise<IRegressionRun[]>} The runs, latest first.
+ */
+export async function get_regression_runs(
+  suiteName?: string,
+): Promise<IRegressionRun[]> {
+  const runs = await invoke<IRegressionRun[]>("get_regression_runs", {
+    suiteName,
+  });
+  return runs;
+}
+
+/**
+ * Re-runs inferences with the same seed and options and compares the
+ * outputs with the logged ones.
+ *
+ * @param {IDefaultConfigs} config - The server to run on.
+ * @param {string} experimentUuid - The experiment uuid.
+ * @param {string[]} inferenceIds - The inferences to verify.
+ * @param {number} [runs] - Reruns per inference, defaults to 3.
+ * @param {boolean} [concurrent] - Send the runs all at once.
+ * @return {Promise<IReproducibilityCheck[]>} One check per inference.
+ */
+export async function verify_reproducibility(
+  config: IDefaultConfigs,
+  experimentUuid: string,
+  inferenceIds: string[],
+  runs?: number,
+  concurrent?: boolean,
+): Promise<IReproducibilityCheck[]> {
+  const checks = await invoke<IReproducibilityCheck[]>(
+    "verify_reproducibility",
+    { config, experimentUuid, inferenceIds, runs, concurrent },
+  );
+  return checks;
+}
+
+/**
+ * Retrieves the reproducibility checks of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<IReproducibilityCheck[]>} The checks, latest first.
+ */
+export async function get_reproducibility_checks(
+  experimentUuid: string,
+): Promise<IReproducibilityCheck[]> {
+  const checks = await invoke<IReproducibilityCheck[]>(
+    "get_reproducibility_checks",
+    { experimentUuid },
+  );
+  return checks;
+}
+
+/**
+ * Reads a JSON, TOML or YAML experiment spec. Prompts given by slug are
+ * replaced by their text, and their library uuids are returned with the spec.
+ *
+ * @param {string} path - The spec file.
+ * @return {Promise<ILoadedExperimentSpec>} The spec.
+ */
+export async function load_experiment_spec(
+  path: string,
+): Promise<ILoadedExperimentSpec> {
+  const spec = await invoke<ILoadedExperimentSpec>("load_experiment_spec", {
+    path,
+  });
+  return spec;
+}
+
+/**
+ * Writes an experiment spec; the format is chosen from the file extension.
+ *
+ * @param {string} path - The spec file.
+ * @param {IExperimentSpec} spec - The spec.
+ * @return {Promise<void>}
+ */
+export async function save_experiment_spec(
+  path: string,
+  spec: IExperimentSpec,
+): Promise<void> {
+  await invoke("save_experiment_spec", { path, spec });
+}
+
+/**
+ * Starts a blind A/B comparison session over an experiment's responses.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<IComparisonSession>} The new session.
+ */
+export async function start_comparison_session(
+  experimentUuid: string,
+): Promise<IComparisonSession> {
+  const session = await invoke<IComparisonSession>(
+    "start_comparison_session",
+    { experimentUuid },
+  );
+  return session;
+}
+
+/**
+ * Retrieves the next pair of responses to vote on.
+ *
+ * @param {string} sessionUuid - The session uuid.
+ * @return {Promise<IBlindPair | null>} The pair, or null when every pair was voted.
+ */
+export async function next_comparison_pair(
+  sessionUuid: string,
+): Promise<IBlindPair | null> {
+  const pair = await invoke<IBlindPair | null>("next_comparison_pair", {
+    sessionUuid,
+  });
+  return pair;
+}
+
+/**
+ * Records which response of a pair was preferred.
+ *
+ * @param {string} sessionUuid - The session uuid.
+ * @param {string} leftId - The inference id shown on the left.
+ * @param {string} rightId - The inference id shown on the right.
+ * @param {TVoteWinner} winner - The preferred side, or a tie.
+ * @return {Promise<void>}
+ */
+export async function record_comparison_vote(
+  sessionUuid: string,
+  leftId: string,
+  rightId: string,
+  winner: TVoteWinner,
+): Promise<void> {
+  await invoke<void>("record_comparison_vote", {
+    sessionUuid,
+    leftId,
+    rightId,
+    winner,
+  });
+}
+
+/**
+ * Retrieves the comparison sessions of an experiment.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<IComparisonSession[]>} The sessions, newest first.
+ */
+export async function get_comparison_sessions(
+  experimentUuid: string,
+): Promise<IComparisonSession[]> {
+  const sessions = await invoke<IComparisonSession[]>(
+    "get_comparison_sessions",
+    { experimentUuid },
+  );
+  return sessions;
+}
+
+/**
+ * Ranks an experiment's model/parameter configurations from the votes
+ * of all its comparison sessions.
+ *
+ * @param {string} experimentUuid - The experiment uuid.
+ * @return {Promise<ILeaderboardEntry[]>} The ranking, best first.
+ */
+export async function comparison_leaderboard(
+  experimentUuid: string,
+): Promise<ILeaderboardEntry[]> {
+  const leaderboard = await invoke<ILeaderboardEntry[]>(
+    "comparison_leaderboard",
+    { experimentUuid },
+  );
+  return leaderboard;
+}
+
+/**
+ * Exports an experiment as one row per inference.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {string} path - The destination file.
+ * @param {TExportFormat} [format] - Defaults to the format matching the file extension.
+ * @return {Promise<number>} The number of exported rows.
+ */
+export async function export_experiment(
+  uuid: string,
+  path: string,
+  format?: TExportFormat,
+): Promise<number> {
+  const rows = await invoke<number>("export_experiment", {
+    uuid,
+    path,
+    format,
+  });
+  return rows;
+}
+
+/**
+ * Renders an experiment report as a self-contained HTML or Markdown file.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {string} path - The destination file.
+ * @param {TReportFormat} [format] - Defaults to the format matching the file extension.
+ * @return {Promise<void>}
+ */
+export async function generate_report(
+  uuid: string,
+  path: string,
+  format?: TReportFormat,
+): Promise<void> {
+  await invoke("generate_report", { uuid, path, format });
+}
+
+/**
+ * Renders a chart of an experiment's inferences as SVG or PNG.
+ *
+ * @param {string} uuid - The experiment uuid.
+ * @param {string} path - The destination file.
+ * @param {IChartSpec} spec - The chart kind and the fields on each axis.
+ * @param {TChartFormat} [format] - Defaults to the format matching the file extension.
+ * @return {Promise<void>}
+ */
+export async function generate_chart(
+  uuid: string,
+  path: string,
+  spec: IChartSpec,
+  format?: TChartFormat,
+): Promise<void> {
+  await invoke("generate_chart", { uuid, path, spec, format });
+}
+
+/**
+ * Imports an experiment log (of any past log version) into the history.
+ *
+ * @param {string} path - The experiment JSON file.
+ * @param {TConflictPolicy} policy - How to handle an experiment whose uuid already exists.
+ * @return {Promise<IExperimentImportReport>} The imported experiment and what was done with it.
+ */
+export async function import_experiment(
+  path: string,
+  policy: TConflictPolicy,
+): Promise<IExperimentImportReport> {
+  const report = await invoke<IExperimentImportReport>("import_experiment", {
+    path,
+    policy,
+  });
+  return report;
+}
+
+/**
+ * Loads a CSV or JSONL dataset whose rows supply values for prompt [placeholders].
+ *
+ * @param {string} path - The path to the dataset file.
+ * @return {Promise<IDataset>} The dataset columns and rows.
+ */
+export async function load_dataset(path: string): Promise<IDataset> {
+  const dataset = await invoke<IDataset>("load_dataset", { path });
+  return dataset;
+}
+
+/**
+ * Checks that every [placeholder] used in the prompts is a dataset column.
+ *
+ * @param {string} path - The path to the dataset file.
+ * @param {string[]} prompts - The prompt templates.
+ * @return {Promise<void>} Rejects with the list of missing placeholders.
+ */
+export async function validate_dataset_prompts(
+  path: string,
+  prompts: string[],
+): Promise<void> {
+  await invoke<void>("validate_dataset_prompts", { path, prompts });
+}
+
+/**
+ * Renders a prompt template (variables, conditionals, loops and includes of
+ * other prompts by slug) so it can be previewed.
  *
- * @return {Promise<IExperimentFile[]>} The list of experiment files retrieved from the server.
+ * @param {string} template - The prompt template.
+ * @param {Record<string, unknown>} variables - Optional values for template variables.
+ * @return {Promise<string>} The rendered prompt.
  */
-export async function get_experiments(): Promise<IExperimentFile[]> {
-  const experiments = await invoke<IExperimentFile[]>("get_experiments");
-  return experiments;
+export async function render_prompt(
+  template: string,
+  variables?: Record<string, unknown>,
+): Promise<string> {
+  const rendered = await invoke<string>("render_prompt", {
+    template,
+    variables,
+  });
+  return rendered;
+}
+
+/**
+ * Lists every stored version of a prompt, newest first.
+ *
+ * @param {string} uuid - The UUID of the prompt.
+ * @return {Promise<IPromptVersion[]>} The prompt's versions.
+ */
+export async function list_prompt_versions(
+  uuid: string,
+): Promise<IPromptVersion[]> {
+  const versions = await invoke<IPromptVersion[]>("list_prompt_versions", {
+    uuid,
+  });
+  return versions;
+}
+
+/**
+ * Returns a line diff between two versions of a prompt.
+ *
+ * @param {string} uuid - The UUID of the prompt.
+ * @param {number} fromVersion - The older version.
+ * @param {number} toVersion - The newer version.
+ * @return {Promise<IDiffLine[]>} The diff, line by line.
+ */
+export async function diff_prompt_versions(
+  uuid: string,
+  fromVersion: number,
+  toVersion: number,
+): Promise<IDiffLine[]> {
+  const diff = await invoke<IDiffLine[]>("diff_prompt_versions", {
+    uuid,
+    fromVersion,
+    toVersion,
+  });
+  return diff;
+}
+
+/**
+ * Restores the text of a previous prompt version (recorded as a new version).
+ *
+ * @param {string} uuid - The UUID of the prompt.
+ * @param {number} version - The version to restore.
+ * @return {Promise<void>}
+ */
+export async function restore_prompt_version(
+  uuid: string,
+  version: number,
+): Promise<void> {
+  await invoke<void>("restore_prompt_version", { uuid, version });
+}
+
+/**
+ * Searches the prompt library by text (name and body), tags and folder.
+ *
+ * @param {IPromptSearch} search - The search criteria.
+ * @return {Promise<IPrompt[]>} The matching prompts, best matches first.
+ */
+export async function search_prompts(search: IPromptSearch): Promise<IPrompt[]> {
+  const prompts = await invoke<IPrompt[]>("search_prompts", { search });
+  return prompts;
+}
+
+/**
+ * Replaces the tags of a prompt.
+ *
+ * @param {string} uuid - The UUID of the prompt.
+ * @param {string[]} tags - The new tags.
+ * @return {Promise<void>}
+ */
+export async function set_prompt_tags(
+  uuid: string,
+  tags: string[],
+): Promise<void> {
+  await invoke<void>("set_prompt_tags", { uuid, tags });
+}
+
+/**
+ * Retrieves every tag used in the prompt library, with usage counts.
+ *
+ * @return {Promise<ITagCount[]>} The tags.
+ */
+export async function get_prompt_tags(): Promise<ITagCount[]> {
+  const tags = await invoke<ITagCount[]>("get_prompt_tags");
+  return tags;
+}
+
+/**
+ * Retrieves every folder used in the prompt library.
+ *
+ * @return {Promise<string[]>} The folder paths.
+ */
+export async function get_prompt_folders(): Promise<string[]> {
+  const folders = await invoke<string[]>("get_prompt_folders");
+  return folders;
+}
+
+/**
+ * Exports the prompt library to a JSON or YAML bundle (format taken from the extension).
+ *
+ * @param {string} path - Where to write the bundle.
+ * @param {string[]} uuids - Optional. Export only these prompts.
+ * @return {Promise<number>} The number of exported prompts.
+ */
+export async function export_prompts(
+  path: string,
+  uuids?: string[],
+): Promise<number> {
+  const count = await invoke<number>("export_prompts", { path, uuids });
+  return count;
+}
+
+/**
+ * Imports a JSON or YAML prompt bundle.
+ *
+ * @param {string} path - The bundle file.
+ * @param {TConflictPolicy} policy - How to handle prompts whose uuid, name or slug already exist.
+ * @return {Promise<IPromptImportReport>} What was created, updated, renamed or skipped.
+ */
+export async function import_prompts(
+  path: string,
+  policy: TConflictPolicy,
+): Promise<IPromptImportReport> {
+  const report = await invoke<IPromptImportReport>("import_prompts", {
+    path,
+    policy,
+  });
+  return report;
 }
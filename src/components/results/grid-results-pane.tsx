@@ -54,41 +54,58 @@ export default function GridResultsPane() {
     if (formValues.experiment_uuid === "") return;
     setNoCompleted(0);
     const localIterations = [];
+    // without a dataset, each prompt runs once with no variables
+    const datasetRows: (Record<string, unknown> | undefined)[] =
+      formValues.dataset_rows.length > 0
+        ? formValues.dataset_rows
+        : [undefined];
     for (const model of formValues.models) {
-      for (const prompt of formValues.prompts) {
-        for (const temperature of formValues.temperatureList) {
-          for (const repeat_penalty of formValues.repeatPenaltyList) {
-            for (const top_k of formValues.topKList) {
-              for (const top_p of formValues.topPList) {
-                for (const repeat_last_n of formValues.repeatLastNList) {
-                  for (const tfs_z of formValues.tfsZList) {
-                    for (const mirostat of formValues.mirostatList) {
-                      for (const mirostat_tau of formValues.mirostatTauList) {
-                        for (const mirostat_eta of formValues.mirostatEtaList) {
-                          // loop over the number of generations
-                          for (
-                            let generation = 0;
-                            generation < formValues.generations;
-                            generation++
-                          ) {
-                            // set seed = generation to ensure results differ when temp > 0
-                            localIterations.push({
-                              experiment_uuid: formValues.experiment_uuid,
-                              model: model,
-                              system_prompt: formValues.system_prompt,
-                              prompt: prompt,
-                              temperature: temperature,
-                              repeat_penalty: repeat_penalty,
-                              top_k: top_k,
-                              top_p: top_p,
-                              repeat_last_n: repeat_last_n,
-                              tfs_z: tfs_z,
-                              mirostat: mirostat,
-                              mirostat_tau: mirostat_tau,
-                              mirostat_eta: mirostat_eta,
-                              generation: generation,
-                              seed: generation,
-                            });
+      for (const [promptIdx, prompt] of formValues.prompts.entries()) {
+        for (const system_prompt of formValues.system_prompts) {
+          for (const [rowIdx, row] of datasetRows.entries()) {
+            for (const temperature of formValues.temperatureList) {
+              for (const repeat_penalty of formValues.repeatPenaltyList) {
+                for (const top_k of formValues.topKList) {
+                  for (const top_p of formValues.topPList) {
+                    for (const repeat_last_n of formValues.repeatLastNList) {
+                      for (const tfs_z of formValues.tfsZList) {
+                        for (const mirostat of formValues.mirostatList) {
+                          for (const mirostat_tau of formValues.mirostatTauList) {
+                            for (const mirostat_eta of formValues.mirostatEtaList) {
+                              for (const think of formValues.thinkList) {
+                                // loop over the number of generations
+                                for (
+                                  let generation = 0;
+                                  generation < formValues.generations;
+                                  generation++
+                                ) {
+                                  // set seed = generation to ensure results differ when temp > 0
+                                  localIterations.push({
+                                    experiment_uuid: formValues.experiment_uuid,
+                                    model: model,
+                                    system_prompt: system_prompt,
+                                    prompt: prompt,
+                                    prompt_uuid:
+                                      formValues.prompt_uuids?.[promptIdx] ??
+                                      null,
+                                    temperature: temperature,
+                                    repeat_penalty: repeat_penalty,
+                                    top_k: top_k,
+                                    top_p: top_p,
+                                    repeat_last_n: repeat_last_n,
+                                    tfs_z: tfs_z,
+                                    mirostat: mirostat,
+                                    mirostat_tau: mirostat_tau,
+                                    mirostat_eta: mirostat_eta,
+                                    think: think,
+                                    generation: generation,
+                                    seed: generation,
+                                    dataset_row: row ? rowIdx : undefined,
+                                    variables: row,
+                                  });
+                                }
+                              }
+                            }
                           }
                         }
                       }
@@ -0,0 +1,86 @@
+import { IRatingInput } from "@/Interfaces";
+import {
+  clear_inference_rating,
+  set_inference_rating,
+} from "@/components/queries";
+import {
+  StarFilledIcon,
+  StarIcon,
+  ThickArrowDownIcon,
+  ThickArrowUpIcon,
+} from "@radix-ui/react-icons";
+import { useState } from "react";
+import { Button } from "../ui/button";
+import { Input } from "../ui/input";
+import { toast } from "../ui/use-toast";
+
+interface IProps {
+  experimentUuid: string;
+  inferenceId: string;
+}
+
+// Thumbs, 1-5 stars and a note for a single inference
+export default function InferenceRating(props: IProps) {
+  const { experimentUuid, inferenceId } = props;
+  const [rating, setRating] = useState<IRatingInput>({});
+
+  const save = async (next: IRatingInput) => {
+    setRating(next);
+    try {
+      if (!next.thumb && !next.rating && !next.note) {
+        await clear_inference_rating(experimentUuid, inferenceId);
+      } else {
+        await set_inference_rating(experimentUuid, inferenceId, next);
+      }
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not save the rating.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  const toggleThumb = (thumb: 1 | -1) =>
+    save({ ...rating, thumb: rating.thumb === thumb ? undefined : thumb });
+
+  const toggleStars = (stars: number) =>
+    save({ ...rating, rating: rating.rating === stars ? undefined : stars });
+
+  return (
+    <div className="flex items-center gap-1">
+      <Button variant="ghost" size="sm" onClick={() => toggleThumb(1)}>
+        <ThickArrowUpIcon
+          className={`h-4 w-4 ${rating.thumb === 1 ? "text-green-600" : "text-gray-400"}`}
+        />
+      </Button>
+      <Button variant="ghost" size="sm" onClick={() => toggleThumb(-1)}>
+        <ThickArrowDownIcon
+          className={`h-4 w-4 ${rating.thumb === -1 ? "text-red-600" : "text-gray-400"}`}
+        />
+      </Button>
+      <div className="mx-1 flex">
+        {[1, 2, 3, 4, 5].map((stars) => (
+          <button key={stars} onClick={() => toggleStars(stars)}>
+            {rating.rating && stars <= rating.rating ? (
+              <StarFilledIcon className="h-4 w-4 text-amber-500" />
+            ) : (
+              <StarIcon className="h-4 w-4 text-gray-400" />
+            )}
+          </button>
+        ))}
+      </div>
+      <Input
+        className="h-7 w-64 text-xs"
+        placeholder="Add a note..."
+        defaultValue={rating.note}
+        onBlur={(e) => {
+          if (e.target.value !== (rating.note ?? "")) {
+            save({ ...rating, note: e.target.value });
+          }
+        }}
+      />
+    </div>
+  );
+}
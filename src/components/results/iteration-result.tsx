@@ -7,17 +7,22 @@ import {
   formatInterval,
   tokensPerSecond,
 } from "@/lib";
-import { ClipboardCopyIcon, ReloadIcon } from "@radix-ui/react-icons";
+import {
+  ClipboardCopyIcon,
+  ReloadIcon,
+  UpdateIcon,
+} from "@radix-ui/react-icons";
 import { useQuery, useQueryClient } from "@tanstack/react-query";
 import { useAtom } from "jotai";
 import { useEffect, useState } from "react";
-import { get_inference } from "../queries";
+import { get_inference, verify_reproducibility } from "../queries";
 import { Button } from "../ui/button";
 import { CollapsibleItem } from "../ui/collapsible-item";
 import { CollapsibleText } from "../ui/collapsible-text";
 import { Separator } from "../ui/separator";
 import Spinner from "../ui/spinner";
 import { toast } from "../ui/use-toast";
+import InferenceRating from "./inference-rating";
 interface IProps {
   params: TParamIteration;
   iterationIndex: number;
@@ -53,6 +58,7 @@ export default function IterationResult(props: IProps) {
     mirostat_eta,
   } = params;
   const [enabled, setEnabled] = useState(false);
+  const [isVerifying, setIsVerifying] = useState(false);
   const [config, __] = useAtom(configAtom);
   const queryClient = useQueryClient();
 
@@ -82,9 +88,42 @@ export default function IterationResult(props: IProps) {
     setEnabled(false);
   };
 
+  // Re-runs the inference with the same seed and options
+  const verifyCurrentInference = async (inferenceId: string) => {
+    setIsVerifying(true);
+    try {
+      const [check] = await verify_reproducibility(
+        config,
+        params.experiment_uuid,
+        [inferenceId],
+      );
+      queryClient.invalidateQueries({
+        queryKey: ["get_reproducibility_checks", params.experiment_uuid],
+      });
+      const reproduced = check.identical_count === check.run_count;
+      toast({
+        variant: reproduced ? "success" : "destructive",
+        title: reproduced
+          ? `Reproduced in ${check.run_count} of ${check.run_count} runs.`
+          : `${check.run_count - check.identical_count} of ${check.run_count} runs differ.`,
+        description: `Ollama ${check.ollama_version ?? "?"}, ${check.model_digest?.slice(0, 19) ?? "unknown digest"}`,
+        duration: 5000,
+      });
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not verify the inference.",
+        description: String(err),
+        duration: 5000,
+      });
+    } finally {
+      setIsVerifying(false);
+    }
+  };
+
   // Updates the list of experiments
   useEffect(() => {
-    queryClient.refetchQueries({ queryKey: ["get_experiments"] });
+    queryClient.refetchQueries({ queryKey: ["list_experiments"] });
   }, [query.data]);
 
   return (
@@ -109,6 +148,12 @@ export default function IterationResult(props: IProps) {
             <div>mirostat: {mirostat}</div>
             <div>mirostat tau: {mirostat_tau}</div>
             <div>mirostat eta: {mirostat_eta}</div>
+            {params.think !== undefined && params.think !== null && (
+              <div>think: {params.think ? "on" : "off"}</div>
+            )}
+            {params.dataset_row !== undefined && (
+              <div>dataset row: {params.dataset_row + 1}</div>
+            )}
             <Separator className="my-2" />
             <div className=" whitespace-pre-wrap">
               prompt:{" "}
@@ -152,10 +197,27 @@ export default function IterationResult(props: IProps) {
               </div>
             )}
 
+            {query.data?.thinking && (
+              <div className="mt-3 whitespace-pre-wrap text-sm text-gray-500">
+                reasoning:{" "}
+                <CollapsibleText text={query.data.thinking} maxChars={120} />
+              </div>
+            )}
+
             <div className="mt-3 whitespace-pre-wrap text-cyan-600 dark:text-cyan-600">
               {query.data && query.data.response}
             </div>
 
+            {query.data && query.data.inference_id && (
+              <div className="mt-2">
+                <InferenceRating
+                  key={query.data.inference_id}
+                  experimentUuid={params.experiment_uuid}
+                  inferenceId={query.data.inference_id}
+                />
+              </div>
+            )}
+
             {/* results metadata */}
             {query.data && (
               <div className="my-3 flex items-start">
@@ -191,6 +253,24 @@ export default function IterationResult(props: IProps) {
                   </Button>
                 )}
 
+                {/* Re-run with the same seed and compare */}
+                {query.isFetched && query.data.inference_id && (
+                  <Button
+                    variant="ghost"
+                    className="mt-1"
+                    size="sm"
+                    title="Verify reproducibility"
+                    disabled={isVerifying}
+                    onClick={() =>
+                      verifyCurrentInference(query.data.inference_id)
+                    }
+                  >
+                    <UpdateIcon
+                      className={`text-grey-700 h-4 w-4 dark:text-gray-400 ${isVerifying ? "animate-spin" : ""}`}
+                    />
+                  </Button>
+                )}
+
                 <CollapsibleItem
                   triggerText="Results metadata"
                   defaultOpen={expandMetadata}
@@ -212,6 +292,11 @@ export default function IterationResult(props: IProps) {
                         )}
                       </div>
                       <div>Eval Count: {query.data.eval_count} tokens</div>
+                      {!!query.data.reasoning_tokens && (
+                        <div>
+                          Reasoning: ~{query.data.reasoning_tokens} tokens
+                        </div>
+                      )}
                       <div>
                         Eval Duration:{" "}
                         {formatInterval(
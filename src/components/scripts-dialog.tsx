@@ -0,0 +1,168 @@
+import { CodeIcon, CrossCircledIcon } from "@radix-ui/react-icons";
+
+import { Button } from "@/components/ui/button";
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogFooter,
+  DialogHeader,
+  DialogTitle,
+  DialogTrigger,
+} from "@/components/ui/dialog";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Textarea } from "@/components/ui/textarea";
+import { toast } from "@/components/ui/use-toast";
+
+import { IScript, IScriptInput } from "@/Interfaces";
+import { delete_script, get_scripts, save_script } from "@/components/queries";
+import { useQuery } from "@tanstack/react-query";
+import { useState } from "react";
+import { Tooltip, TooltipContent, TooltipTrigger } from "./ui/tooltip";
+
+const EMPTY_SCRIPT: IScriptInput = {
+  name: "",
+  description: "",
+  source: `fn pre_inference(params) {
+    params
+}
+
+fn post_inference(response, params) {
+    response
+}
+`,
+};
+
+export function ScriptsDialog() {
+  const [open, setOpen] = useState(false);
+  const [input, setInput] = useState<IScriptInput>(EMPTY_SCRIPT);
+
+  const query = useQuery<IScript[]>({
+    queryKey: ["get_scripts"],
+    queryFn: get_scripts,
+    enabled: open,
+  });
+
+  const update = (changes: Partial<IScriptInput>) =>
+    setInput({ ...input, ...changes });
+
+  const handleSave = async () => {
+    try {
+      await save_script(input);
+      query.refetch();
+      setInput(EMPTY_SCRIPT);
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not save the script.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  const handleDelete = async (name: string) => {
+    try {
+      await delete_script(name);
+      query.refetch();
+    } catch (err) {
+      toast({
+        variant: "destructive",
+        title: "Could not delete the script.",
+        description: String(err),
+        duration: 5000,
+      });
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={setOpen}>
+      <Tooltip>
+        <TooltipTrigger asChild>
+          <DialogTrigger asChild>
+            <Button variant="transparentDark" size="icon">
+              <CodeIcon className="h-5 w-5" />
+            </Button>
+          </DialogTrigger>
+        </TooltipTrigger>
+        <TooltipContent>Scripts</TooltipContent>
+      </Tooltip>
+      <DialogContent className="sm:max-w-[700px]">
+        <DialogHeader>
+          <DialogTitle>Scripts</DialogTitle>
+          <DialogDescription>
+            Rhai scripts attached to experiments. pre_inference(params) returns
+            the parameters to send, with the rendered prompt.
+            post_inference(response, params) returns the response to log, and
+            can set response.scores to a map of scores from 0 to 1. Scripts
+            can call regex_match, regex_replace and regex_captures.
+          </DialogDescription>
+        </DialogHeader>
+
+        <div className="flex flex-col gap-1 text-sm">
+          {query.data?.length === 0 && (
+            <div className="text-gray-500">No scripts yet.</div>
+          )}
+          {query.data?.map((script) => (
+            <div
+              key={script.name}
+              className="flex items-center justify-between gap-2"
+            >
+              <button
+                className="truncate text-left"
+                onClick={() =>
+                  setInput({
+                    name: script.name,
+                    description: script.description,
+                    source: script.source,
+                  })
+                }
+              >
+                <span className="font-bold">{script.name}</span>
+                {script.description && `: ${script.description}`}
+              </button>
+              <Button
+                variant="ghost"
+                size="icon"
+                onClick={() => handleDelete(script.name)}
+              >
+                <CrossCircledIcon className="h-4 w-4 text-red-500" />
+              </Button>
+            </div>
+          ))}
+        </div>
+
+        <div className="grid grid-cols-[120px_1fr] items-center gap-3">
+          <Label htmlFor="script-name">Name</Label>
+          <Input
+            id="script-name"
+            placeholder="e.g.: strip-thinking"
+            value={input.name}
+            onChange={(e) => update({ name: e.target.value })}
+          />
+          <Label htmlFor="script-description">Description</Label>
+          <Input
+            id="script-description"
+            placeholder="Optional"
+            value={input.description}
+            onChange={(e) => update({ description: e.target.value })}
+          />
+        </div>
+        <Textarea
+          className="h-64 font-mono text-xs"
+          spellCheck={false}
+          value={input.source}
+          onChange={(e) => update({ source: e.target.value })}
+        />
+
+        <DialogFooter>
+          <Button variant="secondary" onClick={() => setOpen(false)}>
+            Close
+          </Button>
+          <Button onClick={handleSave}>Save script</Button>
+        </DialogFooter>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -90,3 +90,11 @@ export function isCommaDelimitedList(input: string): boolean {
     return !isNaN(parseFloat(val.trim())) && !/\d\s+\d/.test(val.trim());
   });
 }
+
+// Names the system prompt of a combination, shortened, when it has one
+export function systemPromptLabel(systemPrompt: string): string {
+  const text = systemPrompt.trim();
+  if (!text) return "";
+  const short = text.length > 24 ? `${text.slice(0, 24)}...` : text;
+  return `, system "${short}"`;
+}